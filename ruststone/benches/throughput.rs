@@ -0,0 +1,94 @@
+//! How many game ticks a second the interpreter and the compiled backend get through on a grid
+//! of clocks that never settle. Run it with `cargo bench --bench throughput`, optionally followed
+//! by `-- <clocks across> <ticks>`.
+
+use std::{env, hint::black_box, time::Instant};
+
+use ruststone::{
+    vec3::{Direction, Vec3},
+    voxels::Voxel,
+    world::World,
+    RunOutcome,
+};
+
+/// A torch on the side of a block, with dust leading from it around to a repeater that powers the
+/// block, which turns the torch off, and so on. Takes up a 3 by 3 square from `at`.
+fn clock(world: &mut World, at: Vec3) {
+    world[at] = Voxel::stone().voxel();
+    world[at + Vec3(1, 0, 0)] = Voxel::torch().facing(Direction::East).unwrap().voxel();
+    world[at + Vec3(0, 0, 1)] = Voxel::repeater()
+        .facing(Direction::North)
+        .unwrap()
+        .delay(2)
+        .unwrap()
+        .voxel();
+    world[at + Vec3(0, -1, 1)] = Voxel::stone().voxel();
+
+    for offset in [
+        Vec3(2, 0, 0),
+        Vec3(2, 0, 1),
+        Vec3(2, 0, 2),
+        Vec3(1, 0, 2),
+        Vec3(0, 0, 2),
+    ] {
+        world[at + offset + Vec3(0, -1, 0)] = Voxel::stone().voxel();
+        world[at + offset] = Voxel::dust().voxel();
+    }
+}
+
+fn clocks(across: i32) -> World {
+    let mut world = World::new();
+
+    for x in 0..across {
+        for z in 0..across {
+            clock(&mut world, Vec3(x * 4, 1, z * 4));
+        }
+    }
+
+    world
+}
+
+fn report(name: &str, ticks: u64, started: Instant) {
+    let seconds = started.elapsed().as_secs_f64();
+    println!(
+        "{name:>11}: {ticks} ticks in {seconds:.3}s, {:.0} ticks/s",
+        ticks as f64 / seconds
+    );
+}
+
+fn main() {
+    // Cargo passes `--bench` along, which isn't one of ours.
+    let args: Vec<u64> = env::args()
+        .skip(1)
+        .filter_map(|arg| arg.parse().ok())
+        .collect();
+    let across = args.first().map_or(32, |&across| across as i32);
+    let ticks = args.get(1).copied().unwrap_or(2000);
+
+    println!("{} clocks", across * across);
+
+    let mut interpreted = clocks(across);
+    interpreted.set_max_threads(1);
+    interpreted.build();
+    let started = Instant::now();
+    let outcome = interpreted.run_for(ticks);
+    report("interpreted", ticks, started);
+    assert_eq!(outcome, RunOutcome::BudgetExhausted, "the clocks settled");
+
+    let mut parallel = clocks(across);
+    parallel.set_max_threads(0);
+    parallel.build();
+    let started = Instant::now();
+    parallel.run_for(ticks);
+    report("parallel", ticks, started);
+
+    let mut compiled = clocks(across).compile();
+    let started = Instant::now();
+    while compiled.tick() < ticks {
+        assert!(!compiled.is_done(), "the clocks settled");
+        compiled.step();
+    }
+    report("compiled", ticks, started);
+
+    black_box((interpreted, parallel, compiled));
+}
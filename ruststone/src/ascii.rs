@@ -0,0 +1,202 @@
+//! A layered ASCII world format, where every y layer is drawn as a grid seen from above:
+//!
+//! ```text
+//! // An inverter.
+//! origin 0 0 0
+//! layer 0
+//! ##.
+//! layer 1
+//! *+<
+//! ```
+//!
+//! Within a layer, every row is a z coordinate and every column an x coordinate, counting up from
+//! the origin. Layers are counted up from the origin too. The default legend is:
+//!
+//! | char | voxel |
+//! |------|-------|
+//! | `.` or space | air |
+//! | `#` | stone |
+//! | `*` | torch, standing on the block below |
+//! | `^` `v` `<` `>` | torch, on the side of the block it points at |
+//! | `+` | dust |
+//! | `b` | copper bulb |
+//! | `c` | crafter |
+//! | `n` | note block |
+//! | `p` | piston |
+//! | `d` | dropper |
+//! | `s` | dispenser |
+//!
+//! A line like `legend N note_block:12` adds to it, with the voxel written like it is in the
+//! [`text`](crate::text) format. Repeaters face four ways with four delays, so they are always
+//! drawn through a line like `legend R repeater:south:2`. Lines that start with `//` are comments,
+//! and blank lines are skipped, so a row of nothing but air has to be drawn with dots.
+
+use std::collections::BTreeMap;
+
+use crate::{
+    text::{self, ParseError},
+    vec3::Vec3,
+    world::World,
+};
+
+const DEFAULT_LEGEND: [(char, &str); 15] = [
+    ('.', "air"),
+    (' ', "air"),
+    ('#', "stone"),
+    ('*', "torch"),
+    ('^', "torch:north"),
+    ('v', "torch:south"),
+    ('<', "torch:east"),
+    ('>', "torch:west"),
+    ('+', "dust"),
+    ('b', "copper_bulb"),
+    ('c', "crafter"),
+    ('n', "note_block"),
+    ('p', "piston"),
+    ('d', "dropper"),
+    ('s', "dispenser"),
+];
+
+/// Characters handed out to voxels that aren't in the default legend, before any others.
+const SPARE: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+/// Every character that can be handed out to a voxel that isn't in the default legend. Once the
+/// spare ones run out, it goes on past ASCII, with letters and digits first.
+fn spare_chars() -> impl Iterator<Item = char> {
+    let beyond_ascii = (0xC0..=u32::from(char::MAX)).filter_map(char::from_u32);
+    let (alphanumeric, others) = (beyond_ascii.clone(), beyond_ascii);
+
+    SPARE
+        .chars()
+        .chain(alphanumeric.filter(|c| c.is_alphanumeric()))
+        .chain(others.filter(|c| !c.is_alphanumeric() && !c.is_whitespace() && !c.is_control()))
+}
+
+fn default_legend() -> BTreeMap<char, String> {
+    DEFAULT_LEGEND
+        .iter()
+        .map(|&(c, spec)| (c, String::from(spec)))
+        .collect()
+}
+
+/// Reads a world written in the format described at the top of this module.
+pub fn parse_world(text: &str) -> Result<World, ParseError> {
+    let mut world = World::new();
+    let mut legend = default_legend();
+    let mut origin = Vec3(0, 0, 0);
+    // The layer being drawn and the row up next.
+    let mut layer: Option<(i32, i32)> = None;
+
+    for (idx, line) in text.lines().enumerate() {
+        let line_no = idx + 1;
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() || trimmed.starts_with("//") {
+            continue;
+        }
+
+        let mut words = trimmed.split_whitespace();
+        match words.next() {
+            Some("origin") => {
+                origin = text::parse_vec3(line_no, &mut words)?;
+                continue;
+            }
+            Some("legend") => {
+                let (c, spec) = match (words.next(), words.next(), words.next()) {
+                    (Some(c), Some(spec), None) if c.chars().count() == 1 => {
+                        (c.chars().next().unwrap(), spec)
+                    }
+                    _ => {
+                        return Err(ParseError::new(
+                            line_no,
+                            "a legend is a character followed by a voxel",
+                        ))
+                    }
+                };
+
+                text::parse_voxel(spec).map_err(|message| ParseError::new(line_no, message))?;
+                legend.insert(c, String::from(spec));
+                continue;
+            }
+            Some("layer") => {
+                let y = words
+                    .next()
+                    .and_then(|y| y.parse::<i32>().ok())
+                    .ok_or_else(|| ParseError::new(line_no, "a layer needs its y"))?;
+                layer = Some((y, 0));
+                continue;
+            }
+            _ => (),
+        }
+
+        let Some((y, z)) = layer.as_mut() else {
+            return Err(ParseError::new(line_no, "rows have to come after a layer"));
+        };
+
+        for (x, c) in line.trim_end().chars().enumerate() {
+            let spec = legend
+                .get(&c)
+                .ok_or_else(|| ParseError::new(line_no, format!("{c:?} is not in the legend")))?;
+            let voxel = text::parse_voxel(spec).unwrap();
+
+            if !voxel.is_air() {
+                world[origin + Vec3(x as i32, *y, *z)] = voxel;
+            }
+        }
+
+        *z += 1;
+    }
+
+    Ok(world)
+}
+
+/// Writes every voxel of `world` that isn't air, with the origin at its smallest corner.
+pub fn format_world(world: &World) -> String {
+    let Some(bounds) = world.bounds() else {
+        return String::from("origin 0 0 0\n");
+    };
+    let Vec3(min_x, min_y, min_z) = bounds.min();
+    let Vec3(max_x, max_y, max_z) = bounds.max();
+
+    let mut chars: BTreeMap<String, char> = DEFAULT_LEGEND
+        .iter()
+        .filter(|&&(c, _)| c != ' ')
+        .map(|&(c, spec)| (String::from(spec), c))
+        .collect();
+    let mut spare = spare_chars();
+    let mut legend = String::new();
+
+    let mut layers = String::new();
+    for y in min_y..=max_y {
+        layers.push_str(&format!("layer {}\n", y - min_y));
+
+        for z in min_z..=max_z {
+            let mut row = String::new();
+
+            for x in min_x..=max_x {
+                let spec = text::format_voxel(&world[Vec3(x, y, z)]);
+
+                let c = *chars.entry(spec).or_insert_with_key(|spec| {
+                    let c = spare
+                        .next()
+                        .expect("there are more kinds of voxels than characters");
+                    legend.push_str(&format!("legend {c} {spec}\n"));
+                    c
+                });
+
+                row.push(c);
+            }
+
+            // Blank lines are skipped, so an empty row keeps one of its dots.
+            let row = match row.trim_end_matches('.') {
+                "" => ".",
+                row => row,
+            };
+
+            layers.push_str(row);
+            layers.push('\n');
+        }
+    }
+
+    format!("origin {min_x} {min_y} {min_z}\n{legend}{layers}")
+}
@@ -0,0 +1,261 @@
+//! Building worlds in code, by moving a cursor around like a turtle and leaving voxels behind:
+//!
+//! ```
+//! # use ruststone::{builder::Builder, vec3::Direction, voxels::Voxel};
+//! let mut builder = Builder::new();
+//! builder
+//!     .place(Voxel::stone().voxel())
+//!     .torch_on(Direction::Up)
+//!     .step(Direction::Up, 1)
+//!     .port("source")
+//!     .step(Direction::West, 1)
+//!     .face(Direction::West)
+//!     .lay_dust(10)
+//!     .port("end");
+//!
+//! let world = builder.into_world();
+//! ```
+//!
+//! Anything that builds with a builder is a [`Module`], closures included, and can be placed
+//! whole into another builder, turned to face the way the cursor does. Its named positions come
+//! along as ports of the builder it was placed into.
+
+use crate::{
+    structure::Structure,
+    vec3::{Direction, Region, Rotation, Vec3},
+    voxels::Voxel,
+    world::World,
+};
+
+/// Something that lays itself out with a builder, facing north, and names its ports on it.
+pub trait Module {
+    fn build(&self, builder: &mut Builder);
+}
+
+impl<F: Fn(&mut Builder)> Module for F {
+    fn build(&self, builder: &mut Builder) {
+        self(builder)
+    }
+}
+
+pub struct Builder {
+    world: World,
+    cursor: Vec3,
+    facing: Direction,
+    ports: Vec<(String, Vec3)>,
+}
+
+impl Default for Builder {
+    fn default() -> Builder {
+        Builder::new()
+    }
+}
+
+impl Builder {
+    /// Starts on an empty world, at the origin, facing north.
+    pub fn new() -> Builder {
+        Builder::on(World::new())
+    }
+
+    /// Starts on `world`, at the origin, facing north.
+    pub fn on(world: World) -> Builder {
+        Builder {
+            world,
+            cursor: Vec3(0, 0, 0),
+            facing: Direction::North,
+            ports: Vec::new(),
+        }
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn into_world(self) -> World {
+        self.world
+    }
+
+    pub fn cursor(&self) -> Vec3 {
+        self.cursor
+    }
+
+    pub fn facing(&self) -> Direction {
+        self.facing
+    }
+
+    /// Moves the cursor to `vec3`.
+    pub fn at(&mut self, vec3: Vec3) -> &mut Self {
+        self.cursor = vec3;
+        self
+    }
+
+    /// Faces the cursor `direction`, which is the way it goes forward and lays voxels.
+    pub fn face(&mut self, direction: Direction) -> &mut Self {
+        self.facing = direction;
+        self
+    }
+
+    pub fn turn(&mut self, rotation: Rotation) -> &mut Self {
+        self.face(self.facing.rotate_y(rotation))
+    }
+
+    /// Moves the cursor `count` voxels in `direction`, without turning it.
+    pub fn step(&mut self, direction: Direction, count: i32) -> &mut Self {
+        let Vec3(x, y, z) = direction.to_vec3();
+        self.at(self.cursor + Vec3(x * count, y * count, z * count))
+    }
+
+    /// Moves the cursor `count` voxels the way it faces.
+    pub fn forward(&mut self, count: i32) -> &mut Self {
+        self.step(self.facing, count)
+    }
+
+    /// Puts `voxel` at the cursor, whatever was there before.
+    pub fn place(&mut self, voxel: Voxel) -> &mut Self {
+        self.world[self.cursor] = voxel;
+        self
+    }
+
+    /// Puts `count` of `voxel` in a line from the cursor the way it faces, and leaves the cursor
+    /// on the last one.
+    pub fn lay(&mut self, voxel: Voxel, count: u32) -> &mut Self {
+        for idx in 0..count {
+            if idx > 0 {
+                self.forward(1);
+            }
+
+            self.place(voxel.clone());
+        }
+
+        self
+    }
+
+    /// Lays `count` dust like [`Builder::lay`] does, putting stone under every one of them that
+    /// has nothing solid to sit on.
+    pub fn lay_dust(&mut self, count: u32) -> &mut Self {
+        for idx in 0..count {
+            if idx > 0 {
+                self.forward(1);
+            }
+
+            let below = self.cursor.down();
+            if !self.world[below].is_solid() {
+                self.world[below] = Voxel::stone().voxel();
+            }
+
+            self.place(Voxel::dust().voxel());
+        }
+
+        self
+    }
+
+    /// Puts a torch on the `side` of the block at the cursor, standing on top of it if the side
+    /// is up. The cursor stays on the block.
+    ///
+    /// # Panics
+    ///
+    /// If `side` is down, since torches can't hang under a block.
+    pub fn torch_on(&mut self, side: Direction) -> &mut Self {
+        let torch = match side {
+            Direction::Up => Voxel::torch(),
+            Direction::Down => panic!("a torch can't hang under the block at {}", self.cursor),
+            side => Voxel::torch()
+                .facing(side.opposite())
+                .expect("every side but up and down is horizontal"),
+        };
+
+        self.world[self.cursor.offset(side)] = torch.voxel();
+        self
+    }
+
+    /// Puts a repeater at the cursor that powers whatever is `facing` of it, `delay` redstone
+    /// ticks after it is powered.
+    ///
+    /// # Panics
+    ///
+    /// If `facing` isn't horizontal, or `delay` isn't from 1 to 4.
+    pub fn repeater(&mut self, facing: Direction, delay: u8) -> &mut Self {
+        let repeater = Voxel::repeater()
+            .facing(facing)
+            .and_then(|repeater| repeater.delay(delay))
+            .unwrap_or_else(|error| panic!("{error}"));
+
+        self.place(repeater.voxel())
+    }
+
+    /// Names the position of the cursor.
+    pub fn port(&mut self, name: &str) -> &mut Self {
+        self.ports.retain(|(n, _)| n != name);
+        self.ports.push((String::from(name), self.cursor));
+        self
+    }
+
+    /// The position named `name`.
+    ///
+    /// # Panics
+    ///
+    /// If there is no port called `name`.
+    pub fn port_at(&self, name: &str) -> Vec3 {
+        self.ports
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|&(_, vec3)| vec3)
+            .unwrap_or_else(|| panic!("there is no port called {name}"))
+    }
+
+    /// Every named position, in the order they were named.
+    pub fn ports(&self) -> impl Iterator<Item = (&str, Vec3)> {
+        self.ports.iter().map(|(name, vec3)| (name.as_str(), *vec3))
+    }
+
+    /// Builds `module` on its own, turns it from north to the way the cursor faces, and pastes it
+    /// with its lowest corner at the cursor, replacing whatever was in the way. Its ports become
+    /// ports of this builder, called `name.port`. The cursor stays where it is.
+    ///
+    /// # Panics
+    ///
+    /// If the cursor faces up or down.
+    pub fn module(&mut self, name: &str, module: &impl Module) -> &mut Self {
+        assert!(
+            self.facing.is_horizontal(),
+            "modules can't be turned to face {}",
+            self.facing
+        );
+
+        let mut builder = Builder::new();
+        module.build(&mut builder);
+
+        let mut structure = builder.into_structure();
+        let mut facing = Direction::North;
+        while facing != self.facing {
+            structure = structure.rotate_y(Rotation::Clockwise);
+            facing = facing.rotate_y(Rotation::Clockwise);
+        }
+
+        self.world.paste(&structure, self.cursor);
+        for (port, vec3) in structure.ports() {
+            self.ports
+                .push((format!("{name}.{port}"), self.cursor + vec3));
+        }
+
+        self
+    }
+
+    /// Everything built so far along with the ports, relative to the lowest corner of the region
+    /// around both.
+    pub fn into_structure(self) -> Structure {
+        let ports = self.ports.iter().map(|&(_, vec3)| vec3);
+        let region = match (self.world.bounds(), Region::around(ports)) {
+            (Some(bounds), Some(ports)) => bounds.union(ports),
+            (bounds, ports) => bounds
+                .or(ports)
+                .unwrap_or(Region::new(self.cursor, self.cursor)),
+        };
+
+        self.ports
+            .iter()
+            .fold(self.world.copy(region), |structure, (name, vec3)| {
+                structure.with_port(name, *vec3 - region.min())
+            })
+    }
+}
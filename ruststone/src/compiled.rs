@@ -0,0 +1,590 @@
+//! A compiled backend for running a [`World`] fast.
+//!
+//! The interpreter walks the [`RedstoneArena`] and allocates the consequents of every dispatch.
+//! Compiling lowers the arena into flat arrays instead: every node is an opcode with spans into
+//! shared edge arrays, its state lives in one array per field, and dispatching never allocates.
+//! Dust works out its power straight from the weighted edges to its sources rather than from the
+//! dust around it. A change still travels down a dust chain one dust at a time, since that is the
+//! order the interpreter dispatches in. Graphs that have nothing to dispatch keep no edges at all.
+//!
+//! The compiled world dispatches in the same order as the interpreter, so the two agree on every
+//! frame, not just on where they settle.
+//!
+//! That agreement is why dust chains aren't folded into single weighted edges, and why nodes
+//! without outputs aren't pruned. The order in which a chain is walked decides the order in which
+//! the torches and repeaters along it are scheduled, and two of those dispatched in the same frame
+//! can read each other's changes. A folded chain dispatches them in another order, so it settles
+//! in the same place but can differ on the frames on the way there. A pruned node would have to
+//! be worked out whenever it is read, and stone never tells the dust next to it that it changed.
+//! Both are left out until the frames in between stop mattering.
+//!
+//! [`World`]: crate::world::World
+
+use std::{
+    collections::VecDeque,
+    hash::{Hash, Hasher},
+    ops::Range,
+};
+
+use fnv::{FnvHashMap, FnvHasher};
+
+use crate::{
+    dispatch::RedstoneDispatch,
+    dispatch::{Budget, Cycles, Frame, RunOutcome},
+    redstate::Redstate,
+    vec3::Vec3,
+    world::NoteEvent,
+    CrafterSlot, Instrument, MechanismKind, RedstoneArena, RedstoneGraph, RedstoneId, RedstoneNode,
+};
+
+const NONE: u32 = u32::MAX;
+
+#[derive(Clone, Copy)]
+enum Op {
+    Torch,
+    Dust,
+    Block,
+    Repeater,
+    CopperBulb,
+    /// Indexes into the crafter slots.
+    Crafter(u32),
+    NoteBlock(Instrument, u8),
+    Mechanism(MechanismKind),
+}
+
+#[derive(Clone, Copy)]
+struct Span(u32, u32);
+
+impl Span {
+    const EMPTY: Span = Span(0, 0);
+
+    fn range(self) -> Range<usize> {
+        self.0 as usize..self.1 as usize
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Node {
+    op: Op,
+    offset: u8,
+    /// Into `edges`. For dust, into `weighted` instead.
+    inputs: Span,
+    /// Into `edges`. The quasi-connected inputs of a mechanism, or the locks of a repeater.
+    side_inputs: Span,
+    /// Into `edges`.
+    outputs: Span,
+}
+
+#[derive(Clone, Copy)]
+struct Pending {
+    node: u32,
+    cause: u32,
+    /// The frame at which this becomes dispatchable.
+    due: u64,
+}
+
+#[derive(Clone)]
+pub struct CompiledWorld {
+    nodes: Vec<Node>,
+    edges: Vec<u32>,
+    weighted: Vec<(u8, u32)>,
+
+    power: Vec<u8>,
+    forced: Vec<bool>,
+    /// Whether a copper bulb, crafter or note block was powered when it was last dispatched.
+    latched: Vec<bool>,
+    lit: Vec<bool>,
+    directly_powered: Vec<bool>,
+    quasi_powered: Vec<bool>,
+    active: Vec<bool>,
+    fired: Vec<u32>,
+    slots: Vec<[CrafterSlot; 9]>,
+    played: Vec<(u64, u32)>,
+
+    positions: FnvHashMap<Vec3, u32>,
+
+    frame: u64,
+    /// Every node hashed on its own, all XORed together. Only the nodes that get dispatched are
+    /// hashed again.
+    state: u64,
+    queue: VecDeque<Pending>,
+    /// A ring of buckets, one per frame that can still have something due. A bucket keeps the
+    /// order in which its dispatchables were deferred.
+    buckets: Vec<VecDeque<Pending>>,
+    dispatches: u64,
+}
+
+impl CompiledWorld {
+    pub(crate) fn lower(
+        arena: &RedstoneArena,
+        rgs: &[RedstoneGraph],
+        redstones: &FnvHashMap<Vec3, RedstoneId>,
+    ) -> CompiledWorld {
+        let len = arena.len();
+
+        let mut compiled = CompiledWorld {
+            nodes: Vec::with_capacity(len),
+            edges: Vec::new(),
+            weighted: Vec::new(),
+            power: Vec::with_capacity(len),
+            forced: Vec::with_capacity(len),
+            latched: vec![false; len],
+            lit: vec![false; len],
+            directly_powered: vec![false; len],
+            quasi_powered: vec![false; len],
+            active: vec![false; len],
+            fired: vec![0; len],
+            slots: Vec::new(),
+            played: Vec::new(),
+            positions: redstones
+                .iter()
+                .map(|(&vec3, &redstone)| (vec3, redstone.index() as u32))
+                .collect(),
+            frame: 0,
+            state: 0,
+            queue: VecDeque::new(),
+            buckets: Vec::new(),
+            dispatches: 0,
+        };
+
+        // A graph that has nothing to dispatch never changes, so none of its edges are needed.
+        let mut live = vec![false; len];
+        for rg in rgs.iter().filter(|rg| rg.seeds(arena).next().is_some()) {
+            for &redstone in rg.redstones() {
+                live[redstone.index()] = true;
+            }
+        }
+
+        for redstone in arena.ids() {
+            let node = compiled.lower_node(arena, redstone, live[redstone.index()]);
+            compiled.nodes.push(node);
+        }
+
+        let max_offset = compiled.nodes.iter().map(|n| n.offset).max().unwrap_or(0);
+        compiled.buckets = vec![VecDeque::new(); max_offset as usize + 1];
+
+        for rg in rgs {
+            for (redstone, cause, due) in rg.seeds(arena) {
+                compiled.queue.push_back(Pending {
+                    node: redstone.index() as u32,
+                    cause: cause.map_or(NONE, |cause| cause.index() as u32),
+                    due: due.0,
+                });
+            }
+        }
+
+        compiled
+    }
+
+    fn span<I: IntoIterator<Item = RedstoneId>>(&mut self, ids: I) -> Span {
+        let start = self.edges.len() as u32;
+        self.edges
+            .extend(ids.into_iter().map(|id| id.index() as u32));
+        Span(start, self.edges.len() as u32)
+    }
+
+    fn lower_node(&mut self, arena: &RedstoneArena, redstone: RedstoneId, live: bool) -> Node {
+        let redstone = &arena[redstone];
+        let idx = self.power.len();
+
+        self.power.push(redstone.redstate().get_power());
+        self.forced.push(redstone.redstate().is_forced());
+
+        let op = match redstone.node() {
+            RedstoneNode::Torch(..) => Op::Torch,
+            RedstoneNode::Dust(..) => Op::Dust,
+            RedstoneNode::Block(..) => Op::Block,
+            RedstoneNode::Repeater(..) => Op::Repeater,
+            RedstoneNode::CopperBulb(bulb) => {
+                self.latched[idx] = bulb.powered.get();
+                self.lit[idx] = bulb.is_lit();
+                Op::CopperBulb
+            }
+            RedstoneNode::Crafter(crafter) => {
+                self.latched[idx] = crafter.powered.get();
+                self.fired[idx] = crafter.crafted();
+                self.slots.push(crafter.slots());
+                Op::Crafter(self.slots.len() as u32 - 1)
+            }
+            RedstoneNode::NoteBlock(note_block) => {
+                self.latched[idx] = note_block.powered.get();
+                self.played.extend(
+                    note_block
+                        .played()
+                        .into_iter()
+                        .map(|tick| (tick, idx as u32)),
+                );
+                Op::NoteBlock(note_block.instrument(), note_block.pitch())
+            }
+            RedstoneNode::Mechanism(mechanism) => {
+                self.directly_powered[idx] = mechanism.is_directly_powered();
+                self.quasi_powered[idx] = mechanism.is_quasi_powered();
+                self.active[idx] = mechanism.is_active();
+                self.fired[idx] = mechanism.fired();
+                Op::Mechanism(mechanism.kind())
+            }
+        };
+
+        let mut node = Node {
+            op,
+            offset: redstone.dispatch_frame_offset().0 as u8,
+            inputs: Span::EMPTY,
+            side_inputs: Span::EMPTY,
+            outputs: Span::EMPTY,
+        };
+
+        if !live {
+            return node;
+        }
+
+        match redstone.node() {
+            RedstoneNode::Torch(torch) => {
+                node.inputs = self.span(torch.incoming);
+                node.outputs = self.span(torch.outgoing.iter().copied());
+            }
+            RedstoneNode::Dust(dust) => {
+                let start = self.weighted.len() as u32;
+                self.weighted.extend(
+                    dust.sources
+                        .iter()
+                        .map(|&(w, source)| (w, source.index() as u32)),
+                );
+                node.inputs = Span(start, self.weighted.len() as u32);
+                node.outputs = self.span(dust.neighbors.iter().copied());
+            }
+            RedstoneNode::Block(block) => {
+                node.inputs = self.span(block.incoming.iter().copied());
+                node.outputs = self.span(block.outgoing.iter().copied());
+            }
+            RedstoneNode::Repeater(repeater) => {
+                node.inputs = self.span(repeater.incoming);
+                node.side_inputs = self.span(repeater.neighbors.iter().copied());
+                node.outputs = self.span(repeater.outgoing);
+            }
+            RedstoneNode::CopperBulb(bulb) => {
+                node.inputs = self.span(bulb.incoming.iter().copied());
+                node.outputs = self.span(bulb.outgoing.iter().copied());
+            }
+            RedstoneNode::Crafter(crafter) => {
+                node.inputs = self.span(crafter.incoming.iter().copied());
+                node.outputs = self.span(crafter.outgoing.iter().copied());
+            }
+            RedstoneNode::NoteBlock(note_block) => {
+                node.inputs = self.span(note_block.incoming.iter().copied());
+            }
+            RedstoneNode::Mechanism(mechanism) => {
+                node.inputs = self.span(mechanism.incoming.iter().copied());
+                node.side_inputs = self.span(mechanism.quasi_incoming.iter().copied());
+            }
+        }
+
+        node
+    }
+
+    pub fn run(&mut self) -> RunOutcome {
+        self.run_with_budget(Budget::default())
+    }
+
+    /// Runs until there is nothing left to dispatch, the world comes back to a state it was
+    /// already in, or the budget runs out, whichever comes first.
+    pub fn run_with_budget(&mut self, budget: Budget) -> RunOutcome {
+        let max_dispatches = self.dispatches.saturating_add(budget.max_dispatches());
+        let mut cycles = Cycles::new();
+
+        while !self.is_done() {
+            if cycles.is_empty() {
+                self.state =
+                    (0..self.nodes.len()).fold(0, |state, idx| state ^ self.node_hash(idx));
+                cycles.see(self.state_hash(), Frame(self.frame));
+            }
+
+            if !self.step_within(max_dispatches, true) {
+                return RunOutcome::BudgetExhausted;
+            }
+
+            if self.is_done() {
+                break;
+            }
+
+            if self.tick() > budget.max_ticks() {
+                return RunOutcome::BudgetExhausted;
+            }
+
+            if let Some(outcome) = cycles.see(self.state_hash(), Frame(self.frame)) {
+                return outcome;
+            }
+        }
+
+        RunOutcome::Stable
+    }
+
+    /// Hashes the state of the node at `idx` on its own, along with which node it is.
+    fn node_hash(&self, idx: usize) -> u64 {
+        let mut hasher = FnvHasher::default();
+
+        idx.hash(&mut hasher);
+        self.power[idx].hash(&mut hasher);
+        self.forced[idx].hash(&mut hasher);
+        self.latched[idx].hash(&mut hasher);
+        self.lit[idx].hash(&mut hasher);
+        self.directly_powered[idx].hash(&mut hasher);
+        self.quasi_powered[idx].hash(&mut hasher);
+        self.active[idx].hash(&mut hasher);
+        if let Op::Crafter(slots) = self.nodes[idx].op {
+            self.slots[slots as usize].hash(&mut hasher);
+        }
+
+        hasher.finish()
+    }
+
+    /// Hashes the whole state along with everything that is still pending, relative to the
+    /// current frame.
+    fn state_hash(&self) -> u64 {
+        let mut hasher = FnvHasher::default();
+        self.state.hash(&mut hasher);
+
+        let ring = self.buckets.len() as u64;
+        let pending = self.queue.iter().chain(
+            (self.frame + 1..self.frame + ring)
+                .flat_map(|frame| self.buckets[(frame % ring) as usize].iter()),
+        );
+
+        for p in pending {
+            (p.due - self.frame).hash(&mut hasher);
+            p.node.hash(&mut hasher);
+            p.cause.hash(&mut hasher);
+        }
+
+        hasher.finish()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// The game tick that the next step dispatches.
+    pub fn tick(&self) -> u64 {
+        // A frame is a redstone tick, which is two game ticks.
+        self.frame * 2
+    }
+
+    /// Dispatches everything that is dispatchable in the current frame, then advances to the
+    /// earliest frame at which something else is.
+    pub fn step(&mut self) {
+        self.step_within(u64::MAX, false);
+    }
+
+    /// Steps, unless the total number of dispatches reaches `max_dispatches` first, in which
+    /// case this returns `false` partway through the frame. Keeps the state hashed along the way
+    /// if `hashing`.
+    fn step_within(&mut self, max_dispatches: u64, hashing: bool) -> bool {
+        let ring = self.buckets.len() as u64;
+
+        while let Some(pending) = self.queue.pop_front() {
+            if pending.due > self.frame {
+                self.buckets[(pending.due % ring) as usize].push_back(pending);
+                continue;
+            }
+
+            if self.dispatches == max_dispatches {
+                self.queue.push_front(pending);
+                return false;
+            }
+
+            self.dispatches += 1;
+
+            let node = pending.node as usize;
+            let previous_state = (self.power[node], self.forced[node]);
+            let previous_hash = if hashing { self.node_hash(node) } else { 0 };
+            let outputs = self.dispatch(pending);
+
+            if hashing {
+                self.state ^= previous_hash ^ self.node_hash(node);
+            }
+
+            if previous_state != (self.power[node], self.forced[node]) {
+                for &output in self.edges[outputs.range()].iter() {
+                    self.queue.push_front(Pending {
+                        node: output,
+                        cause: pending.node,
+                        due: self.frame + u64::from(self.nodes[output as usize].offset),
+                    });
+                }
+            }
+        }
+
+        // Every frame that can have something due is in the ring, so the next one is the first
+        // bucket after this frame's that isn't empty.
+        for frame in self.frame + 1..self.frame + ring {
+            let bucket = &mut self.buckets[(frame % ring) as usize];
+            if !bucket.is_empty() {
+                self.frame = frame;
+                std::mem::swap(&mut self.queue, bucket);
+                break;
+            }
+        }
+
+        true
+    }
+
+    fn is_on(&self, node: u32) -> bool {
+        self.power[node as usize] > 0 || self.forced[node as usize]
+    }
+
+    fn any_on(&self, span: Span) -> bool {
+        self.edges[span.range()].iter().any(|&n| self.is_on(n))
+    }
+
+    /// Dispatches `pending` and returns the outputs to schedule, if its redstate changed.
+    fn dispatch(&mut self, pending: Pending) -> Span {
+        let idx = pending.node as usize;
+        let node = self.nodes[idx];
+
+        match node.op {
+            Op::Torch => {
+                self.power[idx] = match self.edges[node.inputs.range()].first() {
+                    Some(&incoming) if self.is_on(incoming) => 0,
+                    _ => 16,
+                };
+                self.forced[idx] = self.power[idx] > 0;
+            }
+            Op::Dust => {
+                self.power[idx] = self.weighted[node.inputs.range()]
+                    .iter()
+                    .map(|&(w, source)| self.power[source as usize].saturating_sub(w))
+                    .max()
+                    .unwrap_or(0);
+            }
+            Op::Block => {
+                let inputs = &self.edges[node.inputs.range()];
+                let has_power = inputs.iter().any(|&n| self.is_on(n));
+                let is_forced = inputs.iter().any(|&n| self.forced[n as usize]);
+
+                self.forced[idx] = has_power;
+                self.power[idx] = if is_forced { 16 } else { 0 };
+            }
+            Op::Repeater => {
+                if self.any_on(node.side_inputs) {
+                    return Span::EMPTY;
+                }
+
+                let Some(&incoming) = self.edges[node.inputs.range()].first() else {
+                    return Span::EMPTY;
+                };
+
+                let is_on = self.is_on(incoming);
+                self.forced[idx] = is_on;
+                self.power[idx] = if is_on { 16 } else { 0 };
+            }
+            Op::CopperBulb => {
+                let powered = self.any_on(node.inputs);
+                if powered && !self.latched[idx] {
+                    self.lit[idx] = !self.lit[idx];
+                }
+
+                self.latched[idx] = powered;
+                self.forced[idx] = self.lit[idx];
+            }
+            Op::Crafter(slots) => {
+                let powered = self.any_on(node.inputs);
+                if powered && !self.latched[idx] {
+                    self.craft(idx, slots as usize);
+                }
+
+                self.latched[idx] = powered;
+                self.forced[idx] = powered;
+            }
+            Op::NoteBlock(..) => {
+                let powered = self.any_on(node.inputs);
+                if powered && !self.latched[idx] {
+                    self.played.push((self.tick(), pending.node));
+                }
+
+                self.latched[idx] = powered;
+                self.forced[idx] = powered;
+            }
+            Op::Mechanism(kind) => {
+                self.directly_powered[idx] = self.any_on(node.inputs);
+                self.quasi_powered[idx] = self.any_on(node.side_inputs);
+
+                // Whatever powers the block above is never next to the mechanism itself, so its
+                // change doesn't reach us as a block update.
+                let is_quasi_cause = |cause: u32| {
+                    self.edges[node.side_inputs.range()].contains(&cause)
+                        && !self.edges[node.inputs.range()].contains(&cause)
+                };
+
+                if pending.cause != NONE && is_quasi_cause(pending.cause) {
+                    return Span::EMPTY;
+                }
+
+                let powered = self.directly_powered[idx] || self.quasi_powered[idx];
+                if powered && !self.active[idx] && kind != MechanismKind::Piston {
+                    self.fired[idx] += 1;
+                }
+
+                self.active[idx] = powered;
+                self.forced[idx] = powered;
+            }
+        }
+
+        node.outputs
+    }
+
+    fn craft(&mut self, idx: usize, slots: usize) {
+        let slots = &mut self.slots[slots];
+        if !slots
+            .iter()
+            .any(|slot| matches!(slot, CrafterSlot::Filled(..)))
+        {
+            return;
+        }
+
+        for slot in slots.iter_mut() {
+            *slot = match *slot {
+                CrafterSlot::Filled(1) => CrafterSlot::Empty,
+                CrafterSlot::Filled(n) => CrafterSlot::Filled(n - 1),
+                slot => slot,
+            };
+        }
+
+        self.fired[idx] += 1;
+    }
+
+    /// The redstate at `vec3`, if there is any redstone there.
+    pub fn redstate(&self, vec3: Vec3) -> Option<Redstate> {
+        let &idx = self.positions.get(&vec3)?;
+
+        let redstate = Redstate::new(self.power[idx as usize]);
+        redstate.set_forced(self.forced[idx as usize]);
+        Some(redstate)
+    }
+
+    /// Every note played so far, ordered by game tick and then by position.
+    pub fn notes(&self) -> Vec<NoteEvent> {
+        let mut vec3s = FnvHashMap::default();
+        for (&vec3, &idx) in self.positions.iter() {
+            vec3s.insert(idx, vec3);
+        }
+
+        let mut notes: Vec<NoteEvent> = self
+            .played
+            .iter()
+            .filter_map(|&(tick, idx)| {
+                let Op::NoteBlock(instrument, pitch) = self.nodes[idx as usize].op else {
+                    return None;
+                };
+
+                Some(NoteEvent {
+                    vec3: vec3s[&idx],
+                    instrument,
+                    pitch,
+                    tick,
+                })
+            })
+            .collect();
+
+        notes.sort_by_key(|n| (n.tick, n.vec3.x(), n.vec3.y(), n.vec3.z()));
+        notes
+    }
+}
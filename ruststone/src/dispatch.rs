@@ -1,10 +1,26 @@
-use std::{cell::RefCell, collections::VecDeque, ops::Add, rc::Rc};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    hash::{Hash, Hasher},
+    ops::Add,
+};
 
-use crate::{Redstone, RedstoneNode};
+use fnv::FnvHasher;
 
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+use crate::{
+    trace::{TraceEvent, TraceSink},
+    RedstoneArena, RedstoneId, RedstoneNode,
+};
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub(crate) struct Frame(pub(crate) u64);
 
+impl Frame {
+    /// A frame is a redstone tick, which is two game ticks.
+    pub(crate) fn ticks(self) -> u64 {
+        self.0 * 2
+    }
+}
+
 impl Add for Frame {
     type Output = Frame;
 
@@ -13,152 +29,574 @@ impl Add for Frame {
     }
 }
 
-pub(crate) struct RedstoneEvent<'r> {
+#[derive(Clone, Copy)]
+pub(crate) struct RedstoneEvent {
     pub(crate) current_frame: Frame,
-    pub(crate) redstone: &'r Redstone<'r>,
+    pub(crate) redstone: RedstoneId,
+    /// The redstone whose change scheduled this dispatch, if any.
+    pub(crate) cause: Option<RedstoneId>,
+}
+
+impl RedstoneEvent {
+    pub(crate) fn schedule(&self, redstone: RedstoneId) -> RedstoneDispatchCtxt {
+        RedstoneDispatchCtxt {
+            next_dispatch_frame: self.current_frame,
+            redstone,
+            cause: Some(self.redstone),
+            delay: None,
+        }
+    }
 }
 
-pub(crate) struct RedstoneDispatchCtxt<'r> {
+#[derive(Clone, Copy)]
+pub(crate) struct RedstoneDispatchCtxt {
     next_dispatch_frame: Frame,
-    redstone: &'r Redstone<'r>,
+    redstone: RedstoneId,
+    cause: Option<RedstoneId>,
+    /// How long this waits before it is dispatchable, if not as long as its redstone usually
+    /// does. A dispatch that was cut off by the end of a run has already waited some of it.
+    delay: Option<Frame>,
 }
 
-impl<'r> RedstoneDispatchCtxt<'r> {
-    pub(crate) fn new(redstone: &'r Redstone<'r>, next_dispatch_frame: Frame) -> Rc<RedstoneDispatchCtxt> {
-        Rc::new(RedstoneDispatchCtxt {
+impl RedstoneDispatchCtxt {
+    pub(crate) fn new(redstone: RedstoneId, next_dispatch_frame: Frame) -> RedstoneDispatchCtxt {
+        RedstoneDispatchCtxt {
             next_dispatch_frame,
             redstone,
-        })
+            cause: None,
+            delay: None,
+        }
+    }
+
+    fn dispatchable_frame(&self, arena: &RedstoneArena) -> Frame {
+        self.next_dispatch_frame
+            + self
+                .delay
+                .unwrap_or_else(|| arena[self.redstone].dispatch_frame_offset())
     }
 
-    fn dispatchable(&self, current_frame: Frame) -> bool {
-        (self.next_dispatch_frame + self.redstone.dispatch_frame_offset()) <= current_frame
+    fn dispatchable(&self, arena: &RedstoneArena, current_frame: Frame) -> bool {
+        self.dispatchable_frame(arena) <= current_frame
     }
 
-    fn dispatch(&self, frame: Frame) -> Vec<Rc<RedstoneDispatchCtxt<'r>>> {
-        assert!(self.dispatchable(frame));
-        self.redstone.dispatch(RedstoneEvent {
-            current_frame: frame,
-            redstone: self.redstone,
-        })
+    fn dispatch(&self, arena: &RedstoneArena, frame: Frame) -> Vec<RedstoneDispatchCtxt> {
+        assert!(self.dispatchable(arena, frame));
+        arena[self.redstone].dispatch(
+            arena,
+            RedstoneEvent {
+                current_frame: frame,
+                redstone: self.redstone,
+                cause: self.cause,
+            },
+        )
     }
 }
 
-pub(crate) trait RedstoneDispatch<'r> {
-    fn dispatch(&self, event: RedstoneEvent<'r>) -> Vec<Rc<RedstoneDispatchCtxt<'r>>>;
+pub(crate) trait RedstoneDispatch {
+    fn dispatch(&self, arena: &RedstoneArena, event: RedstoneEvent) -> Vec<RedstoneDispatchCtxt>;
 
     fn dispatch_frame_offset(&self) -> Frame;
 }
 
-struct RedstoneDispatchSnapshot<'r>(String, &'r RefCell<Vec<String>>);
+/// How far a run may go before it gives up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Budget {
+    ticks: u64,
+    dispatches: u64,
+}
 
-impl<'r> RedstoneDispatchSnapshot<'r> {
-    fn new(vec: &'r RefCell<Vec<String>>) -> RedstoneDispatchSnapshot {
-        RedstoneDispatchSnapshot(String::new(), vec)
+impl Default for Budget {
+    fn default() -> Budget {
+        Budget {
+            ticks: 1 << 20,
+            dispatches: u64::MAX,
+        }
     }
+}
 
-    fn write<T: ToString>(self, str: T) -> RedstoneDispatchSnapshot<'r> {
-        if self.0.is_empty() {
-            RedstoneDispatchSnapshot(str.to_string(), self.1)
-        } else {
-            RedstoneDispatchSnapshot(self.0 + " " + &str.to_string(), self.1)
+impl Budget {
+    pub fn new() -> Budget {
+        Budget::default()
+    }
+
+    /// The last game tick that a run may get to.
+    pub fn ticks(self, ticks: u64) -> Budget {
+        Budget { ticks, ..self }
+    }
+
+    /// How many dispatches a run may make in total.
+    pub fn dispatches(self, dispatches: u64) -> Budget {
+        Budget { dispatches, ..self }
+    }
+
+    pub fn max_ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    pub fn max_dispatches(&self) -> u64 {
+        self.dispatches
+    }
+}
+
+/// How a run ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RunOutcome {
+    /// Nothing is left to dispatch.
+    Stable,
+    /// The run came back to a state it was already in, so it would go on forever. The cycle
+    /// first started at game tick `phase`, and it repeats every `period` game ticks.
+    Oscillating { period: u64, phase: u64 },
+    /// The run went past its [`Budget`] before it settled.
+    BudgetExhausted,
+}
+
+impl RunOutcome {
+    /// Whether the run settled.
+    pub fn is_stable(&self) -> bool {
+        matches!(self, RunOutcome::Stable)
+    }
+
+    /// The outcome of two runs side by side. Two oscillations together repeat once both of them
+    /// do, and only once both of them have started.
+    pub(crate) fn merge(self, other: RunOutcome) -> RunOutcome {
+        match (self, other) {
+            (RunOutcome::BudgetExhausted, _) | (_, RunOutcome::BudgetExhausted) => {
+                RunOutcome::BudgetExhausted
+            }
+            (RunOutcome::Stable, outcome) | (outcome, RunOutcome::Stable) => outcome,
+            (
+                RunOutcome::Oscillating { period, phase },
+                RunOutcome::Oscillating {
+                    period: other_period,
+                    phase: other_phase,
+                },
+            ) => RunOutcome::Oscillating {
+                period: lcm(period, other_period),
+                phase: phase.max(other_phase),
+            },
         }
     }
+}
 
-    fn push(self) {
-        self.1.borrow_mut().push(self.0)
+/// How many of the latest states a run remembers, to tell when its cycle started.
+const MAX_HISTORY: usize = 1 << 16;
+
+/// Looks out for a run coming back to a state it was already in, by the hash of every state it
+/// goes through. Every state is only compared against a single earlier one, which is moved along
+/// whenever the distance between them doubles, so a cycle is found without remembering every
+/// state. How long the cycle is then tells where it started, as long as the run is still
+/// remembered that far back.
+pub(crate) struct Cycles {
+    /// The hashes of the latest states, along with their frames, oldest first.
+    history: VecDeque<(u64, Frame)>,
+    /// How many states were seen before the oldest one in the history.
+    forgotten: usize,
+    /// The state that every later one is compared against, along with its index and frame.
+    checkpoint: (u64, usize, Frame),
+    /// How far the states get from the checkpoint before it is moved along.
+    power: usize,
+}
+
+impl Cycles {
+    pub(crate) fn new() -> Cycles {
+        Cycles {
+            history: VecDeque::new(),
+            forgotten: 0,
+            checkpoint: (0, 0, Frame(0)),
+            power: 1,
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.forgotten == 0 && self.history.is_empty()
+    }
+
+    /// Records the state with `hash` that the run is in at `frame`, and returns how it
+    /// oscillates if it has been in that state before.
+    pub(crate) fn see(&mut self, hash: u64, frame: Frame) -> Option<RunOutcome> {
+        let idx = self.forgotten + self.history.len();
+
+        self.history.push_back((hash, frame));
+        if self.history.len() > MAX_HISTORY {
+            self.history.pop_front();
+            self.forgotten += 1;
+        }
+
+        if idx == 0 {
+            self.checkpoint = (hash, idx, frame);
+            return None;
+        }
+
+        let (checkpoint, checkpoint_idx, checkpoint_frame) = self.checkpoint;
+        if hash == checkpoint {
+            let length = idx - checkpoint_idx;
+            let at = |idx: usize| self.history[idx - self.forgotten];
+
+            // The cycle started at the first state that the one a cycle later is the same as.
+            let start = (self.forgotten..checkpoint_idx)
+                .find(|&idx| at(idx).0 == at(idx + length).0)
+                .map_or(checkpoint_frame, |idx| at(idx).1);
+
+            return Some(RunOutcome::Oscillating {
+                period: frame.ticks() - checkpoint_frame.ticks(),
+                phase: start.ticks(),
+            });
+        }
+
+        if idx - checkpoint_idx == self.power {
+            self.checkpoint = (hash, idx, frame);
+            self.power *= 2;
+        }
+
+        None
+    }
+}
+
+fn lcm(a: u64, b: u64) -> u64 {
+    fn gcd(a: u64, b: u64) -> u64 {
+        if b == 0 {
+            a
+        } else {
+            gcd(b, a % b)
+        }
     }
+
+    a / gcd(a, b) * b
 }
 
-pub struct RedstoneGraph<'r> {
-    dispatch_ctxts: Vec<Rc<RedstoneDispatchCtxt<'r>>>,
-    snapshots: RefCell<Vec<String>>,
+/// Hashes the state of `redstone` on its own, along with which redstone it is.
+fn redstone_hash(arena: &RedstoneArena, redstone: RedstoneId) -> u64 {
+    let mut hasher = FnvHasher::default();
+    redstone.hash(&mut hasher);
+    arena[redstone].hash_state(&mut hasher);
+    hasher.finish()
 }
 
-impl<'r> RedstoneGraph<'r> {
-    pub(crate) fn new() -> RedstoneGraph<'r> {
+pub struct RedstoneGraph {
+    redstones: Vec<RedstoneId>,
+    dispatch_ctxts: Vec<RedstoneDispatchCtxt>,
+}
+
+impl RedstoneGraph {
+    pub(crate) fn new() -> RedstoneGraph {
         RedstoneGraph {
+            redstones: Vec::new(),
             dispatch_ctxts: Vec::new(),
-            snapshots: RefCell::new(Vec::new()),
         }
     }
 
-    pub fn collect(redstone: &'r Redstone<'r>) -> RedstoneGraph {
+    /// Every redstone connected to `redstone`, without anything to dispatch yet.
+    pub(crate) fn connected(arena: &RedstoneArena, redstone: RedstoneId) -> RedstoneGraph {
+        let mut rg = RedstoneGraph::new();
+        rg.redstones = arena.iter(redstone).collect();
+        rg
+    }
+
+    /// A graph of `redstones` exactly, which have to be every redstone connected to them.
+    pub(crate) fn of(redstones: Vec<RedstoneId>) -> RedstoneGraph {
         let mut rg = RedstoneGraph::new();
+        rg.redstones = redstones;
+        rg
+    }
 
-        for redstone in redstone.into_iter() {
-            if let RedstoneNode::Torch(..) = redstone.node() {
-                rg.dispatch_ctxts.push(RedstoneDispatchCtxt::new(redstone, Frame(0)));
+    pub fn collect(arena: &RedstoneArena, redstone: RedstoneId) -> RedstoneGraph {
+        let mut rg = RedstoneGraph::connected(arena, redstone);
+        rg.place(arena);
+        rg
+    }
+
+    /// Dispatches everything that acts as soon as it's placed.
+    pub(crate) fn place(&mut self, arena: &RedstoneArena) {
+        for &redstone in self.redstones.iter() {
+            // Mechanisms check their power when they're placed, so they get dispatched once too.
+            if let RedstoneNode::Torch(..) | RedstoneNode::Mechanism(..) = arena[redstone].node() {
+                self.dispatch_ctxts
+                    .push(RedstoneDispatchCtxt::new(redstone, Frame(0)));
             }
         }
+    }
 
+    /// A graph that delivers a single block update to `redstone`, as if one of its neighbors
+    /// had changed, and then dispatches whatever that update leads to.
+    pub fn block_update(arena: &RedstoneArena, redstone: RedstoneId) -> RedstoneGraph {
+        let mut rg = RedstoneGraph::connected(arena, redstone);
+        rg.update(redstone);
         rg
     }
 
-    pub fn run(&self) {
-        let mut queue = VecDeque::from(self.dispatch_ctxts.clone());
-        let mut frame = Frame(0);
+    pub(crate) fn update(&mut self, redstone: RedstoneId) {
+        self.dispatch_ctxts
+            .push(RedstoneDispatchCtxt::new(redstone, Frame(0)));
+    }
 
-        let mut deferred = VecDeque::new();
-        while !queue.is_empty() {
-            while let Some(c) = queue.pop_front() {
-                if !c.dispatchable(frame) {
-                    self.new_snapshot()
-                        .write(c.redstone)
-                        .write("was deferred")
-                        .push();
+    /// Every redstone in this graph.
+    pub fn redstones(&self) -> &[RedstoneId] {
+        &self.redstones
+    }
 
-                    deferred.push_back(c);
-                    continue;
-                }
+    /// Picks up a dispatch that an earlier run left pending, `delay` frames from the start.
+    pub(crate) fn resume(&mut self, redstone: RedstoneId, cause: Option<RedstoneId>, delay: Frame) {
+        self.dispatch_ctxts.push(RedstoneDispatchCtxt {
+            next_dispatch_frame: Frame(0),
+            redstone,
+            cause,
+            delay: Some(delay),
+        });
+    }
 
-                let previous_state = c.redstone.redstate().clone();
-                let consequents = c.dispatch(frame);
-                let new_state = c.redstone.redstate().clone();
-
-                self.new_snapshot()
-                    .write(c.redstone)
-                    .write("was dispatched, previously")
-                    .write(previous_state.is_on())
-                    .write("and now")
-                    .write(new_state.is_on())
-                    .push();
-
-                if previous_state != new_state {
-                    self.new_snapshot()
-                        .write(consequents.len())
-                        .write("new consequents queued")
-                        .push();
-
-                    for consequent in consequents {
-                        queue.push_front(consequent);
-                    }
-                }
-            }
+    /// The redstones that get dispatched first, in order, along with whatever caused them and
+    /// the frame at which they're dispatchable.
+    pub(crate) fn seeds<'a>(
+        &'a self,
+        arena: &'a RedstoneArena,
+    ) -> impl Iterator<Item = (RedstoneId, Option<RedstoneId>, Frame)> + 'a {
+        self.dispatch_ctxts
+            .iter()
+            .map(|c| (c.redstone, c.cause, c.dispatchable_frame(arena)))
+    }
+
+    pub fn run(&self, arena: &RedstoneArena) -> RunOutcome {
+        self.run_with_budget(arena, Budget::default())
+    }
+
+    /// Runs until there is nothing left to dispatch, the graph comes back to a state it was
+    /// already in, or the budget runs out, whichever comes first.
+    pub fn run_with_budget(&self, arena: &RedstoneArena, budget: Budget) -> RunOutcome {
+        self.run_traced(arena, budget, &mut ())
+    }
 
-            // The queue might be empty, but we might've deferred some of the dispatchables.
-            // In that case, we ought to find the earliest dispatchable frame and go there.
-            // Terminate only when both queue and deferred is empty.
-            if queue.is_empty() && !deferred.is_empty() {
-                let earliest_dispatchable_frame = deferred
-                    .iter()
-                    .map(|c| c.next_dispatch_frame + c.redstone.dispatch_frame_offset())
-                    .min();
-
-                self.new_snapshot()
-                    .write("advancing to frame")
-                    .write(frame.0)
-                    .push();
-
-                frame = earliest_dispatchable_frame.unwrap();
+    /// Runs like [`RedstoneGraph::run_with_budget`], and records everything that happens into
+    /// `sink` along the way.
+    pub fn run_traced(
+        &self,
+        arena: &RedstoneArena,
+        budget: Budget,
+        sink: &mut dyn TraceSink,
+    ) -> RunOutcome {
+        let mut run = self.start(budget);
+        loop {
+            if let Some(outcome) = run.advance(arena, sink) {
+                return outcome;
             }
+        }
+    }
 
-            queue = deferred;
-            deferred = VecDeque::new();
+    pub(crate) fn start(&self, budget: Budget) -> RedstoneRun<'_> {
+        RedstoneRun {
+            rg: self,
+            queue: VecDeque::from(self.dispatch_ctxts.clone()),
+            scheduled: BTreeMap::new(),
+            frame: Frame(0),
+            budget,
+            dispatches: 0,
+            state: 0,
+            cycles: Cycles::new(),
+            stop: None,
         }
     }
+}
+
+/// A run of a [`RedstoneGraph`] that is advanced one frame at a time.
+pub(crate) struct RedstoneRun<'g> {
+    rg: &'g RedstoneGraph,
+    queue: VecDeque<RedstoneDispatchCtxt>,
+    /// Everything that was deferred, bucketed by the frame at which it becomes dispatchable. A
+    /// bucket keeps the order in which its dispatchables were deferred.
+    scheduled: BTreeMap<Frame, VecDeque<RedstoneDispatchCtxt>>,
+    frame: Frame,
+    budget: Budget,
+    dispatches: u64,
+    /// Every redstone of the graph hashed on its own, all XORed together. Only the redstones
+    /// that get dispatched are hashed again.
+    state: u64,
+    cycles: Cycles,
+    /// The frame at which the run stops, whatever is still pending by then.
+    stop: Option<Frame>,
+}
+
+impl RedstoneRun<'_> {
+    /// The frame that the next step dispatches.
+    pub(crate) fn frame(&self) -> Frame {
+        self.frame
+    }
+
+    pub(crate) fn is_done(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Stops the run before it dispatches anything at `frame`. A run that stops there doesn't
+    /// look out for oscillation, since it is going to be picked up again.
+    pub(crate) fn stop_at(mut self, frame: Frame) -> Self {
+        self.stop = Some(frame);
+        self
+    }
+
+    /// Everything this run still had to dispatch, in order, with how many frames after `frame`
+    /// each of them becomes dispatchable.
+    pub(crate) fn pending(
+        &self,
+        arena: &RedstoneArena,
+        frame: Frame,
+    ) -> Vec<(RedstoneId, Option<RedstoneId>, Frame)> {
+        self.queue
+            .iter()
+            .chain(self.scheduled.values().flatten())
+            .map(|c| {
+                let due = c.dispatchable_frame(arena).0.saturating_sub(frame.0);
+                (c.redstone, c.cause, Frame(due))
+            })
+            .collect()
+    }
+
+    /// Steps once and returns the outcome of the run, if it is over.
+    pub(crate) fn advance(
+        &mut self,
+        arena: &RedstoneArena,
+        sink: &mut dyn TraceSink,
+    ) -> Option<RunOutcome> {
+        if self.is_done() {
+            return Some(RunOutcome::Stable);
+        }
+
+        if self.stop.is_some_and(|stop| self.frame >= stop) {
+            return Some(RunOutcome::BudgetExhausted);
+        }
+
+        if self.stop.is_none() && self.cycles.is_empty() {
+            self.state = self
+                .rg
+                .redstones
+                .iter()
+                .fold(0, |state, &redstone| state ^ redstone_hash(arena, redstone));
+            self.cycles.see(self.state_hash(arena), self.frame);
+        }
+
+        if !self.step(arena, sink) {
+            return Some(RunOutcome::BudgetExhausted);
+        }
+
+        if self.is_done() {
+            return Some(RunOutcome::Stable);
+        }
+
+        if self.frame.ticks() > self.budget.ticks {
+            return Some(RunOutcome::BudgetExhausted);
+        }
+
+        if let Some(stop) = self.stop {
+            return (self.frame >= stop).then_some(RunOutcome::BudgetExhausted);
+        }
+
+        let hash = self.state_hash(arena);
+        self.cycles.see(hash, self.frame)
+    }
+
+    /// Hashes the state of every redstone in the graph along with everything that is still
+    /// pending, relative to the current frame. If two frames hash the same, the run repeats.
+    fn state_hash(&self, arena: &RedstoneArena) -> u64 {
+        let mut hasher = FnvHasher::default();
+        self.state.hash(&mut hasher);
+
+        for c in self.queue.iter() {
+            (c.dispatchable_frame(arena).0 - self.frame.0).hash(&mut hasher);
+            c.redstone.hash(&mut hasher);
+            c.cause.hash(&mut hasher);
+        }
+
+        for (frame, queue) in self.scheduled.iter() {
+            (frame.0 - self.frame.0).hash(&mut hasher);
+            for c in queue.iter() {
+                c.redstone.hash(&mut hasher);
+                c.cause.hash(&mut hasher);
+            }
+        }
+
+        hasher.finish()
+    }
+
+    /// Dispatches everything that is dispatchable in the current frame, then advances to the
+    /// earliest frame at which something else is. Returns `false` if the dispatch budget ran
+    /// out before the frame was over.
+    pub(crate) fn step(&mut self, arena: &RedstoneArena, sink: &mut dyn TraceSink) -> bool {
+        let frame = self.frame;
+
+        while let Some(c) = self.queue.pop_front() {
+            if !c.dispatchable(arena, frame) {
+                sink.record(&TraceEvent::Deferred {
+                    tick: frame.ticks(),
+                    redstone: c.redstone,
+                    vec3: arena[c.redstone].vec3(),
+                    until: c.dispatchable_frame(arena).ticks(),
+                });
+
+                self.scheduled
+                    .entry(c.dispatchable_frame(arena))
+                    .or_default()
+                    .push_back(c);
+                continue;
+            }
+
+            if self.dispatches == self.budget.dispatches {
+                self.queue.push_front(c);
+                return false;
+            }
+
+            self.dispatches += 1;
+
+            sink.record(&TraceEvent::Dispatched {
+                tick: frame.ticks(),
+                redstone: c.redstone,
+                vec3: arena[c.redstone].vec3(),
+                cause: c.cause,
+            });
+
+            let previous_state = arena[c.redstone].redstate().clone();
+            // A run that stops on purpose never looks out for oscillation, so it has nothing to
+            // keep hashed.
+            let hashing = self.stop.is_none();
+            let previous_hash = if hashing {
+                redstone_hash(arena, c.redstone)
+            } else {
+                0
+            };
+            let consequents = c.dispatch(arena, frame);
+            let new_state = arena[c.redstone].redstate().clone();
+
+            if hashing {
+                self.state ^= previous_hash ^ redstone_hash(arena, c.redstone);
+            }
+
+            if previous_state != new_state {
+                sink.record(&TraceEvent::PowerChanged {
+                    tick: frame.ticks(),
+                    redstone: c.redstone,
+                    vec3: arena[c.redstone].vec3(),
+                    cause: c.cause,
+                    old_power: previous_state.get_power(),
+                    new_power: new_state.get_power(),
+                    was_on: previous_state.is_on(),
+                    is_on: new_state.is_on(),
+                });
+
+                for consequent in consequents {
+                    self.queue.push_front(consequent);
+                }
+            }
+        }
+
+        // The queue is empty, but we might've deferred some of the dispatchables. In that case,
+        // we go to the earliest frame that has something to dispatch. The run is done only when
+        // nothing is deferred anymore.
+        if let Some((earliest_dispatchable_frame, queue)) = self.scheduled.pop_first() {
+            sink.record(&TraceEvent::FrameAdvanced {
+                from: frame.ticks(),
+                to: earliest_dispatchable_frame.ticks(),
+            });
+
+            self.frame = earliest_dispatchable_frame;
+            self.queue = queue;
+        }
 
-    fn new_snapshot(&self) -> RedstoneDispatchSnapshot {
-        RedstoneDispatchSnapshot::new(&self.snapshots)
+        true
     }
 }
@@ -0,0 +1,193 @@
+use std::{collections::HashSet, fmt::Display};
+
+use crate::{RedstoneArena, RedstoneGraph, RedstoneId, RedstoneNode};
+
+/// Renders redstone as a Graphviz digraph. Nodes are labelled by their name and kind, torches,
+/// repeaters and other directed outputs are arrows, dust neighbors are plain lines, and the
+/// sources a dust reads its power from are dashed arrows labelled with their dust distance.
+///
+/// Print it, or write it out with `write!`, and feed it to `dot`.
+pub struct Dot<'a> {
+    arena: &'a RedstoneArena,
+    redstones: Vec<RedstoneId>,
+    redstate: bool,
+}
+
+impl<'a> Dot<'a> {
+    /// Every redstone in `rg`.
+    pub fn graph(arena: &'a RedstoneArena, rg: &RedstoneGraph) -> Dot<'a> {
+        Dot {
+            arena,
+            redstones: rg.redstones().to_vec(),
+            redstate: false,
+        }
+    }
+
+    /// Every redstone in `arena`, however many graphs that makes.
+    pub fn arena(arena: &'a RedstoneArena) -> Dot<'a> {
+        Dot {
+            arena,
+            redstones: arena.ids().collect(),
+            redstate: false,
+        }
+    }
+
+    /// Fills in every node by its current redstate: white while off, and a deeper red the more
+    /// power it has.
+    pub fn with_redstate(mut self) -> Dot<'a> {
+        self.redstate = true;
+        self
+    }
+
+    fn kind(node: &RedstoneNode) -> String {
+        match node {
+            RedstoneNode::Torch(..) => String::from("torch"),
+            RedstoneNode::Dust(..) => String::from("dust"),
+            RedstoneNode::Block(..) => String::from("block"),
+            RedstoneNode::Repeater(repeater) => format!("repeater ({})", repeater.delay.0),
+            RedstoneNode::CopperBulb(..) => String::from("copper bulb"),
+            RedstoneNode::Crafter(..) => String::from("crafter"),
+            RedstoneNode::NoteBlock(..) => String::from("note block"),
+            RedstoneNode::Mechanism(mechanism) => format!("{:?}", mechanism.kind).to_lowercase(),
+        }
+    }
+
+    /// Whether `source` only quasi-powers `target`, which gets drawn as a quasi link instead.
+    fn is_quasi(&self, source: RedstoneId, target: RedstoneId) -> bool {
+        match self.arena[target].node() {
+            RedstoneNode::Mechanism(mechanism) => {
+                mechanism.quasi_incoming.contains(&source) && !mechanism.incoming.contains(&source)
+            }
+            _ => false,
+        }
+    }
+
+    fn outgoing(node: &RedstoneNode) -> Vec<RedstoneId> {
+        match node {
+            RedstoneNode::Torch(torch) => torch.outgoing.clone(),
+            RedstoneNode::Block(block) => block.outgoing.clone(),
+            RedstoneNode::Repeater(repeater) => repeater.outgoing.into_iter().collect(),
+            RedstoneNode::CopperBulb(bulb) => bulb.outgoing.clone(),
+            RedstoneNode::Crafter(crafter) => crafter.outgoing.clone(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl Display for Dot<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let included: HashSet<RedstoneId> = self.redstones.iter().copied().collect();
+
+        writeln!(f, "digraph redstone {{")?;
+        writeln!(f, "    node [shape=box];")?;
+
+        for &id in &self.redstones {
+            let redstone = &self.arena[id];
+            let name = redstone.name().replace('\\', "\\\\").replace('"', "\\\"");
+            let label = format!("{name}\\n{}", Dot::kind(redstone.node()));
+
+            if self.redstate {
+                let redstate = redstone.redstate();
+                let saturation = if redstate.is_on() {
+                    0.25 + 0.75 * f32::from(redstate.get_power().min(16)) / 16.0
+                } else {
+                    0.0
+                };
+
+                writeln!(
+                    f,
+                    "    n{} [label=\"{label}\\npower {}\", style=filled, fillcolor=\"0.0 {saturation:.3} 1.0\"];",
+                    id.index(),
+                    redstate.get_power()
+                )?;
+            } else {
+                writeln!(f, "    n{} [label=\"{label}\"];", id.index())?;
+            }
+        }
+
+        // A dust links both ways with anything undirected, so those are drawn once as a line.
+        let mut lines = HashSet::new();
+
+        for &id in &self.redstones {
+            let node = self.arena[id].node();
+
+            for target in Dot::outgoing(node) {
+                if !included.contains(&target) {
+                    continue;
+                }
+
+                if let RedstoneNode::Dust(dust) = self.arena[target].node() {
+                    if dust.neighbors.contains(&id) {
+                        continue;
+                    }
+                }
+
+                if self.is_quasi(id, target) {
+                    continue;
+                }
+
+                writeln!(f, "    n{} -> n{};", id.index(), target.index())?;
+            }
+
+            match node {
+                RedstoneNode::Dust(dust) => {
+                    for &neighbor in &dust.neighbors {
+                        if !included.contains(&neighbor)
+                            || self.is_quasi(id, neighbor)
+                            || !lines.insert((id.min(neighbor), id.max(neighbor)))
+                        {
+                            continue;
+                        }
+
+                        writeln!(
+                            f,
+                            "    n{} -> n{} [dir=none];",
+                            id.index(),
+                            neighbor.index()
+                        )?;
+                    }
+
+                    for &(weight, source) in &dust.sources {
+                        if !included.contains(&source) {
+                            continue;
+                        }
+
+                        writeln!(
+                            f,
+                            "    n{} -> n{} [style=dashed, label=\"{weight}\"];",
+                            source.index(),
+                            id.index()
+                        )?;
+                    }
+                }
+                RedstoneNode::Repeater(repeater) => {
+                    for &edge in &repeater.neighbors {
+                        if included.contains(&edge) {
+                            writeln!(
+                                f,
+                                "    n{} -> n{} [style=dotted, label=\"lock\"];",
+                                edge.index(),
+                                id.index()
+                            )?;
+                        }
+                    }
+                }
+                RedstoneNode::Mechanism(mechanism) => {
+                    for &source in &mechanism.quasi_incoming {
+                        if included.contains(&source) {
+                            writeln!(
+                                f,
+                                "    n{} -> n{} [style=dotted, label=\"quasi\"];",
+                                source.index(),
+                                id.index()
+                            )?;
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        writeln!(f, "}}")
+    }
+}
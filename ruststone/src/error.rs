@@ -0,0 +1,134 @@
+use std::{error::Error, fmt::Display};
+
+use crate::{
+    trace::At,
+    vec3::{Direction, Vec3},
+    RedstoneId,
+};
+
+/// Something about a world or a redstone graph that can't be simulated the way it was built.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RuststoneError {
+    /// Dust has to sit on top of a solid block.
+    UnsupportedDust { vec3: Vec3 },
+    /// A torch has to be placed on a solid block.
+    FloatingTorch { vec3: Vec3 },
+    /// The torch or dust at `vec3` lost the block it was on, and dropped during a run that
+    /// wasn't traced.
+    Dropped { vec3: Vec3 },
+    /// A repeater with nothing in front of it to power.
+    RepeaterFacingNothing {
+        redstone: RedstoneId,
+        vec3: Option<Vec3>,
+    },
+    /// Linking `redstone` would give it more edges than it can have.
+    TooManyEdges {
+        redstone: RedstoneId,
+        vec3: Option<Vec3>,
+    },
+    /// `redstone` can only be powered by a single redstone, and already is.
+    AlreadyPowered {
+        redstone: RedstoneId,
+        vec3: Option<Vec3>,
+    },
+    /// `redstone` doesn't power anything, so nothing can be linked from it.
+    NoOutgoingEdges {
+        redstone: RedstoneId,
+        vec3: Option<Vec3>,
+    },
+    /// Dust is powered through the dust around it, so it is never the weighted source of dust.
+    DustAsSource {
+        redstone: RedstoneId,
+        vec3: Option<Vec3>,
+    },
+    /// `redstone` had to be a `expected`.
+    WrongKind {
+        redstone: RedstoneId,
+        vec3: Option<Vec3>,
+        expected: &'static str,
+    },
+    /// A `kind` can only face one of the four horizontal sides.
+    NotHorizontal {
+        kind: &'static str,
+        facing: Direction,
+    },
+    /// A repeater delays by 1 to 4 redstone ticks.
+    InvalidDelay { delay: u8 },
+    /// A note block plays pitches from 0 to 24.
+    InvalidPitch { pitch: u8 },
+    /// A crafter only has 9 slots.
+    NoSuchSlot { idx: usize },
+}
+
+impl RuststoneError {
+    /// Where in the world it went wrong, if the redstone was made for one.
+    pub fn vec3(&self) -> Option<Vec3> {
+        match *self {
+            RuststoneError::UnsupportedDust { vec3 }
+            | RuststoneError::FloatingTorch { vec3 }
+            | RuststoneError::Dropped { vec3 } => Some(vec3),
+            RuststoneError::RepeaterFacingNothing { vec3, .. }
+            | RuststoneError::TooManyEdges { vec3, .. }
+            | RuststoneError::AlreadyPowered { vec3, .. }
+            | RuststoneError::NoOutgoingEdges { vec3, .. }
+            | RuststoneError::DustAsSource { vec3, .. }
+            | RuststoneError::WrongKind { vec3, .. } => vec3,
+            RuststoneError::NotHorizontal { .. }
+            | RuststoneError::InvalidDelay { .. }
+            | RuststoneError::InvalidPitch { .. }
+            | RuststoneError::NoSuchSlot { .. } => None,
+        }
+    }
+}
+
+impl Display for RuststoneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            RuststoneError::UnsupportedDust { vec3 } => {
+                write!(f, "the dust at {vec3} isn't on a solid block")
+            }
+            RuststoneError::FloatingTorch { vec3 } => {
+                write!(f, "the torch at {vec3} isn't placed on a solid block")
+            }
+            RuststoneError::Dropped { vec3 } => {
+                write!(f, "the voxel at {vec3} lost its support and dropped")
+            }
+            RuststoneError::RepeaterFacingNothing { redstone, vec3 } => {
+                write!(f, "the repeater {} faces nothing", At(redstone, vec3))
+            }
+            RuststoneError::TooManyEdges { redstone, vec3 } => {
+                write!(f, "{} can't have any more edges", At(redstone, vec3))
+            }
+            RuststoneError::AlreadyPowered { redstone, vec3 } => {
+                write!(f, "{} is already powered by something", At(redstone, vec3))
+            }
+            RuststoneError::NoOutgoingEdges { redstone, vec3 } => {
+                write!(f, "{} doesn't power anything", At(redstone, vec3))
+            }
+            RuststoneError::DustAsSource { redstone, vec3 } => {
+                write!(
+                    f,
+                    "the dust {} can't be a source of dust",
+                    At(redstone, vec3)
+                )
+            }
+            RuststoneError::WrongKind {
+                redstone,
+                vec3,
+                expected,
+            } => write!(f, "{} isn't a {expected}", At(redstone, vec3)),
+            RuststoneError::NotHorizontal { kind, facing } => {
+                write!(f, "a {kind} can't face {facing}")
+            }
+            RuststoneError::InvalidDelay { delay } => {
+                write!(f, "a repeater can't delay by {delay}")
+            }
+            RuststoneError::InvalidPitch { pitch } => {
+                write!(f, "a note block can't play pitch {pitch}")
+            }
+            RuststoneError::NoSuchSlot { idx } => write!(f, "a crafter has no slot {idx}"),
+        }
+    }
+}
+
+impl Error for RuststoneError {}
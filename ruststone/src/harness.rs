@@ -0,0 +1,329 @@
+//! A harness for testing circuits built in a world. Inputs and outputs are named by position:
+//!
+//! ```no_run
+//! # use ruststone::{harness::Harness, text::parse_world, vec3::Vec3};
+//! # let world = parse_world("").unwrap();
+//! let harness = Harness::new(world)
+//!     .input("a", Vec3(0, 1, 0))
+//!     .input("b", Vec3(0, 1, 2))
+//!     .output("out", Vec3(4, 1, 1));
+//!
+//! harness.assert_truth_table(|inputs| vec![inputs[0] && inputs[1]]);
+//! ```
+//!
+//! A world has no levers, so an input is whatever voxel sits at its position, usually a torch: it
+//! is high while the voxel is there and low while it's broken. An output is high while the
+//! redstone at its position is on. Every check starts over from the world the harness was given,
+//! and a failing one panics with what it saw along with the trace of the run.
+
+use crate::{
+    trace::{Delayed, TraceEvent},
+    vec3::Vec3,
+    voxels::Voxel,
+    world::World,
+    RunOutcome,
+};
+
+#[derive(Clone)]
+pub struct Harness {
+    world: World,
+    inputs: Vec<(String, Vec3, Voxel)>,
+    outputs: Vec<(String, Vec3)>,
+}
+
+impl Harness {
+    pub fn new(world: World) -> Harness {
+        Harness {
+            world,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        }
+    }
+
+    /// Names the voxel at `vec3` as an input.
+    pub fn input(mut self, name: &str, vec3: Vec3) -> Harness {
+        let voxel = self.world[vec3].clone();
+        assert!(!voxel.is_air(), "input {name} at {vec3} is air");
+
+        self.inputs.push((String::from(name), vec3, voxel));
+        self
+    }
+
+    /// Names the redstone at `vec3` as an output.
+    pub fn output(mut self, name: &str, vec3: Vec3) -> Harness {
+        assert!(!self.world[vec3].is_air(), "output {name} at {vec3} is air");
+
+        self.outputs.push((String::from(name), vec3));
+        self
+    }
+
+    /// Settles the world with every input set to `inputs`, in the order they were named, and
+    /// returns the session to go on with.
+    pub fn session(&self, inputs: &[bool]) -> Session<'_> {
+        assert_eq!(
+            inputs.len(),
+            self.inputs.len(),
+            "there are {} inputs",
+            self.inputs.len()
+        );
+
+        let mut session = Session {
+            harness: self,
+            world: self.world.clone(),
+            inputs: inputs.to_vec(),
+            changes: Vec::new(),
+            settling: Vec::new(),
+            settled: 0,
+            events: Vec::new(),
+            outcome: RunOutcome::Stable,
+        };
+
+        for (idx, &high) in inputs.iter().enumerate() {
+            session.drive(idx, high);
+        }
+
+        session.outcome = session.world.run_traced(&mut session.settling);
+        session.settled = session.world.tick();
+        session
+    }
+
+    /// The outputs, in the order they were named, once the world settles with `inputs`.
+    pub fn row(&self, inputs: &[bool]) -> Vec<bool> {
+        self.session(inputs).outputs()
+    }
+
+    /// Checks every combination of inputs against the outputs that `expected` gives for it. The
+    /// first input named is the most significant bit of the row number.
+    pub fn assert_truth_table(&self, expected: impl Fn(&[bool]) -> Vec<bool>) {
+        let n = self.inputs.len();
+
+        for row in 0..1u64 << n {
+            let inputs: Vec<bool> = (0..n).map(|idx| row >> (n - 1 - idx) & 1 == 1).collect();
+            let expected = expected(&inputs);
+            let session = self.session(&inputs);
+
+            if session.outcome != RunOutcome::Stable || session.outputs() != expected {
+                panic!(
+                    "row {row} ({}) gave {}, expected {}, and was {:?}\n{}",
+                    Harness::describe(self.inputs.iter().map(|(n, ..)| n), &inputs),
+                    Harness::describe(self.outputs.iter().map(|(n, _)| n), &session.outputs()),
+                    Harness::describe(self.outputs.iter().map(|(n, _)| n), &expected),
+                    session.outcome,
+                    session.trace()
+                );
+            }
+        }
+    }
+
+    fn describe<'a>(names: impl Iterator<Item = &'a String>, values: &[bool]) -> String {
+        names
+            .zip(values)
+            .map(|(name, &high)| format!("{name}={}", u8::from(high)))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    fn input_idx(&self, name: &str) -> usize {
+        self.inputs
+            .iter()
+            .position(|(n, ..)| n == name)
+            .unwrap_or_else(|| panic!("there is no input called {name}"))
+    }
+
+    fn output_vec3(&self, name: &str) -> Vec3 {
+        self.outputs
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|&(_, vec3)| vec3)
+            .unwrap_or_else(|| panic!("there is no output called {name}"))
+    }
+}
+
+/// A world being driven through a harness, which keeps track of when its inputs changed and of
+/// everything that happened since it settled.
+pub struct Session<'h> {
+    harness: &'h Harness,
+    world: World,
+    inputs: Vec<bool>,
+    /// The game tick at which an input changed, for every change.
+    changes: Vec<(usize, u64)>,
+    /// What happened while the world first settled.
+    settling: Vec<TraceEvent>,
+    /// The game tick the world settled at, which every tick since is counted from.
+    settled: u64,
+    events: Vec<TraceEvent>,
+    outcome: RunOutcome,
+}
+
+impl Session<'_> {
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    /// The game tick the session is at, counted from when the world settled.
+    pub fn tick(&self) -> u64 {
+        self.world.tick() - self.settled
+    }
+
+    /// How the latest run went.
+    pub fn outcome(&self) -> RunOutcome {
+        self.outcome
+    }
+
+    fn drive(&mut self, idx: usize, high: bool) {
+        let (_, vec3, voxel) = &self.harness.inputs[idx];
+
+        self.world[*vec3] = if high {
+            voxel.clone()
+        } else {
+            Voxel::air().voxel()
+        };
+        self.inputs[idx] = high;
+    }
+
+    /// Sets the input called `name` at the current game tick.
+    pub fn set(&mut self, name: &str, high: bool) -> &mut Self {
+        let idx = self.harness.input_idx(name);
+
+        if self.inputs[idx] != high {
+            self.drive(idx, high);
+            self.changes.push((idx, self.tick()));
+        }
+
+        self
+    }
+
+    /// Runs for `ticks` game ticks.
+    pub fn run_for(&mut self, ticks: u64) -> &mut Self {
+        let start = self.tick();
+        let mut sink = Delayed {
+            sink: &mut self.events,
+            ticks: start,
+        };
+
+        self.outcome = self.world.run_for_traced(ticks, &mut sink);
+        self
+    }
+
+    /// Sets the input called `name` for `ticks` game ticks, and back again after.
+    pub fn pulse(&mut self, name: &str, ticks: u64) -> &mut Self {
+        let high = self.inputs[self.harness.input_idx(name)];
+
+        self.set(name, !high).run_for(ticks).set(name, high)
+    }
+
+    pub fn is_high(&self, output: &str) -> bool {
+        let vec3 = self.harness.output_vec3(output);
+
+        self.world
+            .get(vec3)
+            .is_some_and(|redstone| redstone.redstate().is_on())
+    }
+
+    /// Every output, in the order they were named.
+    pub fn outputs(&self) -> Vec<bool> {
+        self.harness
+            .outputs
+            .iter()
+            .map(|(name, _)| self.is_high(name))
+            .collect()
+    }
+
+    /// The game ticks at which the output called `name` turned on or off, since the world
+    /// settled.
+    pub fn edges(&self, output: &str) -> Vec<(u64, bool)> {
+        let vec3 = self.harness.output_vec3(output);
+        let mut edges: Vec<(u64, bool)> = Vec::new();
+
+        for event in &self.events {
+            if let TraceEvent::PowerChanged {
+                tick,
+                vec3: Some(at),
+                was_on,
+                is_on,
+                ..
+            } = *event
+            {
+                if at == vec3 && was_on != is_on {
+                    edges.push((tick, is_on));
+                }
+            }
+        }
+
+        edges
+    }
+
+    /// Checks that the output called `output` rose exactly `ticks` game ticks after the latest
+    /// change to the input called `input`, and not before.
+    pub fn assert_rises_after(&self, input: &str, output: &str, ticks: u64) {
+        self.assert_edge_after(input, output, true, ticks);
+    }
+
+    /// Checks that the output called `output` fell exactly `ticks` game ticks after the latest
+    /// change to the input called `input`, and not before.
+    pub fn assert_falls_after(&self, input: &str, output: &str, ticks: u64) {
+        self.assert_edge_after(input, output, false, ticks);
+    }
+
+    fn assert_edge_after(&self, input: &str, output: &str, rises: bool, ticks: u64) {
+        let idx = self.harness.input_idx(input);
+        let Some(&(_, changed)) = self.changes.iter().rev().find(|&&(i, _)| i == idx) else {
+            self.fail(&format!("{input} never changed"));
+        };
+
+        let edge = self
+            .edges(output)
+            .into_iter()
+            .find(|&(tick, _)| tick >= changed);
+
+        match edge {
+            Some((tick, is_on)) if is_on == rises && tick - changed == ticks => (),
+            Some((tick, is_on)) => self.fail(&format!(
+                "{output} {} {} ticks after {input} changed at {changed}, expected it to {} \
+                 after {ticks}",
+                if is_on { "rose" } else { "fell" },
+                tick - changed,
+                if rises { "rise" } else { "fall" },
+            )),
+            None => self.fail(&format!(
+                "{output} never {} after {input} changed at {changed}",
+                if rises { "rose" } else { "fell" },
+            )),
+        }
+    }
+
+    /// Checks that the output called `output` gave exactly one pulse, `ticks` game ticks long.
+    pub fn assert_pulse(&self, output: &str, ticks: u64) {
+        match self.edges(output)[..] {
+            [(rose, true), (fell, false)] if fell - rose == ticks => (),
+            [(rose, true), (fell, false)] => self.fail(&format!(
+                "{output} pulsed for {} ticks from {rose}, expected {ticks}",
+                fell - rose
+            )),
+            ref edges => self.fail(&format!(
+                "{output} went through {edges:?}, expected a single pulse"
+            )),
+        }
+    }
+
+    /// Every event, a line each, with those from while the world first settled apart.
+    pub fn trace(&self) -> String {
+        let lines = |events: &[TraceEvent]| -> String {
+            events.iter().map(|event| format!("  {event}\n")).collect()
+        };
+
+        format!(
+            "settling:\n{}since:\n{}",
+            lines(&self.settling),
+            lines(&self.events)
+        )
+    }
+
+    fn fail(&self, message: &str) -> ! {
+        panic!(
+            "{message}, with {}\n{}",
+            Harness::describe(self.harness.inputs.iter().map(|(n, ..)| n), &self.inputs),
+            self.trace()
+        )
+    }
+}
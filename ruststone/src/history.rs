@@ -0,0 +1,268 @@
+//! Recording a world as it runs, so that it can be rewound to any earlier tick and replayed.
+//!
+//! A [`History`] keeps what changed at every tick along with a snapshot of the whole world every
+//! [`KEYFRAME_TICKS`] game ticks. Rewinding restores the latest snapshot before the tick and
+//! replays whatever was applied to the world since, so stepping forward again after a rewind
+//! goes through the same ticks. Applying something new after a rewind forgets what was applied
+//! after it the first time around.
+
+use crate::{
+    stimulus::{Action, Script},
+    trace::{Delayed, TraceEvent},
+    vec3::Vec3,
+    world::{Snapshot, World},
+};
+
+/// How many game ticks apart the snapshots of a history are taken.
+pub const KEYFRAME_TICKS: u64 = 64;
+
+/// A redstone whose power changed, and the redstone whose change it was dispatched by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Change {
+    pub tick: u64,
+    pub vec3: Vec3,
+    /// Nothing caused dispatches that were queued up from the start, or by a block update.
+    pub cause: Option<Vec3>,
+    pub old_power: u8,
+    pub new_power: u8,
+    pub was_on: bool,
+    pub is_on: bool,
+}
+
+/// A snapshot of the world and of the torches its script toggled off.
+#[derive(Clone)]
+struct Keyframe {
+    snapshot: Snapshot,
+    script: Script,
+}
+
+pub struct History {
+    world: World,
+    /// Remembers the torches that were toggled off.
+    script: Script,
+    keyframes: Vec<Keyframe>,
+    /// Everything that was applied to the world, with the game tick it was applied at.
+    actions: Vec<(u64, Action)>,
+    /// How many of the actions were applied to the world as it is now.
+    applied: usize,
+    events: Vec<TraceEvent>,
+    changes: Vec<Change>,
+}
+
+impl History {
+    /// Starts recording `world` from the game tick it was stepped up to.
+    pub fn new(world: World) -> History {
+        let keyframe = Keyframe {
+            snapshot: world.snapshot(),
+            script: Script::default(),
+        };
+
+        History {
+            world,
+            script: Script::default(),
+            keyframes: vec![keyframe],
+            actions: Vec::new(),
+            applied: 0,
+            events: Vec::new(),
+            changes: Vec::new(),
+        }
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn tick(&self) -> u64 {
+        self.world.tick()
+    }
+
+    /// The game tick the recording started at.
+    pub fn start(&self) -> u64 {
+        self.keyframes[0].snapshot.tick()
+    }
+
+    /// Applies `action` at the current tick. If the history was rewound, whatever was applied
+    /// from this tick on is forgotten.
+    pub fn apply(&mut self, action: Action) {
+        self.actions.truncate(self.applied);
+        self.actions.push((self.tick(), action.clone()));
+        self.applied += 1;
+
+        self.script.apply(&mut self.world, &action);
+    }
+
+    /// Runs for a single redstone tick.
+    pub fn step(&mut self) {
+        self.run_for(2);
+    }
+
+    /// Runs for `ticks` game ticks, rounded up to whole redstone ticks, applying whatever was
+    /// applied along the way the last time around.
+    pub fn run_for(&mut self, ticks: u64) {
+        let until = self.tick() + ticks.div_ceil(2) * 2;
+
+        while self.tick() < until {
+            let now = self.tick();
+            while let Some((_, action)) = self
+                .actions
+                .get(self.applied)
+                .filter(|&&(tick, _)| tick == now)
+            {
+                self.script.apply(&mut self.world, action);
+                self.applied += 1;
+            }
+
+            let next_keyframe = (self.tick() / KEYFRAME_TICKS + 1) * KEYFRAME_TICKS;
+            let mut stop = until.min(next_keyframe);
+            if let Some(&(tick, _)) = self.actions.get(self.applied) {
+                stop = stop.min(tick);
+            }
+
+            self.run_to(stop);
+
+            if self.tick().is_multiple_of(KEYFRAME_TICKS) && self.latest_keyframe() < self.tick() {
+                self.keyframes.push(Keyframe {
+                    snapshot: self.world.snapshot(),
+                    script: self.script.clone(),
+                });
+            }
+        }
+    }
+
+    fn latest_keyframe(&self) -> u64 {
+        self.keyframes.last().unwrap().snapshot.tick()
+    }
+
+    /// Runs up to game tick `stop`, recording what happened.
+    fn run_to(&mut self, stop: u64) {
+        let start = self.tick();
+        let mut events = Vec::new();
+
+        self.world.run_for_traced(
+            stop - start,
+            &mut Delayed {
+                sink: &mut events,
+                ticks: start,
+            },
+        );
+
+        // The ids only hold for the arena the run went through, which is the one left after it.
+        let arena = self.world.arena();
+        let vec3 = |id| arena.get(id).and_then(|redstone| redstone.vec3());
+
+        for event in &events {
+            if let TraceEvent::PowerChanged {
+                tick,
+                vec3: Some(at),
+                cause,
+                old_power,
+                new_power,
+                was_on,
+                is_on,
+                ..
+            } = *event
+            {
+                self.changes.push(Change {
+                    tick,
+                    vec3: at,
+                    cause: cause.and_then(vec3),
+                    old_power,
+                    new_power,
+                    was_on,
+                    is_on,
+                });
+            }
+        }
+
+        self.events.extend(events);
+    }
+
+    /// Puts the world back the way it was at game tick `tick`, rounded down to a whole redstone
+    /// tick, before anything was applied at it.
+    ///
+    /// # Panics
+    ///
+    /// If `tick` is before the recording started.
+    pub fn rewind_to(&mut self, tick: u64) {
+        let tick = tick - tick % 2;
+        assert!(
+            tick >= self.start(),
+            "the recording started at tick {}",
+            self.start()
+        );
+
+        self.keyframes
+            .retain(|keyframe| keyframe.snapshot.tick() <= tick);
+        let keyframe = self.keyframes.last().unwrap().clone();
+        let from = keyframe.snapshot.tick();
+
+        self.world.restore(&keyframe.snapshot);
+        self.script = keyframe.script;
+        self.applied = self.actions.partition_point(|&(t, _)| t < from);
+        self.events.retain(|event| event.tick() < from);
+        self.changes.retain(|change| change.tick < from);
+
+        self.run_for(tick - from);
+    }
+
+    /// Rewinds by a single redstone tick.
+    pub fn step_back(&mut self) {
+        self.rewind_to(self.tick().saturating_sub(2).max(self.start()));
+    }
+
+    /// Every event recorded at game tick `tick`.
+    pub fn events_at(&self, tick: u64) -> &[TraceEvent] {
+        let from = self.events.partition_point(|event| event.tick() < tick);
+        let to = self.events.partition_point(|event| event.tick() <= tick);
+
+        &self.events[from..to]
+    }
+
+    /// Every power change recorded at game tick `tick`, in the order they happened.
+    pub fn changes_at(&self, tick: u64) -> &[Change] {
+        let from = self.changes.partition_point(|change| change.tick < tick);
+        let to = self.changes.partition_point(|change| change.tick <= tick);
+
+        &self.changes[from..to]
+    }
+
+    /// The power change that caused the redstone at `vec3` to change at game tick `tick`: the
+    /// latest change before it of the redstone it was dispatched by. Returns `None` if it didn't
+    /// change then, or if it wasn't dispatched by another redstone.
+    pub fn cause(&self, vec3: Vec3, tick: u64) -> Option<Change> {
+        let idx = self
+            .changes
+            .iter()
+            .rposition(|change| change.tick == tick && change.vec3 == vec3)?;
+
+        self.cause_of(idx).map(|idx| self.changes[idx])
+    }
+
+    fn cause_of(&self, idx: usize) -> Option<usize> {
+        let cause = self.changes[idx].cause?;
+
+        self.changes[..idx]
+            .iter()
+            .rposition(|change| change.vec3 == cause)
+    }
+
+    /// The change of the redstone at `vec3` at game tick `tick`, followed by its cause, the cause
+    /// of that, and so on for as far back as the recording goes.
+    pub fn causal_chain(&self, vec3: Vec3, tick: u64) -> Vec<Change> {
+        let Some(mut idx) = self
+            .changes
+            .iter()
+            .rposition(|change| change.tick == tick && change.vec3 == vec3)
+        else {
+            return Vec::new();
+        };
+
+        let mut chain = vec![self.changes[idx]];
+        while let Some(cause) = self.cause_of(idx) {
+            chain.push(self.changes[cause]);
+            idx = cause;
+        }
+
+        chain
+    }
+}
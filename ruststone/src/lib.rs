@@ -1,10 +1,26 @@
 mod dispatch;
+mod error;
 mod redstate;
 mod redstone;
+mod storage;
+mod sync;
 
+pub mod ascii;
+pub mod builder;
+pub mod compiled;
+pub mod dot;
+pub mod harness;
+pub mod history;
+pub mod stimulus;
+pub mod structure;
+pub mod text;
+pub mod trace;
+pub mod vcd;
 pub mod vec3;
+pub mod view;
 pub mod voxels;
 pub mod world;
 
-pub use dispatch::RedstoneGraph;
+pub use dispatch::{Budget, RedstoneGraph, RunOutcome};
+pub use error::RuststoneError;
 pub use redstone::*;
@@ -0,0 +1,323 @@
+use std::{
+    env, fs,
+    io::{self, Read, Write},
+    process::{Command, ExitCode, Stdio},
+};
+
+use ruststone::{
+    ascii,
+    dot::Dot,
+    stimulus::Script,
+    text,
+    trace::{TraceSink, TraceWriter},
+    vcd::{Probe, VcdRecorder},
+    vec3::Vec3,
+    view::{Key, Viewer, HELP},
+    world::World,
+    RunOutcome,
+};
+
+const USAGE: &str = "\
+usage: ruststone <world> [options]
+
+Runs the world until it settles, and prints the state of its redstone.
+
+options:
+    --ticks <n>          run for <n> game ticks instead of until it settles
+    --script <file>      apply the stimuli in <file> as the world runs
+    --probe <x,y,z>      only report on the redstone at x, y, z
+    --probe <name>       only report on the redstone called <name>
+    --format <format>    states (the default), vcd, trace, dot, world or layers
+    --output <file>      write to <file> instead of the standard output
+    --threads <n>        use at most <n> threads, or one per core with 0
+    --view               step through the world in the terminal instead
+    --help               print this and exit
+
+Worlds and scripts are plain text. A world either has a voxel per line, like
+`0 1 0 torch:west`, or draws its layers after a `layer <y>` line each. A script
+has a stimulus per line, like `10 toggle 0 1 0`. Schematics can't be read yet.";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    States,
+    Vcd,
+    Trace,
+    Dot,
+    World,
+    Layers,
+}
+
+struct Args {
+    world: String,
+    ticks: Option<u64>,
+    script: Option<String>,
+    probes: Vec<Probe>,
+    format: Format,
+    output: Option<String>,
+    threads: Option<usize>,
+    view: bool,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+        let mut world = None;
+        let mut parsed = Args {
+            world: String::new(),
+            ticks: None,
+            script: None,
+            probes: Vec::new(),
+            format: Format::States,
+            output: None,
+            threads: None,
+            view: false,
+        };
+
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| args.next().ok_or_else(|| format!("{name} needs a value"));
+
+            match arg.as_str() {
+                "--help" | "-h" => return Ok(None),
+                "--ticks" => {
+                    let ticks = value("--ticks")?;
+                    let ticks = ticks
+                        .parse()
+                        .map_err(|_| format!("{ticks:?} is not a number of ticks"))?;
+                    parsed.ticks = Some(ticks);
+                }
+                "--view" => parsed.view = true,
+                "--script" => parsed.script = Some(value("--script")?),
+                "--probe" => parsed.probes.push(Args::probe(&value("--probe")?)),
+                "--format" => {
+                    parsed.format = match value("--format")?.as_str() {
+                        "states" => Format::States,
+                        "vcd" => Format::Vcd,
+                        "trace" => Format::Trace,
+                        "dot" => Format::Dot,
+                        "world" => Format::World,
+                        "layers" => Format::Layers,
+                        format => return Err(format!("{format:?} is not a format")),
+                    }
+                }
+                "--output" => parsed.output = Some(value("--output")?),
+                "--threads" => {
+                    let threads = value("--threads")?;
+                    match threads.parse() {
+                        Ok(threads) => parsed.threads = Some(threads),
+                        _ => return Err(format!("{threads:?} is not a number of threads")),
+                    }
+                }
+                option if option.starts_with("--") => {
+                    return Err(format!("{option:?} is not an option"))
+                }
+                path if world.is_none() => world = Some(String::from(path)),
+                path => return Err(format!("unexpected {path:?}")),
+            }
+        }
+
+        parsed.world = world.ok_or("missing the world")?;
+        Ok(Some(parsed))
+    }
+
+    /// `1,2,3` is a position, and anything else is a name.
+    fn probe(arg: &str) -> Probe {
+        let coordinates: Vec<Option<i32>> = arg.split(',').map(|c| c.trim().parse().ok()).collect();
+
+        match coordinates[..] {
+            [Some(x), Some(y), Some(z)] => Probe::at(Vec3(x, y, z)),
+            _ => Probe::named(arg),
+        }
+    }
+}
+
+fn read(path: &str) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))
+}
+
+fn describe(outcome: RunOutcome) -> String {
+    match outcome {
+        RunOutcome::Stable => String::from("settled"),
+        RunOutcome::Oscillating { period, phase } => {
+            format!("oscillating every {period} ticks from tick {phase}")
+        }
+        RunOutcome::BudgetExhausted => String::from("still running"),
+    }
+}
+
+/// Reads the world at `path`, in whichever of the text formats it is written in.
+fn load(path: &str) -> Result<World, String> {
+    let bytes = fs::read(path).map_err(|e| format!("{path}: {e}"))?;
+
+    // TODO: schematics are gzipped NBT, which needs an inflater and an NBT reader first.
+    let is_schematic = [".schem", ".schematic", ".litematic"]
+        .iter()
+        .any(|extension| path.ends_with(extension));
+    if is_schematic || bytes.starts_with(&[0x1f, 0x8b]) {
+        return Err(format!(
+            "{path}: schematics can't be read yet, only the text formats"
+        ));
+    }
+
+    let contents = String::from_utf8(bytes).map_err(|e| format!("{path}: {e}"))?;
+    let layered = contents
+        .lines()
+        .any(|line| line.trim_start().starts_with("layer"));
+
+    if layered {
+        ascii::parse_world(&contents)
+    } else {
+        text::parse_world(&contents)
+    }
+    .map_err(|e| format!("{path}: {e}"))
+}
+
+fn simulate(args: &Args) -> Result<(), String> {
+    let mut world = load(&args.world)?;
+    let mut script = match &args.script {
+        Some(path) => Script::parse(&read(path)?).map_err(|e| format!("{path}: {e}"))?,
+        None => Script::default(),
+    };
+
+    if let Some(threads) = args.threads {
+        world.set_max_threads(threads);
+    }
+
+    if args.view {
+        return view(world).map_err(|e| e.to_string());
+    }
+
+    // Building the world comes with whatever is wrong with it.
+    for diagnostic in world.validate() {
+        eprintln!("ruststone: warning: {diagnostic}");
+    }
+
+    // Without probes, everything gets reported on.
+    let probes = if args.probes.is_empty() {
+        let mut vec3s: Vec<Vec3> = world
+            .arena()
+            .ids()
+            .filter_map(|id| world.arena()[id].vec3())
+            .collect();
+        vec3s.sort_by_key(|v| (v.x(), v.y(), v.z()));
+        vec3s.into_iter().map(Probe::at).collect()
+    } else {
+        args.probes.clone()
+    };
+
+    let mut vcd = VcdRecorder::new(world.arena(), &probes).map_err(|e| e.to_string())?;
+    let mut trace = TraceWriter::new(Vec::new());
+
+    let sink: &mut dyn TraceSink = match args.format {
+        Format::Vcd => &mut vcd,
+        Format::Trace => &mut trace,
+        _ => &mut (),
+    };
+
+    let outcome = script.run(&mut world, args.ticks, sink);
+    eprintln!("{}", describe(outcome));
+
+    let mut output = Vec::new();
+    match args.format {
+        Format::States => {
+            for probe in &probes {
+                let redstone = match probe {
+                    Probe::At(vec3) => world.get(*vec3),
+                    Probe::Named(name) => world
+                        .arena()
+                        .ids()
+                        .map(|id| &world.arena()[id])
+                        .find(|r| r.name() == *name),
+                };
+
+                match redstone {
+                    Some(redstone) => writeln!(
+                        output,
+                        "{redstone}: {} ({})",
+                        redstone.redstate().get_power(),
+                        if redstone.redstate().is_on() {
+                            "on"
+                        } else {
+                            "off"
+                        }
+                    ),
+                    None => writeln!(output, "{probe}: gone"),
+                }
+                .unwrap();
+            }
+        }
+        Format::Vcd => {
+            vcd.extend_to(world.tick());
+            vcd.write(&mut output).unwrap()
+        }
+        Format::Trace => output = trace.finish().unwrap(),
+        Format::Dot => write!(output, "{}", Dot::arena(world.arena()).with_redstate()).unwrap(),
+        Format::World => output = text::format_world(&world).into_bytes(),
+        Format::Layers => output = ascii::format_world(&world).into_bytes(),
+    }
+
+    match &args.output {
+        Some(path) => fs::write(path, output).map_err(|e| format!("{path}: {e}")),
+        None => io::stdout().write_all(&output).map_err(|e| e.to_string()),
+    }
+}
+
+fn stty(args: &[&str]) -> io::Result<()> {
+    Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .status()
+        .map(|_| ())
+}
+
+/// Hands every key to a [`Viewer`] and redraws it, with the terminal in non-canonical mode so
+/// that keys come in as they're pressed.
+fn view(world: World) -> io::Result<()> {
+    let mut viewer = Viewer::new(world);
+    let mut stdin = io::stdin();
+    let mut stdout = io::stdout();
+
+    stty(&["-icanon", "-echo", "min", "1"])?;
+    print!("\x1b[?25l");
+
+    let result = (|| loop {
+        write!(stdout, "\x1b[H\x1b[2J{}\n{HELP}\n", viewer.render())?;
+        stdout.flush()?;
+
+        let mut buf = [0; 8];
+        let len = stdin.read(&mut buf)?;
+        if len == 0 {
+            return Ok(());
+        }
+
+        if let Some(key) = Key::from_bytes(&buf[..len]) {
+            if !viewer.press(key) {
+                return Ok(());
+            }
+        }
+    })();
+
+    print!("\x1b[?25h");
+    stty(&["icanon", "echo"])?;
+    result
+}
+
+fn main() -> ExitCode {
+    let args = match Args::parse(env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(message) => {
+            eprintln!("ruststone: {message}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match simulate(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("ruststone: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
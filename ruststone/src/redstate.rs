@@ -1,9 +1,10 @@
-use std::cell::Cell;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crate::sync::Flag;
 
-#[derive(Clone, PartialEq, Eq)]
 pub struct Redstate {
-    power: Cell<u8>,
-    forced: Cell<bool>,
+    power: AtomicU8,
+    forced: Flag,
 }
 
 impl Redstate {
@@ -13,13 +14,13 @@ impl Redstate {
 
     pub(crate) fn new(power: u8) -> Redstate {
         Redstate {
-            power: Cell::new(power),
-            forced: Cell::new(false),
+            power: AtomicU8::new(power),
+            forced: Flag::new(false),
         }
     }
 
     pub fn get_power(&self) -> u8 {
-        self.power.get()
+        self.power.load(Ordering::Relaxed)
     }
 
     pub fn is_forced(&self) -> bool {
@@ -27,7 +28,7 @@ impl Redstate {
     }
 
     pub(crate) fn set_power(&self, val: u8) {
-        self.power.set(val);
+        self.power.store(val, Ordering::Relaxed);
     }
 
     pub(crate) fn set_forced(&self, val: bool) {
@@ -42,3 +43,19 @@ impl Redstate {
         !self.is_on()
     }
 }
+
+impl Clone for Redstate {
+    fn clone(&self) -> Redstate {
+        let redstate = Redstate::new(self.get_power());
+        redstate.set_forced(self.is_forced());
+        redstate
+    }
+}
+
+impl PartialEq for Redstate {
+    fn eq(&self, other: &Redstate) -> bool {
+        self.get_power() == other.get_power() && self.is_forced() == other.is_forced()
+    }
+}
+
+impl Eq for Redstate {}
@@ -1,96 +1,519 @@
 use std::{
-    cell::{Cell, RefCell},
     collections::{HashSet, VecDeque},
     fmt::Display,
-    rc::Rc,
+    hash::{Hash, Hasher},
+    mem,
+    ops::Index,
+    sync::Mutex,
 };
 
-use typed_arena::Arena;
+use fnv::FnvHashMap;
 
 use crate::{
-    dispatch::{RedstoneDispatchCtxt, RedstoneEvent, RedstoneDispatch, Frame},
+    dispatch::{Frame, RedstoneDispatch, RedstoneDispatchCtxt, RedstoneEvent},
+    error::RuststoneError,
     redstate::Redstate,
+    sync::{Counter, Flag},
+    vec3::Vec3,
 };
 
-#[derive(Default)]
-pub struct RedstoneArena<'r> {
-    arena: Arena<Redstone<'r>>,
+/// How many edges a redstone can have either way, one through each of its sides.
+const MAX_EDGES: usize = 6;
+
+/// Identifies a [`Redstone`] within the [`RedstoneArena`] that made it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RedstoneId(pub(crate) usize);
+
+impl RedstoneId {
+    pub fn index(self) -> usize {
+        self.0
+    }
 }
 
-impl<'r> RedstoneArena<'r> {
-    pub fn new() -> RedstoneArena<'r> {
+impl Display for RedstoneId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct RedstoneArena {
+    redstones: Vec<Redstone>,
+}
+
+impl Index<RedstoneId> for RedstoneArena {
+    type Output = Redstone;
+
+    fn index(&self, id: RedstoneId) -> &Redstone {
+        &self.redstones[id.0]
+    }
+}
+
+impl RedstoneArena {
+    pub fn new() -> RedstoneArena {
         RedstoneArena {
-            arena: Arena::new(),
+            redstones: Vec::new(),
         }
     }
 
-    pub fn make_torch(&'r self, name: &str) -> &Redstone {
-        self.arena.alloc(Redstone {
+    pub fn len(&self) -> usize {
+        self.redstones.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.redstones.is_empty()
+    }
+
+    pub fn get(&self, id: RedstoneId) -> Option<&Redstone> {
+        self.redstones.get(id.0)
+    }
+
+    /// Every redstone in this arena, in the order they were made.
+    pub fn ids(&self) -> impl Iterator<Item = RedstoneId> {
+        (0..self.redstones.len()).map(RedstoneId)
+    }
+
+    /// Walks every redstone that is connected to `redstone`, breadth first, whichever way the
+    /// edges between them go.
+    pub fn iter(&self, redstone: RedstoneId) -> RedstoneIter<'_> {
+        let mut linked_from: FnvHashMap<RedstoneId, Vec<RedstoneId>> = FnvHashMap::default();
+        for id in self.ids() {
+            for other in self.linked(id) {
+                linked_from.entry(other).or_default().push(id);
+            }
+        }
+
+        let mut iter = RedstoneIter {
+            arena: self,
+            linked_from,
+            queue: VecDeque::new(),
+            visited: HashSet::new(),
+        };
+
+        iter.queue.push_front(redstone);
+
+        iter
+    }
+
+    /// Splits this arena into the groups of redstones that are connected to each other, whichever
+    /// way the edges between them go. Every redstone is in exactly one group; the groups are in
+    /// the order of their first redstone, and each is in the order its redstones were made.
+    pub fn components(&self) -> Vec<Vec<RedstoneId>> {
+        fn find(parents: &mut [usize], mut idx: usize) -> usize {
+            while parents[idx] != idx {
+                parents[idx] = parents[parents[idx]];
+                idx = parents[idx];
+            }
+
+            idx
+        }
+
+        let mut parents: Vec<usize> = (0..self.len()).collect();
+        for id in self.ids() {
+            for other in self.linked(id) {
+                let (a, b) = (find(&mut parents, id.0), find(&mut parents, other.0));
+                // The smaller root wins, so that every root is the first redstone of its group.
+                parents[a.max(b)] = a.min(b);
+            }
+        }
+
+        let mut components: Vec<Vec<RedstoneId>> = Vec::new();
+        let mut slots: FnvHashMap<usize, usize> = FnvHashMap::default();
+        for id in self.ids() {
+            let root = find(&mut parents, id.0);
+            let slot = *slots.entry(root).or_insert_with(|| {
+                components.push(Vec::new());
+                components.len() - 1
+            });
+            components[slot].push(id);
+        }
+
+        components
+    }
+
+    /// Every redstone that `redstone` keeps an edge to, be it incoming, outgoing, a source, a
+    /// quasi-connection or a lock.
+    fn linked(&self, redstone: RedstoneId) -> Vec<RedstoneId> {
+        let mut linked = Vec::new();
+
+        match self[redstone].node() {
+            RedstoneNode::Torch(torch) => {
+                linked.extend(torch.incoming);
+                linked.extend(torch.outgoing.iter());
+            }
+            RedstoneNode::Dust(dust) => {
+                linked.extend(dust.neighbors.iter());
+                linked.extend(dust.sources.iter().map(|&(_, source)| source));
+            }
+            RedstoneNode::Block(block) => {
+                linked.extend(block.incoming.iter());
+                linked.extend(block.outgoing.iter());
+            }
+            RedstoneNode::Repeater(repeater) => {
+                // TODO: This is probably too fragile to rely on for deterministic locking
+                // on this repeater where the neighbors also lock this at the same time.
+                // I'm not sure yet.
+                linked.extend(repeater.neighbors.iter());
+                linked.extend(repeater.incoming);
+                linked.extend(repeater.outgoing);
+            }
+            RedstoneNode::CopperBulb(bulb) => {
+                linked.extend(bulb.incoming.iter());
+                linked.extend(bulb.outgoing.iter());
+            }
+            RedstoneNode::Crafter(crafter) => {
+                linked.extend(crafter.incoming.iter());
+                linked.extend(crafter.outgoing.iter());
+            }
+            RedstoneNode::NoteBlock(note_block) => {
+                linked.extend(note_block.incoming.iter());
+            }
+            RedstoneNode::Mechanism(mechanism) => {
+                linked.extend(mechanism.incoming.iter());
+                linked.extend(mechanism.quasi_incoming.iter());
+            }
+        }
+
+        linked
+    }
+
+    fn alloc(&mut self, name: &str, node: RedstoneNode) -> RedstoneId {
+        self.redstones.push(Redstone {
             name: String::from(name),
+            vec3: None,
             redstate: Redstate::zero(),
-            node: RedstoneNode::Torch(RedstoneTorch {
-                incoming: Cell::new(None),
-                outgoing: RefCell::new(Vec::new()),
+            node,
+        });
+
+        RedstoneId(self.redstones.len() - 1)
+    }
+
+    pub(crate) fn place(&mut self, id: RedstoneId, vec3: Vec3) {
+        self.redstones[id.0].vec3 = Some(vec3);
+    }
+
+    fn node_mut(&mut self, id: RedstoneId) -> &mut RedstoneNode {
+        &mut self.redstones[id.0].node
+    }
+
+    pub fn make_torch(&mut self, name: &str) -> RedstoneId {
+        self.alloc(
+            name,
+            RedstoneNode::Torch(RedstoneTorch {
+                incoming: None,
+                outgoing: Vec::new(),
             }),
-        })
+        )
     }
 
-    pub fn make_dust(&'r self, name: &str) -> &Redstone {
-        self.arena.alloc(Redstone {
-            name: String::from(name),
-            redstate: Redstate::zero(),
-            node: RedstoneNode::Dust(RedstoneDust {
-                neighbors: RefCell::new(Vec::new()),
-                sources: RefCell::new(Vec::new()),
+    pub fn make_dust(&mut self, name: &str) -> RedstoneId {
+        self.alloc(
+            name,
+            RedstoneNode::Dust(RedstoneDust {
+                neighbors: Vec::new(),
+                sources: Vec::new(),
             }),
-        })
+        )
     }
 
-    pub fn make_block(&'r self, name: &str) -> &Redstone {
-        self.arena.alloc(Redstone {
-            name: String::from(name),
-            redstate: Redstate::zero(),
-            node: RedstoneNode::Block(Block {
-                incoming: RefCell::new(Vec::new()),
-                outgoing: RefCell::new(Vec::new()),
+    pub fn make_block(&mut self, name: &str) -> RedstoneId {
+        self.alloc(
+            name,
+            RedstoneNode::Block(Block {
+                incoming: Vec::new(),
+                outgoing: Vec::new(),
             }),
-        })
+        )
     }
 
-    pub fn make_repeater(&'r self, name: &str, delay: u8) -> &Redstone {
-        assert!((1..=4).contains(&delay));
-        self.arena.alloc(Redstone {
-            name: String::from(name),
-            redstate: Redstate::zero(),
-            node: RedstoneNode::Repeater(RedstoneRepeater {
+    /// Fails if `delay` isn't from 1 to 4.
+    pub fn make_repeater(&mut self, name: &str, delay: u8) -> Result<RedstoneId, RuststoneError> {
+        if !(1..=4).contains(&delay) {
+            return Err(RuststoneError::InvalidDelay { delay });
+        }
+
+        Ok(self.alloc(
+            name,
+            RedstoneNode::Repeater(RedstoneRepeater {
                 delay: Frame(delay.into()),
-                incoming: Cell::new(None),
-                outgoing: Cell::new(None),
-                neighbors: RefCell::new(Vec::new()),
+                incoming: None,
+                outgoing: None,
+                neighbors: Vec::new(),
+            }),
+        ))
+    }
+
+    pub fn make_copper_bulb(&mut self, name: &str) -> RedstoneId {
+        self.alloc(
+            name,
+            RedstoneNode::CopperBulb(RedstoneCopperBulb {
+                incoming: Vec::new(),
+                outgoing: Vec::new(),
+                powered: Flag::new(false),
+                lit: Flag::new(false),
+            }),
+        )
+    }
+
+    pub fn make_crafter(&mut self, name: &str) -> RedstoneId {
+        self.alloc(
+            name,
+            RedstoneNode::Crafter(RedstoneCrafter {
+                incoming: Vec::new(),
+                outgoing: Vec::new(),
+                powered: Flag::new(false),
+                slots: Mutex::new([CrafterSlot::Empty; 9]),
+                crafted: Counter::new(0),
+            }),
+        )
+    }
+
+    /// Fails if `pitch` isn't from 0 to 24.
+    pub fn make_note_block(
+        &mut self,
+        name: &str,
+        instrument: Instrument,
+        pitch: u8,
+    ) -> Result<RedstoneId, RuststoneError> {
+        if pitch > 24 {
+            return Err(RuststoneError::InvalidPitch { pitch });
+        }
+
+        Ok(self.alloc(
+            name,
+            RedstoneNode::NoteBlock(RedstoneNoteBlock {
+                instrument,
+                pitch,
+                incoming: Vec::new(),
+                powered: Flag::new(false),
+                played: Mutex::new(Vec::new()),
             }),
-        })
+        ))
+    }
+
+    pub fn make_mechanism(&mut self, name: &str, kind: MechanismKind) -> RedstoneId {
+        self.alloc(
+            name,
+            RedstoneNode::Mechanism(RedstoneMechanism {
+                kind,
+                incoming: Vec::new(),
+                quasi_incoming: Vec::new(),
+                directly_powered: Flag::new(false),
+                quasi_powered: Flag::new(false),
+                active: Flag::new(false),
+                fired: Counter::new(0),
+            }),
+        )
+    }
+
+    /// Links `source` to `target`, so that `target` is dispatched whenever `source` changes.
+    /// Nothing is linked if either of them can't take another edge.
+    pub fn link(&mut self, source: RedstoneId, target: RedstoneId) -> Result<(), RuststoneError> {
+        self.check_outgoing(source)?;
+        self.check_incoming(source, target)?;
+        self.link_outgoing(source, target);
+
+        let is_undirected = self[source].is_undirected();
+        match self.node_mut(target) {
+            RedstoneNode::Torch(torch) => torch.incoming = Some(source),
+            RedstoneNode::Dust(dust) => {
+                if is_undirected {
+                    dust.neighbors.push(source);
+                }
+            }
+            RedstoneNode::Block(block) => block.incoming.push(source),
+            RedstoneNode::Repeater(repeater) => repeater.incoming = Some(source),
+            RedstoneNode::CopperBulb(bulb) => bulb.incoming.push(source),
+            RedstoneNode::Crafter(crafter) => crafter.incoming.push(source),
+            RedstoneNode::NoteBlock(note_block) => note_block.incoming.push(source),
+            RedstoneNode::Mechanism(mechanism) => mechanism.incoming.push(source),
+        }
+
+        Ok(())
+    }
+
+    /// Links `source` to the block above `mechanism` instead of `mechanism` itself, which
+    /// quasi-powers the mechanism.
+    pub fn quasi_link(
+        &mut self,
+        source: RedstoneId,
+        mechanism: RedstoneId,
+    ) -> Result<(), RuststoneError> {
+        let RedstoneNode::Mechanism(node) = self[mechanism].node() else {
+            return Err(self.wrong_kind(mechanism, "mechanism"));
+        };
+
+        if node.quasi_incoming.len() >= MAX_EDGES {
+            return Err(self.too_many_edges(mechanism));
+        }
+
+        self.check_outgoing(source)?;
+        self.link_outgoing(source, mechanism);
+
+        if let RedstoneNode::Mechanism(node) = self.node_mut(mechanism) {
+            node.quasi_incoming.push(source);
+        }
+
+        Ok(())
+    }
+
+    fn too_many_edges(&self, redstone: RedstoneId) -> RuststoneError {
+        RuststoneError::TooManyEdges {
+            redstone,
+            vec3: self[redstone].vec3(),
+        }
+    }
+
+    fn wrong_kind(&self, redstone: RedstoneId, expected: &'static str) -> RuststoneError {
+        RuststoneError::WrongKind {
+            redstone,
+            vec3: self[redstone].vec3(),
+            expected,
+        }
+    }
+
+    fn check_incoming(&self, source: RedstoneId, target: RedstoneId) -> Result<(), RuststoneError> {
+        let is_undirected = self[source].is_undirected();
+        let already_powered = RuststoneError::AlreadyPowered {
+            redstone: target,
+            vec3: self[target].vec3(),
+        };
+
+        let fits = match self[target].node() {
+            RedstoneNode::Torch(torch) if torch.incoming.is_some() => return Err(already_powered),
+            RedstoneNode::Repeater(repeater) if repeater.incoming.is_some() => {
+                return Err(already_powered)
+            }
+            RedstoneNode::Torch(..) | RedstoneNode::Repeater(..) => true,
+            RedstoneNode::Dust(dust) => !is_undirected || dust.neighbors.len() < MAX_EDGES,
+            RedstoneNode::Block(block) => block.incoming.len() < MAX_EDGES,
+            RedstoneNode::CopperBulb(bulb) => bulb.incoming.len() < MAX_EDGES,
+            RedstoneNode::Crafter(crafter) => crafter.incoming.len() < MAX_EDGES,
+            RedstoneNode::NoteBlock(note_block) => note_block.incoming.len() < MAX_EDGES,
+            RedstoneNode::Mechanism(mechanism) => mechanism.incoming.len() < MAX_EDGES,
+        };
+
+        match fits {
+            true => Ok(()),
+            false => Err(self.too_many_edges(target)),
+        }
+    }
+
+    fn check_outgoing(&self, source: RedstoneId) -> Result<(), RuststoneError> {
+        let fits = match self[source].node() {
+            RedstoneNode::Torch(torch) => torch.outgoing.len() < MAX_EDGES,
+            RedstoneNode::Dust(dust) => dust.neighbors.len() < MAX_EDGES,
+            RedstoneNode::Block(block) => block.outgoing.len() < MAX_EDGES,
+            RedstoneNode::Repeater(repeater) => repeater.outgoing.is_none(),
+            RedstoneNode::CopperBulb(bulb) => bulb.outgoing.len() < MAX_EDGES,
+            RedstoneNode::Crafter(crafter) => crafter.outgoing.len() < MAX_EDGES,
+            RedstoneNode::NoteBlock(..) | RedstoneNode::Mechanism(..) => {
+                return Err(RuststoneError::NoOutgoingEdges {
+                    redstone: source,
+                    vec3: self[source].vec3(),
+                })
+            }
+        };
+
+        match fits {
+            true => Ok(()),
+            false => Err(self.too_many_edges(source)),
+        }
+    }
+
+    /// Only ever called once [`RedstoneArena::check_outgoing`] passed.
+    fn link_outgoing(&mut self, source: RedstoneId, target: RedstoneId) {
+        match self.node_mut(source) {
+            RedstoneNode::Torch(torch) => torch.outgoing.push(target),
+            RedstoneNode::Dust(dust) => dust.neighbors.push(target),
+            RedstoneNode::Block(block) => block.outgoing.push(target),
+            RedstoneNode::Repeater(repeater) => repeater.outgoing = Some(target),
+            RedstoneNode::CopperBulb(bulb) => bulb.outgoing.push(target),
+            RedstoneNode::Crafter(crafter) => crafter.outgoing.push(target),
+            RedstoneNode::NoteBlock(..) | RedstoneNode::Mechanism(..) => (),
+        }
+    }
+
+    pub fn add_weighted_edge(
+        &mut self,
+        dust: RedstoneId,
+        source: RedstoneId,
+        weight: u8,
+    ) -> Result<(), RuststoneError> {
+        if let RedstoneNode::Dust(..) = self[source].node() {
+            return Err(RuststoneError::DustAsSource {
+                redstone: source,
+                vec3: self[source].vec3(),
+            });
+        }
+
+        let error = self.wrong_kind(dust, "dust");
+        let RedstoneNode::Dust(dust) = self.node_mut(dust) else {
+            return Err(error);
+        };
+
+        dust.sources.push((weight, source));
+        Ok(())
+    }
+
+    /// Lets `edge` lock `repeater` whenever it is on. A repeater is only ever locked from its two
+    /// sides.
+    pub fn lock(&mut self, repeater: RedstoneId, edge: RedstoneId) -> Result<(), RuststoneError> {
+        // TODO: comparator too.
+        if !matches!(self[edge].node(), RedstoneNode::Repeater(..)) {
+            return Err(self.wrong_kind(edge, "repeater"));
+        }
+
+        let error = self.wrong_kind(repeater, "repeater");
+        let too_many_edges = self.too_many_edges(repeater);
+        let RedstoneNode::Repeater(repeater) = self.node_mut(repeater) else {
+            return Err(error);
+        };
+
+        if repeater.neighbors.len() >= 2 {
+            return Err(too_many_edges);
+        }
+
+        repeater.neighbors.push(edge);
+        Ok(())
+    }
+
+    /// Every repeater that has nothing in front of it, which is always a mistake.
+    pub fn validate(&self) -> Vec<RuststoneError> {
+        self.ids()
+            .filter(
+                |&id| matches!(self[id].node(), RedstoneNode::Repeater(r) if r.outgoing.is_none()),
+            )
+            .map(|id| RuststoneError::RepeaterFacingNothing {
+                redstone: id,
+                vec3: self[id].vec3(),
+            })
+            .collect()
     }
 }
 
-pub struct RedstoneTorch<'r> {
-    pub(crate) incoming: Cell<Option<&'r Redstone<'r>>>,
-    pub(crate) outgoing: RefCell<Vec<&'r Redstone<'r>>>,
+#[derive(Clone)]
+pub struct RedstoneTorch {
+    pub(crate) incoming: Option<RedstoneId>,
+    pub(crate) outgoing: Vec<RedstoneId>,
 }
 
-impl<'r> RedstoneDispatch<'r> for RedstoneTorch<'r> {
-    fn dispatch(&self, event: RedstoneEvent<'r>) -> Vec<Rc<RedstoneDispatchCtxt<'r>>> {
+impl RedstoneDispatch for RedstoneTorch {
+    fn dispatch(&self, arena: &RedstoneArena, event: RedstoneEvent) -> Vec<RedstoneDispatchCtxt> {
         let mut extra = Vec::new();
 
-        match &self.incoming.get() {
-            Some(incoming) => event
-                .redstone
-                .redstate()
-                .set_power(if incoming.redstate().is_on() { 0 } else { 16 }),
-            None => event.redstone.redstate().set_power(16),
-        }
+        let is_lit = match self.incoming {
+            Some(incoming) => !arena[incoming].redstate().is_on(),
+            None => true,
+        };
+
+        // A lit torch strongly powers whatever block it links to.
+        let redstate = arena[event.redstone].redstate();
+        redstate.set_power(if is_lit { 16 } else { 0 });
+        redstate.set_forced(is_lit);
 
-        for out in self.outgoing.borrow().iter() {
-            extra.push(RedstoneDispatchCtxt::new(out, event.current_frame));
+        for &out in self.outgoing.iter() {
+            extra.push(event.schedule(out));
         }
 
         extra
@@ -101,30 +524,27 @@ impl<'r> RedstoneDispatch<'r> for RedstoneTorch<'r> {
     }
 }
 
-pub struct RedstoneDust<'r> {
-    pub(crate) neighbors: RefCell<Vec<&'r Redstone<'r>>>,
-    pub(crate) sources: RefCell<Vec<(u8, &'r Redstone<'r>)>>,
+#[derive(Clone)]
+pub struct RedstoneDust {
+    pub(crate) neighbors: Vec<RedstoneId>,
+    pub(crate) sources: Vec<(u8, RedstoneId)>,
 }
 
-impl<'r> RedstoneDispatch<'r> for RedstoneDust<'r> {
-    fn dispatch(&self, event: RedstoneEvent<'r>) -> Vec<Rc<RedstoneDispatchCtxt<'r>>> {
+impl RedstoneDispatch for RedstoneDust {
+    fn dispatch(&self, arena: &RedstoneArena, event: RedstoneEvent) -> Vec<RedstoneDispatchCtxt> {
         let mut extra = Vec::new();
 
-        let sources = self.sources.borrow();
-        let Some((weight, redstate)) = sources
+        let power = self
+            .sources
             .iter()
-            .map(|(w, r)| (w, r.redstate()))
-            .max_by_key(|(&w, r)| r.get_power().saturating_sub(w))
-        else {
-            return extra;
-        };
+            .map(|&(w, r)| arena[r].redstate().get_power().saturating_sub(w))
+            .max()
+            .unwrap_or(0);
 
-        event.redstone
-            .redstate()
-            .set_power(redstate.get_power().saturating_sub(*weight));
+        arena[event.redstone].redstate().set_power(power);
 
-        for neighbor in self.neighbors.borrow().iter() {
-            extra.push(RedstoneDispatchCtxt::new(neighbor, event.current_frame));
+        for &neighbor in self.neighbors.iter() {
+            extra.push(event.schedule(neighbor));
         }
 
         extra
@@ -136,29 +556,29 @@ impl<'r> RedstoneDispatch<'r> for RedstoneDust<'r> {
 }
 
 // Not the Redstone Block! It's just a block like Sandstone.
-pub struct Block<'r> {
-    pub(crate) incoming: RefCell<Vec<&'r Redstone<'r>>>,
-    pub(crate) outgoing: RefCell<Vec<&'r Redstone<'r>>>,
+#[derive(Clone)]
+pub struct Block {
+    pub(crate) incoming: Vec<RedstoneId>,
+    pub(crate) outgoing: Vec<RedstoneId>,
 }
 
-impl<'r> RedstoneDispatch<'r> for Block<'r> {
-    fn dispatch(&self, event: RedstoneEvent<'r>) -> Vec<Rc<RedstoneDispatchCtxt<'r>>> {
+impl RedstoneDispatch for Block {
+    fn dispatch(&self, arena: &RedstoneArena, event: RedstoneEvent) -> Vec<RedstoneDispatchCtxt> {
         let mut extra = Vec::new();
 
-        let has_power = self.incoming.borrow().iter().any(|r| r.redstate().is_on());
+        let has_power = self.incoming.iter().any(|&r| arena[r].redstate().is_on());
         let is_forced = self
             .incoming
-            .borrow()
             .iter()
-            .any(|r| r.redstate().is_forced());
+            .any(|&r| arena[r].redstate().is_forced());
 
-        event.redstone.redstate().set_forced(has_power);
-        event.redstone
+        arena[event.redstone].redstate().set_forced(has_power);
+        arena[event.redstone]
             .redstate()
             .set_power(if is_forced { 16 } else { 0 });
 
-        for out in self.outgoing.borrow().iter() {
-            extra.push(RedstoneDispatchCtxt::new(out, event.current_frame));
+        for &out in self.outgoing.iter() {
+            extra.push(event.schedule(out));
         }
 
         extra
@@ -169,35 +589,35 @@ impl<'r> RedstoneDispatch<'r> for Block<'r> {
     }
 }
 
-pub struct RedstoneRepeater<'r> {
+#[derive(Clone)]
+pub struct RedstoneRepeater {
     pub(crate) delay: Frame,
-    pub(crate) incoming: Cell<Option<&'r Redstone<'r>>>,
-    pub(crate) outgoing: Cell<Option<&'r Redstone<'r>>>,
-    pub(crate) neighbors: RefCell<Vec<&'r Redstone<'r>>>,
+    pub(crate) incoming: Option<RedstoneId>,
+    pub(crate) outgoing: Option<RedstoneId>,
+    pub(crate) neighbors: Vec<RedstoneId>,
 }
 
-impl<'r> RedstoneDispatch<'r> for RedstoneRepeater<'r> {
-    fn dispatch(&self, event: RedstoneEvent<'r>) -> Vec<Rc<RedstoneDispatchCtxt<'r>>> {
+impl RedstoneDispatch for RedstoneRepeater {
+    fn dispatch(&self, arena: &RedstoneArena, event: RedstoneEvent) -> Vec<RedstoneDispatchCtxt> {
         let mut extra = Vec::new();
 
         // If any neighbors are on, we'll need to lock the redstate of this repeater.
-        if self.neighbors.borrow().iter().any(|n| n.redstate.is_on()) {
+        if self.neighbors.iter().any(|&n| arena[n].redstate.is_on()) {
             return extra;
         }
 
-        let Some(incoming) = self.incoming.get() else {
+        let Some(incoming) = self.incoming else {
             return extra;
         };
 
-        event.redstone
+        let is_on = arena[incoming].redstate().is_on();
+        arena[event.redstone].redstate().set_forced(is_on);
+        arena[event.redstone]
             .redstate()
-            .set_forced(incoming.redstate().is_on());
-        event.redstone
-            .redstate()
-            .set_power(if incoming.redstate().is_on() { 16 } else { 0 });
+            .set_power(if is_on { 16 } else { 0 });
 
-        if let Some(outgoing) = self.outgoing.get() {
-            extra.push(RedstoneDispatchCtxt::new(outgoing, event.current_frame));
+        if let Some(outgoing) = self.outgoing {
+            extra.push(event.schedule(outgoing));
         }
 
         extra
@@ -208,29 +628,360 @@ impl<'r> RedstoneDispatch<'r> for RedstoneRepeater<'r> {
     }
 }
 
-pub enum RedstoneNode<'r> {
-    Torch(RedstoneTorch<'r>),
-    Dust(RedstoneDust<'r>),
-    Block(Block<'r>),
-    Repeater(RedstoneRepeater<'r>),
+#[derive(Clone)]
+pub struct RedstoneCopperBulb {
+    pub(crate) incoming: Vec<RedstoneId>,
+    pub(crate) outgoing: Vec<RedstoneId>,
+    pub(crate) powered: Flag,
+    pub(crate) lit: Flag,
+}
+
+impl RedstoneCopperBulb {
+    pub fn is_lit(&self) -> bool {
+        self.lit.get()
+    }
+
+    /// A lit copper bulb is read as a full signal by a comparator, and an unlit one as nothing.
+    pub fn comparator_output(&self) -> u8 {
+        if self.is_lit() {
+            15
+        } else {
+            0
+        }
+    }
 }
 
-pub struct Redstone<'r> {
+impl RedstoneDispatch for RedstoneCopperBulb {
+    fn dispatch(&self, arena: &RedstoneArena, event: RedstoneEvent) -> Vec<RedstoneDispatchCtxt> {
+        let mut extra = Vec::new();
+
+        // The bulb only toggles on the rising edge, so we have to remember whether it was
+        // already powered the last time it got dispatched.
+        let powered = self.incoming.iter().any(|&r| arena[r].redstate().is_on());
+        if powered && !self.powered.get() {
+            self.lit.set(!self.lit.get());
+        }
+
+        self.powered.set(powered);
+        arena[event.redstone].redstate().set_forced(self.lit.get());
+
+        for &out in self.outgoing.iter() {
+            extra.push(event.schedule(out));
+        }
+
+        extra
+    }
+
+    fn dispatch_frame_offset(&self) -> Frame {
+        Frame(1)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CrafterSlot {
+    Empty,
+    Disabled,
+    Filled(u8),
+}
+
+pub struct RedstoneCrafter {
+    pub(crate) incoming: Vec<RedstoneId>,
+    pub(crate) outgoing: Vec<RedstoneId>,
+    pub(crate) powered: Flag,
+    pub(crate) slots: Mutex<[CrafterSlot; 9]>,
+    pub(crate) crafted: Counter,
+}
+
+impl RedstoneCrafter {
+    pub fn slots(&self) -> [CrafterSlot; 9] {
+        *self.slots.lock().unwrap()
+    }
+
+    /// Fails if there is no slot `idx`.
+    pub fn set_slot(&self, idx: usize, slot: CrafterSlot) -> Result<(), RuststoneError> {
+        let mut slots = self.slots.lock().unwrap();
+        let Some(at) = slots.get_mut(idx) else {
+            return Err(RuststoneError::NoSuchSlot { idx });
+        };
+
+        *at = match slot {
+            CrafterSlot::Filled(0) => CrafterSlot::Empty,
+            slot => slot,
+        };
+        Ok(())
+    }
+
+    /// How many times this crafter fired with something in its grid.
+    pub fn crafted(&self) -> u32 {
+        self.crafted.get()
+    }
+
+    /// Every slot that is either disabled or holds an item adds one to the signal.
+    pub fn comparator_output(&self) -> u8 {
+        self.slots
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|slot| !matches!(slot, CrafterSlot::Empty))
+            .count() as u8
+    }
+
+    // We don't know about recipes, so whatever is in the grid is assumed to craft something
+    // and every filled slot gives up one item.
+    fn craft(&self) {
+        let mut slots = self.slots.lock().unwrap();
+        if !slots
+            .iter()
+            .any(|slot| matches!(slot, CrafterSlot::Filled(..)))
+        {
+            return;
+        }
+
+        for slot in slots.iter_mut() {
+            *slot = match *slot {
+                CrafterSlot::Filled(1) => CrafterSlot::Empty,
+                CrafterSlot::Filled(n) => CrafterSlot::Filled(n - 1),
+                slot => slot,
+            };
+        }
+
+        self.crafted.set(self.crafted.get() + 1);
+    }
+}
+
+impl Clone for RedstoneCrafter {
+    fn clone(&self) -> RedstoneCrafter {
+        RedstoneCrafter {
+            incoming: self.incoming.clone(),
+            outgoing: self.outgoing.clone(),
+            powered: self.powered.clone(),
+            slots: Mutex::new(self.slots()),
+            crafted: self.crafted.clone(),
+        }
+    }
+}
+
+impl RedstoneDispatch for RedstoneCrafter {
+    fn dispatch(&self, arena: &RedstoneArena, event: RedstoneEvent) -> Vec<RedstoneDispatchCtxt> {
+        let mut extra = Vec::new();
+
+        let powered = self.incoming.iter().any(|&r| arena[r].redstate().is_on());
+        if powered && !self.powered.get() {
+            self.craft();
+        }
+
+        self.powered.set(powered);
+        arena[event.redstone].redstate().set_forced(powered);
+
+        for &out in self.outgoing.iter() {
+            extra.push(event.schedule(out));
+        }
+
+        extra
+    }
+
+    // The crafter waits four game ticks before it crafts.
+    fn dispatch_frame_offset(&self) -> Frame {
+        Frame(2)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Instrument {
+    Harp,
+    Basedrum,
+    Snare,
+    Hat,
+    Bass,
+    Flute,
+    Bell,
+    Guitar,
+    Chime,
+    Xylophone,
+    IronXylophone,
+    CowBell,
+    Didgeridoo,
+    Bit,
+    Banjo,
+    Pling,
+}
+
+pub struct RedstoneNoteBlock {
+    pub(crate) instrument: Instrument,
+    pub(crate) pitch: u8,
+    pub(crate) incoming: Vec<RedstoneId>,
+    pub(crate) powered: Flag,
+    pub(crate) played: Mutex<Vec<u64>>,
+}
+
+impl RedstoneNoteBlock {
+    pub fn instrument(&self) -> Instrument {
+        self.instrument
+    }
+
+    pub fn pitch(&self) -> u8 {
+        self.pitch
+    }
+
+    /// The game ticks at which this note block played, in order.
+    pub fn played(&self) -> Vec<u64> {
+        self.played.lock().unwrap().clone()
+    }
+}
+
+impl Clone for RedstoneNoteBlock {
+    fn clone(&self) -> RedstoneNoteBlock {
+        RedstoneNoteBlock {
+            instrument: self.instrument,
+            pitch: self.pitch,
+            incoming: self.incoming.clone(),
+            powered: self.powered.clone(),
+            played: Mutex::new(self.played()),
+        }
+    }
+}
+
+impl RedstoneDispatch for RedstoneNoteBlock {
+    fn dispatch(&self, arena: &RedstoneArena, event: RedstoneEvent) -> Vec<RedstoneDispatchCtxt> {
+        let powered = self.incoming.iter().any(|&r| arena[r].redstate().is_on());
+        if powered && !self.powered.get() {
+            // A frame is a redstone tick, which is two game ticks.
+            self.played.lock().unwrap().push(event.current_frame.0 * 2);
+        }
+
+        self.powered.set(powered);
+        arena[event.redstone].redstate().set_forced(powered);
+
+        Vec::new()
+    }
+
+    fn dispatch_frame_offset(&self) -> Frame {
+        Frame(0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MechanismKind {
+    Piston,
+    Dropper,
+    Dispenser,
+}
+
+/// Pistons, droppers and dispensers. These are quasi-connected: they're also powered by whatever
+/// would power the block above them, but they only notice it once they get a block update.
+#[derive(Clone)]
+pub struct RedstoneMechanism {
+    pub(crate) kind: MechanismKind,
+    pub(crate) incoming: Vec<RedstoneId>,
+    pub(crate) quasi_incoming: Vec<RedstoneId>,
+    pub(crate) directly_powered: Flag,
+    pub(crate) quasi_powered: Flag,
+    pub(crate) active: Flag,
+    pub(crate) fired: Counter,
+}
+
+impl RedstoneMechanism {
+    pub fn kind(&self) -> MechanismKind {
+        self.kind
+    }
+
+    /// Whether the piston is extended, or the dropper or dispenser is triggered.
+    pub fn is_active(&self) -> bool {
+        self.active.get()
+    }
+
+    /// How many times a dropper or dispenser fired. Always zero for pistons.
+    pub fn fired(&self) -> u32 {
+        self.fired.get()
+    }
+
+    pub fn is_directly_powered(&self) -> bool {
+        self.directly_powered.get()
+    }
+
+    pub fn is_quasi_powered(&self) -> bool {
+        self.quasi_powered.get()
+    }
+
+    pub fn is_powered(&self) -> bool {
+        self.is_directly_powered() || self.is_quasi_powered()
+    }
+
+    /// Returns `true` if the power changed since the last block update, e.g. a piston that is
+    /// powered but hasn't extended yet. This is the state BUD switches are built on.
+    pub fn is_budded(&self) -> bool {
+        self.is_powered() != self.is_active()
+    }
+
+    fn is_quasi_cause(&self, cause: RedstoneId) -> bool {
+        self.quasi_incoming.contains(&cause) && !self.incoming.contains(&cause)
+    }
+}
+
+impl RedstoneDispatch for RedstoneMechanism {
+    fn dispatch(&self, arena: &RedstoneArena, event: RedstoneEvent) -> Vec<RedstoneDispatchCtxt> {
+        let is_on = |&r: &RedstoneId| arena[r].redstate().is_on();
+        self.directly_powered.set(self.incoming.iter().any(is_on));
+        self.quasi_powered
+            .set(self.quasi_incoming.iter().any(is_on));
+
+        // Whatever powers the block above is never next to the mechanism itself, so its change
+        // doesn't reach us as a block update.
+        if event.cause.is_some_and(|cause| self.is_quasi_cause(cause)) {
+            return Vec::new();
+        }
+
+        let powered = self.is_powered();
+        if powered && !self.active.get() && self.kind != MechanismKind::Piston {
+            self.fired.set(self.fired.get() + 1);
+        }
+
+        self.active.set(powered);
+        arena[event.redstone].redstate().set_forced(powered);
+
+        Vec::new()
+    }
+
+    fn dispatch_frame_offset(&self) -> Frame {
+        Frame(0)
+    }
+}
+
+#[derive(Clone)]
+pub enum RedstoneNode {
+    Torch(RedstoneTorch),
+    Dust(RedstoneDust),
+    Block(Block),
+    Repeater(RedstoneRepeater),
+    CopperBulb(RedstoneCopperBulb),
+    Crafter(RedstoneCrafter),
+    NoteBlock(RedstoneNoteBlock),
+    Mechanism(RedstoneMechanism),
+}
+
+#[derive(Clone)]
+pub struct Redstone {
     name: String,
+    vec3: Option<Vec3>,
     redstate: Redstate,
-    node: RedstoneNode<'r>,
+    node: RedstoneNode,
 }
 
-impl<'r> Redstone<'r> {
+impl Redstone {
     pub fn name(&self) -> String {
         self.name.clone()
     }
 
+    /// Where this redstone is in the world, if it was made for one.
+    pub fn vec3(&self) -> Option<Vec3> {
+        self.vec3
+    }
+
     pub fn redstate(&self) -> &Redstate {
         &self.redstate
     }
 
-    pub fn node(&self) -> &RedstoneNode<'r> {
+    pub fn node(&self) -> &RedstoneNode {
         &self.node
     }
 
@@ -240,6 +991,10 @@ impl<'r> Redstone<'r> {
             RedstoneNode::Dust(..) => false,
             RedstoneNode::Block(..) => false,
             RedstoneNode::Repeater(..) => true,
+            RedstoneNode::CopperBulb(..) => false,
+            RedstoneNode::Crafter(..) => false,
+            RedstoneNode::NoteBlock(..) => false,
+            RedstoneNode::Mechanism(..) => false,
         }
     }
 
@@ -247,111 +1002,97 @@ impl<'r> Redstone<'r> {
         !self.is_directed()
     }
 
-    pub fn link(&'r self, target: &'r Redstone<'r>) {
+    /// The signal strength a comparator would read out of this redstone, if it can be read at all.
+    pub fn comparator_output(&self) -> Option<u8> {
         match self.node() {
-            RedstoneNode::Torch(torch) => {
-                assert!(torch.outgoing.borrow().len() <= 5);
-                torch.outgoing.borrow_mut().push(target);
-            }
-            RedstoneNode::Dust(dust) => {
-                assert!(dust.neighbors.borrow().len() <= 6);
-                dust.neighbors.borrow_mut().push(target);
+            RedstoneNode::CopperBulb(bulb) => Some(bulb.comparator_output()),
+            RedstoneNode::Crafter(crafter) => Some(crafter.comparator_output()),
+            _ => None,
+        }
+    }
+
+    /// Hashes everything about this redstone that decides how it reacts to its next dispatch.
+    /// Counters and logs, like how often a dropper fired, only ever grow, so they're left out.
+    pub(crate) fn hash_state<H: Hasher>(&self, state: &mut H) {
+        self.redstate.get_power().hash(state);
+        self.redstate.is_forced().hash(state);
+
+        match self.node() {
+            RedstoneNode::CopperBulb(bulb) => {
+                bulb.powered.get().hash(state);
+                bulb.is_lit().hash(state);
             }
-            RedstoneNode::Block(block) => {
-                assert!(block.outgoing.borrow().len() <= 6);
-                block.outgoing.borrow_mut().push(target);
+            RedstoneNode::Crafter(crafter) => {
+                crafter.powered.get().hash(state);
+                crafter.slots().hash(state);
             }
-            RedstoneNode::Repeater(repeater) => {
-                assert!(repeater.outgoing.get().is_none());
-                repeater.outgoing.set(Some(target));
+            RedstoneNode::NoteBlock(note_block) => note_block.powered.get().hash(state),
+            RedstoneNode::Mechanism(mechanism) => {
+                mechanism.is_directly_powered().hash(state);
+                mechanism.is_quasi_powered().hash(state);
+                mechanism.is_active().hash(state);
             }
+            _ => (),
         }
+    }
 
-        match target.node() {
-            RedstoneNode::Torch(torch) => {
-                assert!(torch.incoming.get().is_none());
-                torch.incoming.set(Some(self));
+    /// Takes over the state of `other` if it is the same kind of redstone, so that a rebuilt
+    /// graph carries on from where the old one left off.
+    pub(crate) fn inherit(&self, other: &Redstone) {
+        if mem::discriminant(self.node()) != mem::discriminant(other.node()) {
+            return;
+        }
+
+        self.redstate.set_power(other.redstate.get_power());
+        self.redstate.set_forced(other.redstate.is_forced());
+
+        match (self.node(), other.node()) {
+            (RedstoneNode::CopperBulb(bulb), RedstoneNode::CopperBulb(other)) => {
+                bulb.powered.set(other.powered.get());
+                bulb.lit.set(other.lit.get());
             }
-            RedstoneNode::Dust(dust) => {
-                if self.is_undirected() {
-                    assert!(dust.neighbors.borrow().len() <= 6);
-                    dust.neighbors.borrow_mut().push(self);
-                }
+            (RedstoneNode::Crafter(crafter), RedstoneNode::Crafter(other)) => {
+                crafter.powered.set(other.powered.get());
+                *crafter.slots.lock().unwrap() = other.slots();
+                crafter.crafted.set(other.crafted.get());
             }
-            RedstoneNode::Block(block) => {
-                assert!(block.incoming.borrow().len() <= 6);
-                block.incoming.borrow_mut().push(self);
+            (RedstoneNode::NoteBlock(note_block), RedstoneNode::NoteBlock(other)) => {
+                note_block.powered.set(other.powered.get());
+                *note_block.played.lock().unwrap() = other.played();
             }
-            RedstoneNode::Repeater(repeater) => {
-                if self.is_undirected() {
-                    assert!(repeater.incoming.get().is_none());
-                    repeater.incoming.set(Some(self));
-                }
+            (RedstoneNode::Mechanism(mechanism), RedstoneNode::Mechanism(other)) => {
+                mechanism.directly_powered.set(other.directly_powered.get());
+                mechanism.quasi_powered.set(other.quasi_powered.get());
+                mechanism.active.set(other.active.get());
+                mechanism.fired.set(other.fired.get());
             }
+            _ => (),
         }
     }
 }
 
-pub struct RedstoneIter<'r> {
-    queue: VecDeque<&'r Redstone<'r>>,
-    visited: HashSet<*const Redstone<'r>>,
+pub struct RedstoneIter<'a> {
+    arena: &'a RedstoneArena,
+    /// The redstones with an edge to every redstone, since edges are only kept one way.
+    linked_from: FnvHashMap<RedstoneId, Vec<RedstoneId>>,
+    queue: VecDeque<RedstoneId>,
+    visited: HashSet<RedstoneId>,
 }
 
-impl<'r> Iterator for RedstoneIter<'r> {
-    type Item = &'r Redstone<'r>;
+impl<'a> Iterator for RedstoneIter<'a> {
+    type Item = RedstoneId;
 
     fn next(&mut self) -> Option<Self::Item> {
         while let Some(current) = self.queue.pop_front() {
-            if self.visited.contains(&(current as *const Redstone)) {
+            if self.visited.contains(&current) {
                 continue;
             }
 
-            self.visited.insert(current as *const Redstone);
+            self.visited.insert(current);
 
-            match current.node() {
-                RedstoneNode::Torch(torch) => {
-                    if let Some(incoming) = torch.incoming.get() {
-                        self.queue.push_back(incoming);
-                    }
-
-                    for outgoing in torch.outgoing.borrow().iter() {
-                        self.queue.push_back(outgoing);
-                    }
-                }
-                RedstoneNode::Dust(dust) => {
-                    for neighbor in dust.neighbors.borrow().iter() {
-                        self.queue.push_back(neighbor);
-                    }
-
-                    for (_, source) in dust.sources.borrow().iter() {
-                        self.queue.push_back(source);
-                    }
-                }
-                RedstoneNode::Block(block) => {
-                    for incoming in block.incoming.borrow().iter() {
-                        self.queue.push_back(incoming);
-                    }
-
-                    for outgoing in block.outgoing.borrow().iter() {
-                        self.queue.push_back(outgoing);
-                    }
-                }
-                RedstoneNode::Repeater(repeater) => {
-                    // TODO: This is probably too fragile to rely on for deterministic locking
-                    // on this repeater where the neighbors also lock this at the same time.
-                    // I'm not sure yet.
-                    for neighbor in repeater.neighbors.borrow().iter() {
-                        self.queue.push_back(neighbor);
-                    }
-
-                    if let Some(incoming) = repeater.incoming.get() {
-                        self.queue.push_back(incoming);
-                    }
-
-                    if let Some(outgoing) = repeater.outgoing.get() {
-                        self.queue.push_back(outgoing);
-                    }
-                }
+            self.queue.extend(self.arena.linked(current));
+            if let Some(linked_from) = self.linked_from.get(&current) {
+                self.queue.extend(linked_from.iter());
             }
 
             return Some(current);
@@ -361,35 +1102,23 @@ impl<'r> Iterator for RedstoneIter<'r> {
     }
 }
 
-impl<'r> IntoIterator for &'r Redstone<'r> {
-    type Item = &'r Redstone<'r>;
-    type IntoIter = RedstoneIter<'r>;
-
-    fn into_iter(self) -> Self::IntoIter {
-        let mut iter = RedstoneIter {
-            queue: VecDeque::new(),
-            visited: HashSet::new(),
-        };
-
-        iter.queue.push_front(self);
-
-        iter
-    }
-}
-
-impl<'r> Display for Redstone<'r> {
+impl Display for Redstone {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(self.name().as_str())
     }
 }
 
-impl<'r> RedstoneDispatch<'r> for Redstone<'r> {
-    fn dispatch(&self, event: RedstoneEvent<'r>) -> Vec<Rc<RedstoneDispatchCtxt<'r>>> {
+impl RedstoneDispatch for Redstone {
+    fn dispatch(&self, arena: &RedstoneArena, event: RedstoneEvent) -> Vec<RedstoneDispatchCtxt> {
         match self.node() {
-            RedstoneNode::Torch(torch) => torch.dispatch(event),
-            RedstoneNode::Dust(dust) => dust.dispatch(event),
-            RedstoneNode::Block(block) => block.dispatch(event),
-            RedstoneNode::Repeater(repeater) => repeater.dispatch(event),
+            RedstoneNode::Torch(torch) => torch.dispatch(arena, event),
+            RedstoneNode::Dust(dust) => dust.dispatch(arena, event),
+            RedstoneNode::Block(block) => block.dispatch(arena, event),
+            RedstoneNode::Repeater(repeater) => repeater.dispatch(arena, event),
+            RedstoneNode::CopperBulb(bulb) => bulb.dispatch(arena, event),
+            RedstoneNode::Crafter(crafter) => crafter.dispatch(arena, event),
+            RedstoneNode::NoteBlock(note_block) => note_block.dispatch(arena, event),
+            RedstoneNode::Mechanism(mechanism) => mechanism.dispatch(arena, event),
         }
     }
 
@@ -399,29 +1128,10 @@ impl<'r> RedstoneDispatch<'r> for Redstone<'r> {
             RedstoneNode::Dust(dust) => dust.dispatch_frame_offset(),
             RedstoneNode::Block(block) => block.dispatch_frame_offset(),
             RedstoneNode::Repeater(repeater) => repeater.dispatch_frame_offset(),
+            RedstoneNode::CopperBulb(bulb) => bulb.dispatch_frame_offset(),
+            RedstoneNode::Crafter(crafter) => crafter.dispatch_frame_offset(),
+            RedstoneNode::NoteBlock(note_block) => note_block.dispatch_frame_offset(),
+            RedstoneNode::Mechanism(mechanism) => mechanism.dispatch_frame_offset(),
         }
     }
 }
-
-pub fn add_weighted_edge<'r>(dust: &'r Redstone<'r>, source: &'r Redstone<'r>, weight: u8) {
-    let RedstoneNode::Dust(dust) = dust.node() else {
-        panic!("`dust` must be a RedstoneDust");
-    };
-
-    if let RedstoneNode::Dust(..) = source.node() {
-        panic!("`source` cannot be a RedstoneDust");
-    }
-
-    dust.sources.borrow_mut().push((weight, source));
-}
-
-pub fn lock<'r>(repeater: &'r Redstone<'r>, edge: &'r Redstone<'r>) {
-    let RedstoneNode::Repeater(repeater) = repeater.node() else {
-        panic!("`repeater` must be a RedstoneRepeater");
-    };
-
-    assert!((0..=2).contains(&repeater.neighbors.borrow().len()));
-    assert!(matches!(edge.node(), RedstoneNode::Repeater(..))); // TODO: comparator too.
-
-    repeater.neighbors.borrow_mut().push(edge);
-}
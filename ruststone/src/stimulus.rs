@@ -0,0 +1,182 @@
+//! Scripts that poke at a world while it runs. Every line is the game tick at which something
+//! happens, followed by what happens:
+//!
+//! ```text
+//! # Break the torch at (0, 1, 0) at tick 10, and put it back at tick 30.
+//! 10 toggle 0 1 0
+//! 30 toggle 0 1 0
+//! 40 place 3 1 0 torch:west
+//! 50 break 3 1 0
+//! 60 update 5 0 0
+//! ```
+//!
+//! Worlds run a redstone tick, or two game ticks, at a time, so every tick has to be even. A world
+//! has no levers, so its inputs are torches: toggling one breaks it, and toggling it again puts
+//! it back the way it was.
+
+use crate::{
+    text::{self, ParseError},
+    trace::{Delayed, TraceSink},
+    vec3::Vec3,
+    voxels::Voxel,
+    world::World,
+    RunOutcome,
+};
+
+#[derive(Clone)]
+pub enum Action {
+    /// Breaks the torch at the position, or puts back the torch that was broken there.
+    Toggle(Vec3),
+    Place(Vec3, Voxel),
+    Break(Vec3),
+    /// Delivers a block update to the voxel at the position.
+    Update(Vec3),
+}
+
+#[derive(Clone)]
+pub struct Stimulus {
+    /// The game tick at which this happens.
+    pub tick: u64,
+    pub action: Action,
+}
+
+/// A list of stimuli, ordered by the game tick at which they happen.
+#[derive(Clone, Default)]
+pub struct Script {
+    stimuli: Vec<Stimulus>,
+    /// Torches that were toggled off, to be put back when they're toggled on again.
+    toggled: Vec<(Vec3, Voxel)>,
+}
+
+impl Script {
+    pub fn new(mut stimuli: Vec<Stimulus>) -> Script {
+        stimuli.sort_by_key(|s| s.tick);
+
+        Script {
+            stimuli,
+            toggled: Vec::new(),
+        }
+    }
+
+    /// Reads a script written in the format described at the top of this module.
+    pub fn parse(text: &str) -> Result<Script, ParseError> {
+        let mut stimuli = Vec::new();
+
+        for (line, content) in text::lines(text) {
+            let mut words = content.split_whitespace();
+
+            let tick = words
+                .next()
+                .ok_or_else(|| ParseError::new(line, "missing the tick"))?;
+            let tick = tick
+                .parse::<u64>()
+                .map_err(|_| ParseError::new(line, format!("{tick:?} is not a tick")))?;
+            if !tick.is_multiple_of(2) {
+                return Err(ParseError::new(
+                    line,
+                    format!("{tick} is not a redstone tick, which are even"),
+                ));
+            }
+
+            let action = words
+                .next()
+                .ok_or_else(|| ParseError::new(line, "missing the action"))?;
+            let vec3 = text::parse_vec3(line, &mut words)?;
+
+            let action = match action {
+                "toggle" => Action::Toggle(vec3),
+                "break" => Action::Break(vec3),
+                "update" => Action::Update(vec3),
+                "place" => {
+                    let spec = words
+                        .next()
+                        .ok_or_else(|| ParseError::new(line, "missing the voxel to place"))?;
+                    let voxel = text::parse_voxel(spec)
+                        .map_err(|message| ParseError::new(line, message))?;
+
+                    Action::Place(vec3, voxel)
+                }
+                action => {
+                    return Err(ParseError::new(
+                        line,
+                        format!("{action:?} is not an action"),
+                    ))
+                }
+            };
+
+            if let Some(extra) = words.next() {
+                return Err(ParseError::new(line, format!("unexpected {extra:?}")));
+            }
+
+            stimuli.push(Stimulus { tick, action });
+        }
+
+        Ok(Script::new(stimuli))
+    }
+
+    pub fn stimuli(&self) -> &[Stimulus] {
+        &self.stimuli
+    }
+
+    /// Runs `world` from where it is, applying every stimulus at its tick. Once they're all
+    /// applied, the world runs up to game tick `until`, or until it settles if there is none.
+    /// Everything that happens is recorded into `sink`, at the game tick the world was at. A
+    /// stimulus at an odd tick happens at the even one after it, since the world runs a redstone
+    /// tick at a time.
+    ///
+    /// The runs between stimuli stop on purpose, so they don't count towards the outcome. Without
+    /// `until`, it is the outcome of the run once every stimulus is applied; with it, that of the
+    /// last run before game tick `until`.
+    pub fn run(
+        &mut self,
+        world: &mut World,
+        until: Option<u64>,
+        sink: &mut dyn TraceSink,
+    ) -> RunOutcome {
+        let mut outcome = RunOutcome::Stable;
+
+        for stimulus in self.stimuli.clone() {
+            if until.is_some_and(|until| stimulus.tick > until) {
+                break;
+            }
+
+            if stimulus.tick > world.tick() {
+                outcome = Script::run_for(world, stimulus.tick - world.tick(), sink);
+            }
+
+            self.apply(world, &stimulus.action);
+        }
+
+        match until {
+            Some(until) if until > world.tick() => {
+                Script::run_for(world, until - world.tick(), sink)
+            }
+            Some(_) => outcome,
+            None => {
+                let ticks = world.tick();
+                world.run_traced(&mut Delayed { sink, ticks })
+            }
+        }
+    }
+
+    fn run_for(world: &mut World, ticks: u64, sink: &mut dyn TraceSink) -> RunOutcome {
+        let start = world.tick();
+        world.run_for_traced(ticks, &mut Delayed { sink, ticks: start })
+    }
+
+    pub fn apply(&mut self, world: &mut World, action: &Action) {
+        match action {
+            Action::Toggle(vec3) => {
+                if let Some(idx) = self.toggled.iter().position(|(v, _)| v == vec3) {
+                    world[*vec3] = self.toggled.remove(idx).1;
+                } else if world[*vec3].is_torch() {
+                    self.toggled.push((*vec3, world[*vec3].clone()));
+                    world[*vec3] = Voxel::air().voxel();
+                }
+            }
+            Action::Place(vec3, voxel) => world[*vec3] = voxel.clone(),
+            Action::Break(vec3) => world[*vec3] = Voxel::air().voxel(),
+            Action::Update(vec3) => world.schedule_update(*vec3),
+        }
+    }
+}
@@ -0,0 +1,240 @@
+//! Where a world keeps its voxels. Positions are grouped into sections of 16×16×16 like the
+//! game's chunk format: every section keeps a palette of the voxels it holds, along with an index
+//! into the palette for every position, packed as tightly as the palette allows. Sections that
+//! are nothing but air aren't kept at all.
+
+use fnv::FnvHashMap;
+
+use crate::{vec3::Vec3, voxels::Voxel};
+
+/// How many positions a section is across, along every axis.
+pub(crate) const SECTION_SIZE: i32 = 16;
+const SECTION_VOLUME: usize = (SECTION_SIZE * SECTION_SIZE * SECTION_SIZE) as usize;
+
+/// The fewest bits an index is packed into, once there is more than one voxel to tell apart.
+const MIN_BITS: u32 = 4;
+
+#[derive(Clone, Default)]
+pub(crate) struct VoxelStorage {
+    /// Every section with something in it, by the position of the section.
+    sections: FnvHashMap<Vec3, Section>,
+}
+
+impl VoxelStorage {
+    /// The section that `vec3` is in, and where in it.
+    fn locate(vec3: Vec3) -> (Vec3, usize) {
+        let section = Vec3(
+            vec3.x().div_euclid(SECTION_SIZE),
+            vec3.y().div_euclid(SECTION_SIZE),
+            vec3.z().div_euclid(SECTION_SIZE),
+        );
+
+        // Positions are laid out a layer at a time, then a row at a time, the same way regions
+        // are iterated, so that neighbors along a row sit right next to each other.
+        let Vec3(x, y, z) = vec3 - VoxelStorage::origin(section);
+        let local = (y * SECTION_SIZE + z) * SECTION_SIZE + x;
+
+        (section, local as usize)
+    }
+
+    fn position(section: Vec3, local: usize) -> Vec3 {
+        let local = local as i32;
+        let offset = Vec3(
+            local % SECTION_SIZE,
+            local / (SECTION_SIZE * SECTION_SIZE),
+            local / SECTION_SIZE % SECTION_SIZE,
+        );
+
+        VoxelStorage::origin(section) + offset
+    }
+
+    /// The smallest corner of `section`.
+    fn origin(section: Vec3) -> Vec3 {
+        Vec3(
+            section.x() * SECTION_SIZE,
+            section.y() * SECTION_SIZE,
+            section.z() * SECTION_SIZE,
+        )
+    }
+
+    pub(crate) fn get(&self, vec3: Vec3) -> &Voxel {
+        let (section, local) = VoxelStorage::locate(vec3);
+
+        self.sections
+            .get(&section)
+            .map(|section| section.get(local))
+            .unwrap_or_default()
+    }
+
+    /// The voxel at `vec3` to be changed in place. Whatever it's changed to is only looked up in
+    /// the palette the next time the section is changed, or the storage is settled.
+    pub(crate) fn get_mut(&mut self, vec3: Vec3) -> &mut Voxel {
+        let (section, local) = VoxelStorage::locate(vec3);
+
+        self.sections.entry(section).or_default().get_mut(local)
+    }
+
+    pub(crate) fn set(&mut self, vec3: Vec3, voxel: Voxel) {
+        *self.get_mut(vec3) = voxel;
+    }
+
+    /// Looks up whatever was changed in the palettes, and forgets every section that's left with
+    /// nothing but air.
+    pub(crate) fn settle(&mut self) {
+        self.sections.retain(|_, section| {
+            section.settle();
+            !section.is_empty()
+        });
+    }
+
+    /// Every voxel that isn't air, a section at a time.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (Vec3, &Voxel)> {
+        self.sections.iter().flat_map(|(&at, section)| {
+            (0..SECTION_VOLUME).filter_map(move |local| {
+                let voxel = section.get(local);
+                (!voxel.is_air()).then(|| (VoxelStorage::position(at, local), voxel))
+            })
+        })
+    }
+
+    /// How many sections hold anything but air.
+    pub(crate) fn sections(&self) -> usize {
+        self.sections
+            .values()
+            .filter(|section| !section.is_empty())
+            .count()
+    }
+}
+
+#[derive(Clone)]
+struct Section {
+    palette: Vec<Voxel>,
+    /// How many positions use every entry of the palette. Entries that none do are free.
+    counts: Vec<u16>,
+    /// How many bits every index is packed into. With none, every position is the first entry.
+    bits: u32,
+    /// The indices, packed into words without any of them straddling two.
+    data: Vec<u64>,
+    /// The position last handed out to be changed, along with its entry, which nothing else
+    /// uses and which might have been changed into one the palette already has.
+    changing: Option<(usize, usize)>,
+}
+
+impl Default for Section {
+    fn default() -> Section {
+        Section {
+            palette: vec![Voxel::air().voxel()],
+            counts: vec![SECTION_VOLUME as u16],
+            bits: 0,
+            data: Vec::new(),
+            changing: None,
+        }
+    }
+}
+
+impl Section {
+    fn get(&self, local: usize) -> &Voxel {
+        &self.palette[self.index(local)]
+    }
+
+    fn get_mut(&mut self, local: usize) -> &mut Voxel {
+        self.settle();
+
+        let entry = self.index(local);
+        if self.counts[entry] > 1 {
+            self.counts[entry] -= 1;
+
+            let voxel = self.palette[entry].clone();
+            let entry = self.add(voxel);
+            self.set_index(local, entry);
+        }
+
+        let entry = self.index(local);
+        self.changing = Some((local, entry));
+        &mut self.palette[entry]
+    }
+
+    /// Merges the entry last handed out with the same one in the palette, if there is one.
+    fn settle(&mut self) {
+        let Some((local, entry)) = self.changing.take() else {
+            return;
+        };
+
+        let same = (0..self.palette.len()).find(|&other| {
+            other != entry && self.counts[other] > 0 && self.palette[other] == self.palette[entry]
+        });
+
+        if let Some(other) = same {
+            self.counts[entry] = 0;
+            self.counts[other] += 1;
+            self.set_index(local, other);
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.palette
+            .iter()
+            .zip(&self.counts)
+            .all(|(voxel, &count)| count == 0 || voxel.is_air())
+    }
+
+    /// Puts `voxel` into a free entry of the palette, used by a single position, and packs the
+    /// indices wider if it takes more bits than they have.
+    fn add(&mut self, voxel: Voxel) -> usize {
+        let entry = match self.counts.iter().position(|&count| count == 0) {
+            Some(entry) => {
+                self.palette[entry] = voxel;
+                entry
+            }
+            None => {
+                self.palette.push(voxel);
+                self.counts.push(0);
+                self.palette.len() - 1
+            }
+        };
+        self.counts[entry] = 1;
+
+        let bits = usize::BITS - (self.palette.len() - 1).leading_zeros();
+        if bits > self.bits {
+            self.repack(bits.max(MIN_BITS));
+        }
+
+        entry
+    }
+
+    fn per_word(bits: u32) -> usize {
+        (u64::BITS / bits) as usize
+    }
+
+    fn index(&self, local: usize) -> usize {
+        if self.bits == 0 {
+            return 0;
+        }
+
+        let per_word = Section::per_word(self.bits);
+        let word = self.data[local / per_word];
+        let shift = (local % per_word) as u32 * self.bits;
+
+        ((word >> shift) & ((1 << self.bits) - 1)) as usize
+    }
+
+    fn set_index(&mut self, local: usize, entry: usize) {
+        let per_word = Section::per_word(self.bits);
+        let shift = (local % per_word) as u32 * self.bits;
+        let mask = ((1 << self.bits) - 1) << shift;
+        let word = &mut self.data[local / per_word];
+
+        *word = (*word & !mask) | ((entry as u64) << shift);
+    }
+
+    fn repack(&mut self, bits: u32) {
+        let indices: Vec<usize> = (0..SECTION_VOLUME).map(|local| self.index(local)).collect();
+
+        self.bits = bits;
+        self.data = vec![0; SECTION_VOLUME.div_ceil(Section::per_word(bits))];
+
+        for (local, entry) in indices.into_iter().enumerate() {
+            self.set_index(local, entry);
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! Cuboids of voxels lifted out of a world, to be turned around and placed elsewhere:
+//!
+//! ```
+//! # use ruststone::{vec3::{Region, Rotation, Vec3}, voxels::Voxel, world::World};
+//! let mut world = World::new();
+//! world[Vec3(0, 0, 0)] = Voxel::stone().voxel();
+//! world[Vec3(0, 1, 0)] = Voxel::torch().voxel();
+//!
+//! let tower = world.copy(Region::new(Vec3(0, 0, 0), Vec3(0, 1, 0)));
+//! let tower = tower.rotate_y(Rotation::Clockwise);
+//! world.paste(&tower, Vec3(5, 0, 0));
+//! ```
+//!
+//! A structure keeps its voxels relative to its lowest corner, so however it's turned it's
+//! pasted with that corner at the position it's given.
+
+use crate::{
+    vec3::{Axis, Rotation, Vec3},
+    voxels::Voxel,
+};
+
+#[derive(Clone)]
+pub struct Structure {
+    /// How many voxels across the structure is along every axis.
+    pub(crate) size: Vec3,
+    /// Everything but air, relative to the lowest corner, ordered by layer, row and column.
+    pub(crate) voxels: Vec<(Vec3, Voxel)>,
+    /// Named positions, relative to the lowest corner, that turn along with the voxels.
+    pub(crate) ports: Vec<(String, Vec3)>,
+}
+
+impl Structure {
+    pub fn size(&self) -> Vec3 {
+        self.size
+    }
+
+    /// Every voxel that isn't air, relative to the lowest corner.
+    pub fn voxels(&self) -> impl Iterator<Item = (Vec3, &Voxel)> {
+        self.voxels.iter().map(|(vec3, voxel)| (*vec3, voxel))
+    }
+
+    /// Names the position `vec3`, relative to the lowest corner.
+    pub fn with_port(mut self, name: &str, vec3: Vec3) -> Structure {
+        self.ports.push((String::from(name), vec3));
+        self
+    }
+
+    /// The position named `name`, relative to the lowest corner, wherever it was turned to.
+    pub fn port(&self, name: &str) -> Option<Vec3> {
+        self.ports
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|&(_, vec3)| vec3)
+    }
+
+    /// Every named position, in the order they were named.
+    pub fn ports(&self) -> impl Iterator<Item = (&str, Vec3)> {
+        self.ports.iter().map(|(name, vec3)| (name.as_str(), *vec3))
+    }
+
+    /// Turns the structure a quarter around the Y-axis. Turning it twice turns it around.
+    pub fn rotate_y(self, rotation: Rotation) -> Structure {
+        let Vec3(x, y, z) = self.size;
+        let size = Vec3(z, y, x);
+
+        // Whichever corner ends up lowest after turning has to be moved back to the origin.
+        let shift = match rotation {
+            Rotation::Clockwise => Vec3(0, 0, x - 1),
+            Rotation::Counterclockwise => Vec3(z - 1, 0, 0),
+        };
+
+        self.transform(
+            size,
+            |vec3| vec3.rotate_y(rotation) + shift,
+            |voxel| voxel.rotate_y(rotation),
+        )
+    }
+
+    /// Reflects the structure across `axis`, in place.
+    ///
+    /// # Panics
+    ///
+    /// If `axis` is the Y-axis, since torches standing on a block would end up under it.
+    pub fn mirror(self, axis: Axis) -> Structure {
+        let size = self.size;
+        let shift = match axis {
+            Axis::X => Vec3(size.x() - 1, 0, 0),
+            Axis::Y => panic!("structures can't be turned upside down"),
+            Axis::Z => Vec3(0, 0, size.z() - 1),
+        };
+
+        self.transform(
+            size,
+            |vec3| vec3.mirror(axis) + shift,
+            |voxel| voxel.mirror(axis),
+        )
+    }
+
+    fn transform(
+        self,
+        size: Vec3,
+        position: impl Fn(Vec3) -> Vec3,
+        voxel: impl Fn(&Voxel) -> Voxel,
+    ) -> Structure {
+        let mut voxels: Vec<(Vec3, Voxel)> = self
+            .voxels
+            .iter()
+            .map(|(vec3, v)| (position(*vec3), voxel(v)))
+            .collect();
+        voxels.sort_by_key(|&(Vec3(x, y, z), _)| (y, z, x));
+
+        let ports = self
+            .ports
+            .into_iter()
+            .map(|(name, vec3)| (name, position(vec3)))
+            .collect();
+
+        Structure {
+            size,
+            voxels,
+            ports,
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+// Every dispatch happens through a shared reference to the arena, so the state that changes has
+// to be interiorly mutable. These stand in for `Cell` so that the arena can still be shared
+// between threads. A graph is only ever dispatched by one thread at a time, so relaxed ordering
+// is all we need.
+
+#[derive(Debug, Default)]
+pub(crate) struct Flag(AtomicBool);
+
+impl Flag {
+    pub(crate) fn new(val: bool) -> Flag {
+        Flag(AtomicBool::new(val))
+    }
+
+    pub(crate) fn get(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set(&self, val: bool) {
+        self.0.store(val, Ordering::Relaxed);
+    }
+}
+
+impl Clone for Flag {
+    fn clone(&self) -> Flag {
+        Flag::new(self.get())
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Counter(AtomicU32);
+
+impl Counter {
+    pub(crate) fn new(val: u32) -> Counter {
+        Counter(AtomicU32::new(val))
+    }
+
+    pub(crate) fn get(&self) -> u32 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set(&self, val: u32) {
+        self.0.store(val, Ordering::Relaxed);
+    }
+}
+
+impl Clone for Counter {
+    fn clone(&self) -> Counter {
+        Counter::new(self.get())
+    }
+}
@@ -0,0 +1,275 @@
+//! A plain-text world format with one voxel per line:
+//!
+//! ```text
+//! # A torch on a block, powering a line of dust.
+//! 0 0 0 stone
+//! 0 1 0 torch
+//! 1 0 0 stone
+//! 1 1 0 dust
+//! 2 1 0 torch:west
+//! 3 0 0 note_block:12
+//! 0 1 1 repeater:south:3
+//! ```
+//!
+//! Every line is the x, y and z of the voxel followed by what it is. Torches can be given the side
+//! of the block they're placed on, pistons, droppers and dispensers the way they face if it isn't
+//! up, repeaters the way they face and their delay if it isn't 1, note blocks their pitch, and
+//! crafters their nine slots, each either `-` for empty, `x` for disabled, or how many items it
+//! holds. Anything after a `#` is a comment, and anything left out is air.
+
+use std::{error::Error, fmt::Display};
+
+use crate::{
+    vec3::{Direction, Vec3},
+    voxels::{NoteBlockVoxel, RepeaterVoxel, Voxel},
+    world::World,
+    CrafterSlot,
+};
+
+/// What went wrong where, while reading text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// The line it went wrong on, counting from 1.
+    pub line: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub(crate) fn new(line: usize, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for ParseError {}
+
+/// The meaningful part of every line along with its line number, without comments and blank
+/// lines.
+pub(crate) fn lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.lines().enumerate().filter_map(|(idx, line)| {
+        let line = line.split('#').next().unwrap().trim();
+        (!line.is_empty()).then_some((idx + 1, line))
+    })
+}
+
+/// Reads three whitespace separated coordinates off the front of `words`.
+pub(crate) fn parse_vec3<'a>(
+    line: usize,
+    words: &mut impl Iterator<Item = &'a str>,
+) -> Result<Vec3, ParseError> {
+    let mut coordinate = |axis: &str| {
+        let word = words
+            .next()
+            .ok_or_else(|| ParseError::new(line, format!("missing the {axis} coordinate")))?;
+
+        word.parse::<i32>()
+            .map_err(|_| ParseError::new(line, format!("{word:?} is not a coordinate")))
+    };
+
+    Ok(Vec3(coordinate("x")?, coordinate("y")?, coordinate("z")?))
+}
+
+/// Reads a voxel like `torch:west` or `note_block:12`.
+pub fn parse_voxel(spec: &str) -> Result<Voxel, String> {
+    let (kind, arg) = match spec.split_once(':') {
+        Some((kind, arg)) => (kind, Some(arg)),
+        None => (spec, None),
+    };
+
+    let no_arg = |voxel: Voxel| match arg {
+        Some(arg) => Err(format!("{kind} doesn't take {arg:?}")),
+        None => Ok(voxel),
+    };
+
+    match kind {
+        "air" => no_arg(Voxel::air().voxel()),
+        "stone" => no_arg(Voxel::stone().voxel()),
+        "dust" => no_arg(Voxel::dust().voxel()),
+        "copper_bulb" => no_arg(Voxel::copper_bulb().voxel()),
+        "piston" => Ok(Voxel::piston().facing(facing(arg)?).voxel()),
+        "dropper" => Ok(Voxel::dropper().facing(facing(arg)?).voxel()),
+        "dispenser" => Ok(Voxel::dispenser().facing(facing(arg)?).voxel()),
+        "torch" => {
+            let torch = Voxel::torch();
+            let torch = match arg.map(Direction::from_name) {
+                None => torch,
+                Some(Some(facing)) if facing.is_horizontal() => {
+                    torch.facing(facing).map_err(|error| error.to_string())?
+                }
+                Some(_) => return Err(format!("{:?} is not a facing", arg.unwrap())),
+            };
+
+            Ok(torch.voxel())
+        }
+        "repeater" => {
+            let (facing, delay) = match arg.map(|arg| arg.split_once(':').unwrap_or((arg, "1"))) {
+                None => (Direction::North, "1"),
+                Some((facing, delay)) => match Direction::from_name(facing) {
+                    Some(facing) if facing.is_horizontal() => (facing, delay),
+                    _ => return Err(format!("{facing:?} is not a facing")),
+                },
+            };
+
+            let delay = match delay.parse::<u8>() {
+                Ok(delay) if (1..=4).contains(&delay) => delay,
+                _ => return Err(format!("{delay:?} is not a delay from 1 to 4")),
+            };
+
+            Voxel::repeater()
+                .facing(facing)
+                .and_then(|repeater| repeater.delay(delay))
+                .map(RepeaterVoxel::voxel)
+                .map_err(|error| error.to_string())
+        }
+        "note_block" => {
+            let pitch = match arg {
+                None => 0,
+                Some(arg) => match arg.parse::<u8>() {
+                    Ok(pitch) if pitch <= 24 => pitch,
+                    _ => return Err(format!("{arg:?} is not a pitch from 0 to 24")),
+                },
+            };
+
+            Voxel::note_block()
+                .pitch(pitch)
+                .map(NoteBlockVoxel::voxel)
+                .map_err(|error| error.to_string())
+        }
+        "crafter" => {
+            let mut crafter = Voxel::crafter();
+            let Some(arg) = arg else {
+                return Ok(crafter.voxel());
+            };
+
+            let slots: Vec<&str> = arg.split(',').collect();
+            if slots.len() != 9 {
+                return Err(format!("a crafter has 9 slots, not {}", slots.len()));
+            }
+
+            for (idx, slot) in slots.into_iter().enumerate() {
+                let slot = match slot {
+                    "-" => CrafterSlot::Empty,
+                    "x" => CrafterSlot::Disabled,
+                    count => match count.parse::<u8>() {
+                        Ok(count) => CrafterSlot::Filled(count),
+                        Err(_) => return Err(format!("{count:?} is not a crafter slot")),
+                    },
+                };
+
+                crafter = crafter
+                    .with_slot(idx, slot)
+                    .map_err(|error| error.to_string())?;
+            }
+
+            Ok(crafter.voxel())
+        }
+        kind => Err(format!("{kind:?} is not a voxel")),
+    }
+}
+
+/// Reads the way a voxel faces, which is up if it isn't given.
+fn facing(arg: Option<&str>) -> Result<Direction, String> {
+    match arg {
+        None => Ok(Direction::Up),
+        Some(arg) => Direction::from_name(arg).ok_or_else(|| format!("{arg:?} is not a facing")),
+    }
+}
+
+/// Writes a voxel the way [`parse_voxel`] reads it.
+pub fn format_voxel(voxel: &Voxel) -> String {
+    match voxel {
+        Voxel::Air(..) => String::from("air"),
+        Voxel::Stone(..) => String::from("stone"),
+        Voxel::Dust(..) => String::from("dust"),
+        Voxel::CopperBulb(..) => String::from("copper_bulb"),
+        Voxel::Piston(piston) => with_facing("piston", piston.facing),
+        Voxel::Dropper(dropper) => with_facing("dropper", dropper.facing),
+        Voxel::Dispenser(dispenser) => with_facing("dispenser", dispenser.facing),
+        Voxel::Torch(torch) => match torch.facing {
+            None => String::from("torch"),
+            Some(facing) => format!("torch:{facing}"),
+        },
+        Voxel::Repeater(repeater) => match repeater.delay {
+            1 => format!("repeater:{}", repeater.facing),
+            delay => format!("repeater:{}:{delay}", repeater.facing),
+        },
+        Voxel::NoteBlock(note_block) => match note_block.pitch {
+            0 => String::from("note_block"),
+            pitch => format!("note_block:{pitch}"),
+        },
+        Voxel::Crafter(crafter) => {
+            if crafter.slots.iter().all(|&slot| slot == CrafterSlot::Empty) {
+                return String::from("crafter");
+            }
+
+            let slots: Vec<String> = crafter
+                .slots
+                .iter()
+                .map(|slot| match slot {
+                    CrafterSlot::Empty => String::from("-"),
+                    CrafterSlot::Disabled => String::from("x"),
+                    CrafterSlot::Filled(count) => count.to_string(),
+                })
+                .collect();
+
+            format!("crafter:{}", slots.join(","))
+        }
+    }
+}
+
+fn with_facing(kind: &str, facing: Direction) -> String {
+    match facing {
+        Direction::Up => String::from(kind),
+        facing => format!("{kind}:{facing}"),
+    }
+}
+
+/// Reads a world written in the format described at the top of this module.
+pub fn parse_world(text: &str) -> Result<World, ParseError> {
+    let mut world = World::new();
+
+    for (line, content) in lines(text) {
+        let mut words = content.split_whitespace();
+        let vec3 = parse_vec3(line, &mut words)?;
+
+        let spec = words
+            .next()
+            .ok_or_else(|| ParseError::new(line, "missing the voxel"))?;
+        let voxel = parse_voxel(spec).map_err(|message| ParseError::new(line, message))?;
+
+        if let Some(extra) = words.next() {
+            return Err(ParseError::new(line, format!("unexpected {extra:?}")));
+        }
+
+        world[vec3] = voxel;
+    }
+
+    Ok(world)
+}
+
+/// Writes every voxel of `world` that isn't air, ordered by y, then z, then x.
+pub fn format_world(world: &World) -> String {
+    let mut voxels: Vec<(Vec3, &Voxel)> = world.voxels().filter(|(_, v)| !v.is_air()).collect();
+    voxels.sort_by_key(|(v, _)| (v.y(), v.z(), v.x()));
+
+    let mut text = String::new();
+    for (vec3, voxel) in voxels {
+        text.push_str(&format!(
+            "{} {} {} {}\n",
+            vec3.x(),
+            vec3.y(),
+            vec3.z(),
+            format_voxel(voxel)
+        ));
+    }
+
+    text
+}
@@ -0,0 +1,257 @@
+use std::{
+    fmt::Display,
+    io::{self, Write},
+};
+
+use crate::{vec3::Vec3, RedstoneId};
+
+/// Something that happened while a redstone graph was running. Every tick is a game tick, and
+/// every redstone carries its position too, if it was made for a world.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraceEvent {
+    /// `redstone` was dispatched, either because `cause` changed or because it was queued up
+    /// from the start.
+    Dispatched {
+        tick: u64,
+        redstone: RedstoneId,
+        vec3: Option<Vec3>,
+        cause: Option<RedstoneId>,
+    },
+    /// `redstone` was due, but it won't be dispatchable until game tick `until`.
+    Deferred {
+        tick: u64,
+        redstone: RedstoneId,
+        vec3: Option<Vec3>,
+        until: u64,
+    },
+    /// Nothing was left to dispatch at game tick `from`, so the run went on to `to`.
+    FrameAdvanced { from: u64, to: u64 },
+    /// Dispatching `redstone` changed its redstate.
+    PowerChanged {
+        tick: u64,
+        redstone: RedstoneId,
+        vec3: Option<Vec3>,
+        cause: Option<RedstoneId>,
+        old_power: u8,
+        new_power: u8,
+        was_on: bool,
+        is_on: bool,
+    },
+    /// The torch or dust at `vec3` lost the block it was on, and dropped before the run started.
+    /// Unlike the other ticks, `tick` isn't counted from the start of the run, but is the game
+    /// tick the world was at when it dropped.
+    Dropped { tick: u64, vec3: Vec3 },
+}
+
+impl TraceEvent {
+    /// The game tick at which this happened. A frame advance happens at the end of the tick it
+    /// advances from.
+    pub fn tick(&self) -> u64 {
+        match *self {
+            TraceEvent::Dispatched { tick, .. } => tick,
+            TraceEvent::Deferred { tick, .. } => tick,
+            TraceEvent::FrameAdvanced { from, .. } => from,
+            TraceEvent::PowerChanged { tick, .. } => tick,
+            TraceEvent::Dropped { tick, .. } => tick,
+        }
+    }
+
+    /// This same event, `ticks` game ticks later. A drop already knows its game tick, so it is
+    /// left as it is.
+    pub fn delayed(self, ticks: u64) -> TraceEvent {
+        match self {
+            TraceEvent::Dispatched {
+                tick,
+                redstone,
+                vec3,
+                cause,
+            } => TraceEvent::Dispatched {
+                tick: tick + ticks,
+                redstone,
+                vec3,
+                cause,
+            },
+            TraceEvent::Deferred {
+                tick,
+                redstone,
+                vec3,
+                until,
+            } => TraceEvent::Deferred {
+                tick: tick + ticks,
+                redstone,
+                vec3,
+                until: until + ticks,
+            },
+            TraceEvent::FrameAdvanced { from, to } => TraceEvent::FrameAdvanced {
+                from: from + ticks,
+                to: to + ticks,
+            },
+            TraceEvent::PowerChanged {
+                tick,
+                redstone,
+                vec3,
+                cause,
+                old_power,
+                new_power,
+                was_on,
+                is_on,
+            } => TraceEvent::PowerChanged {
+                tick: tick + ticks,
+                redstone,
+                vec3,
+                cause,
+                old_power,
+                new_power,
+                was_on,
+                is_on,
+            },
+            TraceEvent::Dropped { .. } => self,
+        }
+    }
+
+    /// The redstone this happened to, if any.
+    pub fn redstone(&self) -> Option<RedstoneId> {
+        match *self {
+            TraceEvent::Dispatched { redstone, .. } => Some(redstone),
+            TraceEvent::Deferred { redstone, .. } => Some(redstone),
+            TraceEvent::FrameAdvanced { .. } => None,
+            TraceEvent::PowerChanged { redstone, .. } => Some(redstone),
+            TraceEvent::Dropped { .. } => None,
+        }
+    }
+}
+
+pub(crate) struct At(pub(crate) RedstoneId, pub(crate) Option<Vec3>);
+
+impl Display for At {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.1 {
+            Some(vec3) => write!(f, "{} at {vec3}", self.0),
+            None => write!(f, "{}", self.0),
+        }
+    }
+}
+
+struct By(Option<RedstoneId>);
+
+impl Display for By {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(cause) => write!(f, " by {cause}"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Display for TraceEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            TraceEvent::Dispatched {
+                tick,
+                redstone,
+                vec3,
+                cause,
+            } => write!(
+                f,
+                "{tick}: {} was dispatched{}",
+                At(redstone, vec3),
+                By(cause)
+            ),
+            TraceEvent::Deferred {
+                tick,
+                redstone,
+                vec3,
+                until,
+            } => write!(
+                f,
+                "{tick}: {} was deferred until {until}",
+                At(redstone, vec3)
+            ),
+            TraceEvent::FrameAdvanced { from, to } => write!(f, "{from}: advancing to {to}"),
+            TraceEvent::PowerChanged {
+                tick,
+                redstone,
+                vec3,
+                cause,
+                old_power,
+                new_power,
+                was_on,
+                is_on,
+            } => write!(
+                f,
+                "{tick}: {} went from {old_power} ({}) to {new_power} ({}){}",
+                At(redstone, vec3),
+                if was_on { "on" } else { "off" },
+                if is_on { "on" } else { "off" },
+                By(cause)
+            ),
+            TraceEvent::Dropped { tick, vec3 } => write!(f, "{tick}: the voxel at {vec3} dropped"),
+        }
+    }
+}
+
+/// Receives every [`TraceEvent`] of a run, in order.
+pub trait TraceSink: Send {
+    fn record(&mut self, event: &TraceEvent);
+}
+
+/// Drops everything.
+impl TraceSink for () {
+    fn record(&mut self, _: &TraceEvent) {}
+}
+
+impl TraceSink for Vec<TraceEvent> {
+    fn record(&mut self, event: &TraceEvent) {
+        self.push(*event);
+    }
+}
+
+/// Hands every event on to another sink, `ticks` game ticks later. Every run counts its ticks
+/// from zero, so this lines up the events of runs that follow one another.
+pub struct Delayed<'a> {
+    pub sink: &'a mut dyn TraceSink,
+    pub ticks: u64,
+}
+
+impl TraceSink for Delayed<'_> {
+    fn record(&mut self, event: &TraceEvent) {
+        self.sink.record(&event.delayed(self.ticks));
+    }
+}
+
+/// Writes every event on its own line. The first error stops the writing and is kept around.
+pub struct TraceWriter<W: Write + Send> {
+    writer: W,
+    error: Option<io::Error>,
+}
+
+impl<W: Write + Send> TraceWriter<W> {
+    pub fn new(writer: W) -> TraceWriter<W> {
+        TraceWriter {
+            writer,
+            error: None,
+        }
+    }
+
+    /// Flushes the writer and hands it back, or the first error that came up.
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+impl<W: Write + Send> TraceSink for TraceWriter<W> {
+    fn record(&mut self, event: &TraceEvent) {
+        if self.error.is_some() {
+            return;
+        }
+
+        if let Err(error) = writeln!(self.writer, "{event}") {
+            self.error = Some(error);
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    io::{self, Write},
+};
+
+use crate::{
+    trace::{TraceEvent, TraceSink},
+    vec3::Vec3,
+    RedstoneArena, RedstoneId,
+};
+
+/// A redstone to record, found either by its position or by its name.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Probe {
+    At(Vec3),
+    Named(String),
+}
+
+impl Probe {
+    pub fn at(vec3: Vec3) -> Probe {
+        Probe::At(vec3)
+    }
+
+    pub fn named(name: &str) -> Probe {
+        Probe::Named(String::from(name))
+    }
+}
+
+impl Display for Probe {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Probe::At(vec3) => write!(f, "{vec3}"),
+            Probe::Named(name) => write!(f, "{name:?}"),
+        }
+    }
+}
+
+/// A probe that has nothing to record.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownProbe(pub Probe);
+
+impl Display for UnknownProbe {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "There is no redstone at {}", self.0)
+    }
+}
+
+impl Error for UnknownProbe {}
+
+/// Redstone in a world is followed by its position, so that its probe survives the world being
+/// rebuilt. Anything else can only be followed by its id.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Key {
+    At(Vec3),
+    Id(RedstoneId),
+}
+
+struct Signal {
+    label: String,
+    key: Key,
+    power: u8,
+    on: bool,
+}
+
+struct Change {
+    tick: u64,
+    signal: usize,
+    power: u8,
+    on: bool,
+}
+
+/// Records the power of a few probed redstones as a run goes, and writes it out as a Value Change
+/// Dump that waveform viewers like GTKWave can open. Every probe turns into two signals: a 5-bit
+/// one for its power, and a 1-bit one for whether it is on.
+///
+/// The time unit is a game tick, so one redstone tick spans two units.
+pub struct VcdRecorder {
+    signals: Vec<Signal>,
+    changes: Vec<Change>,
+    offset: u64,
+    end: u64,
+}
+
+impl VcdRecorder {
+    /// Finds every probe in `arena`, and takes its current redstate as the initial value. A world
+    /// has to be built for its redstone to be found.
+    pub fn new(arena: &RedstoneArena, probes: &[Probe]) -> Result<VcdRecorder, UnknownProbe> {
+        let mut signals = Vec::new();
+
+        for probe in probes {
+            let found = arena.ids().find(|&id| match probe {
+                Probe::At(vec3) => arena[id].vec3() == Some(*vec3),
+                Probe::Named(name) => arena[id].name() == *name,
+            });
+
+            let Some(id) = found else {
+                return Err(UnknownProbe(probe.clone()));
+            };
+
+            let redstone = &arena[id];
+            signals.push(Signal {
+                label: VcdRecorder::label(&redstone.name()),
+                key: redstone.vec3().map_or(Key::Id(id), Key::At),
+                power: redstone.redstate().get_power(),
+                on: redstone.redstate().is_on(),
+            });
+        }
+
+        Ok(VcdRecorder {
+            signals,
+            changes: Vec::new(),
+            offset: 0,
+            end: 0,
+        })
+    }
+
+    /// Every run starts over from game tick 0. Calling this before a run moves that run to start
+    /// at `tick` instead, so that several runs can be recorded one after the other.
+    pub fn start_run_at(&mut self, tick: u64) {
+        self.offset = tick;
+        self.end = self.end.max(tick);
+    }
+
+    /// Makes the dump last at least until game tick `tick`, even if nothing changes by then.
+    pub fn extend_to(&mut self, tick: u64) {
+        self.end = self.end.max(tick);
+    }
+
+    /// The last game tick recorded so far.
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Writes everything recorded so far. Only the last value of a signal within a tick makes it
+    /// into the dump.
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "$version ruststone $end")?;
+        writeln!(writer, "$timescale 50ms $end")?;
+        writeln!(writer, "$scope module ruststone $end")?;
+
+        for (idx, signal) in self.signals.iter().enumerate() {
+            let (power, on) = VcdRecorder::codes(idx);
+            writeln!(writer, "$var wire 5 {power} {} $end", signal.label)?;
+            writeln!(writer, "$var wire 1 {on} {}_on $end", signal.label)?;
+        }
+
+        writeln!(writer, "$upscope $end")?;
+        writeln!(writer, "$enddefinitions $end")?;
+
+        let mut current: Vec<(u8, bool)> = self.signals.iter().map(|s| (s.power, s.on)).collect();
+
+        writeln!(writer, "#0")?;
+        writeln!(writer, "$dumpvars")?;
+        for (idx, &(power, on)) in current.iter().enumerate() {
+            VcdRecorder::write_power(&mut writer, idx, power)?;
+            VcdRecorder::write_on(&mut writer, idx, on)?;
+        }
+        writeln!(writer, "$end")?;
+
+        let mut changes: Vec<&Change> = self.changes.iter().collect();
+        changes.sort_by_key(|c| c.tick);
+
+        let mut last = 0;
+        let mut changes = changes.as_slice();
+        while let Some(first) = changes.first() {
+            let tick = first.tick;
+            let len = changes.iter().take_while(|c| c.tick == tick).count();
+            let (now, rest) = changes.split_at(len);
+            changes = rest;
+
+            let mut latest = current.clone();
+            for change in now {
+                latest[change.signal] = (change.power, change.on);
+            }
+
+            if latest == current {
+                continue;
+            }
+
+            writeln!(writer, "#{tick}")?;
+            for (idx, (&(power, on), &(old_power, was_on))) in
+                latest.iter().zip(&current).enumerate()
+            {
+                if power != old_power {
+                    VcdRecorder::write_power(&mut writer, idx, power)?;
+                }
+
+                if on != was_on {
+                    VcdRecorder::write_on(&mut writer, idx, on)?;
+                }
+            }
+
+            current = latest;
+            last = tick;
+        }
+
+        if self.end > last {
+            writeln!(writer, "#{}", self.end)?;
+        }
+
+        writer.flush()
+    }
+
+    fn write_power<W: Write>(writer: &mut W, idx: usize, power: u8) -> io::Result<()> {
+        writeln!(writer, "b{power:b} {}", VcdRecorder::codes(idx).0)
+    }
+
+    fn write_on<W: Write>(writer: &mut W, idx: usize, on: bool) -> io::Result<()> {
+        writeln!(writer, "{}{}", u8::from(on), VcdRecorder::codes(idx).1)
+    }
+
+    /// The identifier codes of the power and on signals of the `idx`th probe.
+    fn codes(idx: usize) -> (String, String) {
+        (VcdRecorder::code(2 * idx), VcdRecorder::code(2 * idx + 1))
+    }
+
+    // Identifier codes are written in base 94, using every printable character but space.
+    fn code(mut idx: usize) -> String {
+        let mut code = String::new();
+
+        loop {
+            code.push(char::from(b'!' + (idx % 94) as u8));
+            idx /= 94;
+
+            if idx == 0 {
+                return code;
+            }
+
+            idx -= 1;
+        }
+    }
+
+    // Reference names can't have whitespace in them, and GTKWave reads brackets as bit ranges,
+    // so "torch (0, -1, 0)" becomes "torch_0_-1_0".
+    fn label(name: &str) -> String {
+        name.split(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("_")
+    }
+}
+
+impl TraceSink for VcdRecorder {
+    fn record(&mut self, event: &TraceEvent) {
+        let tick = self.offset + event.tick();
+        self.end = self.end.max(tick);
+
+        let TraceEvent::PowerChanged {
+            redstone,
+            vec3,
+            new_power,
+            is_on,
+            ..
+        } = *event
+        else {
+            return;
+        };
+
+        let key = vec3.map_or(Key::Id(redstone), Key::At);
+        for (signal, _) in self
+            .signals
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.key == key)
+        {
+            self.changes.push(Change {
+                tick,
+                signal,
+                power: new_power,
+                on: is_on,
+            });
+        }
+    }
+}
@@ -31,28 +31,306 @@ impl Vec3 {
         Vec3(self.x().abs(), self.y().abs(), self.z().abs())
     }
 
+    /// Turns the position a quarter around the Y-axis through the origin, the same way
+    /// [`Direction::rotate_y`] turns directions.
+    pub fn rotate_y(self, rotation: Rotation) -> Vec3 {
+        match rotation {
+            Rotation::Clockwise => Vec3(self.z(), self.y(), -self.x()),
+            Rotation::Counterclockwise => Vec3(-self.z(), self.y(), self.x()),
+        }
+    }
+
+    /// Reflects the position in the plane through the origin across `axis`.
+    pub fn mirror(self, axis: Axis) -> Vec3 {
+        match axis {
+            Axis::X => Vec3(-self.x(), self.y(), self.z()),
+            Axis::Y => Vec3(self.x(), -self.y(), self.z()),
+            Axis::Z => Vec3(self.x(), self.y(), -self.z()),
+        }
+    }
+
+    /// The voxel next to this one in `direction`.
+    pub fn offset(self, direction: Direction) -> Vec3 {
+        self + direction.to_vec3()
+    }
+
     pub fn up(self) -> Vec3 {
-        self + Vec3(0, 1, 0)
+        self.offset(Direction::Up)
     }
 
     pub fn down(self) -> Vec3 {
-        self - Vec3(0, 1, 0)
+        self.offset(Direction::Down)
     }
 
     pub fn west(self) -> Vec3 {
-        self + Vec3(1, 0, 0)
+        self.offset(Direction::West)
     }
 
     pub fn east(self) -> Vec3 {
-        self - Vec3(1, 0, 0)
+        self.offset(Direction::East)
     }
 
     pub fn north(self) -> Vec3 {
-        self - Vec3(0, 0, 1)
+        self.offset(Direction::North)
     }
 
     pub fn south(self) -> Vec3 {
-        self + Vec3(0, 0, 1)
+        self.offset(Direction::South)
+    }
+}
+
+/// A cuboid of positions between two corners, both included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Region {
+    min: Vec3,
+    max: Vec3,
+}
+
+impl Region {
+    /// The region between the corners `a` and `b`, whichever way around they are.
+    pub fn new(a: Vec3, b: Vec3) -> Region {
+        Region {
+            min: Vec3(a.x().min(b.x()), a.y().min(b.y()), a.z().min(b.z())),
+            max: Vec3(a.x().max(b.x()), a.y().max(b.y()), a.z().max(b.z())),
+        }
+    }
+
+    /// The region `size` across from its smallest corner `min`.
+    ///
+    /// # Panics
+    ///
+    /// If the region would be empty along any axis.
+    pub fn from_size(min: Vec3, size: Vec3) -> Region {
+        assert!(
+            size.x() > 0 && size.y() > 0 && size.z() > 0,
+            "{size} is not the size of a region"
+        );
+
+        Region {
+            min,
+            max: min + size - Vec3(1, 1, 1),
+        }
+    }
+
+    /// The smallest region around every position in `vec3s`, if there are any.
+    pub fn around(vec3s: impl IntoIterator<Item = Vec3>) -> Option<Region> {
+        let mut vec3s = vec3s.into_iter();
+        let first = vec3s.next()?;
+
+        Some(vec3s.fold(Region::new(first, first), |region, vec3| {
+            region.union(Region::new(vec3, vec3))
+        }))
+    }
+
+    pub fn min(self) -> Vec3 {
+        self.min
+    }
+
+    pub fn max(self) -> Vec3 {
+        self.max
+    }
+
+    /// How many positions across the region is along every axis.
+    pub fn size(self) -> Vec3 {
+        self.max - self.min + Vec3(1, 1, 1)
+    }
+
+    /// How many positions there are in the region.
+    pub fn volume(self) -> u64 {
+        let Vec3(x, y, z) = self.size();
+        x as u64 * y as u64 * z as u64
+    }
+
+    pub fn contains(self, vec3: Vec3) -> bool {
+        (self.min.x()..=self.max.x()).contains(&vec3.x())
+            && (self.min.y()..=self.max.y()).contains(&vec3.y())
+            && (self.min.z()..=self.max.z()).contains(&vec3.z())
+    }
+
+    /// The positions in both regions, if they overlap at all.
+    pub fn intersection(self, other: Region) -> Option<Region> {
+        let min = Vec3(
+            self.min.x().max(other.min.x()),
+            self.min.y().max(other.min.y()),
+            self.min.z().max(other.min.z()),
+        );
+        let max = Vec3(
+            self.max.x().min(other.max.x()),
+            self.max.y().min(other.max.y()),
+            self.max.z().min(other.max.z()),
+        );
+
+        (min.x() <= max.x() && min.y() <= max.y() && min.z() <= max.z())
+            .then_some(Region { min, max })
+    }
+
+    /// The smallest region around both regions.
+    pub fn union(self, other: Region) -> Region {
+        Region::new(
+            Vec3(
+                self.min.x().min(other.min.x()),
+                self.min.y().min(other.min.y()),
+                self.min.z().min(other.min.z()),
+            ),
+            Vec3(
+                self.max.x().max(other.max.x()),
+                self.max.y().max(other.max.y()),
+                self.max.z().max(other.max.z()),
+            ),
+        )
+    }
+
+    /// The region moved by `offset`.
+    pub fn translate(self, offset: Vec3) -> Region {
+        Region {
+            min: self.min + offset,
+            max: self.max + offset,
+        }
+    }
+
+    /// Every position in the region, a layer at a time from the bottom, each a row at a time
+    /// from the north, each from east to west.
+    pub fn iter(self) -> impl Iterator<Item = Vec3> {
+        let Region { min, max } = self;
+
+        (min.y()..=max.y()).flat_map(move |y| {
+            (min.z()..=max.z()).flat_map(move |z| (min.x()..=max.x()).map(move |x| Vec3(x, y, z)))
+        })
+    }
+}
+
+impl Display for Region {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} to {}", self.min, self.max)
+    }
+}
+
+/// One of the six ways out of a voxel, along the coordinate system described on [`Vec3`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    West,
+    East,
+    North,
+    South,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+/// A quarter turn, as seen from above.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rotation {
+    Clockwise,
+    Counterclockwise,
+}
+
+impl Direction {
+    /// Every direction, in the order the neighbors of a voxel are visited.
+    pub const ALL: [Direction; 6] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::West,
+        Direction::East,
+        Direction::North,
+        Direction::South,
+    ];
+
+    /// The four directions that stay on the same level, clockwise from north.
+    pub const HORIZONTAL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::West => Direction::East,
+            Direction::East => Direction::West,
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+        }
+    }
+
+    pub fn axis(self) -> Axis {
+        match self {
+            Direction::West | Direction::East => Axis::X,
+            Direction::Up | Direction::Down => Axis::Y,
+            Direction::North | Direction::South => Axis::Z,
+        }
+    }
+
+    #[must_use]
+    pub fn is_horizontal(self) -> bool {
+        self.axis() != Axis::Y
+    }
+
+    /// Turns the direction a quarter around the Y-axis, the way a compass goes from north to
+    /// east when clockwise. Up and down stay as they are.
+    pub fn rotate_y(self, rotation: Rotation) -> Direction {
+        let Some(idx) = Direction::HORIZONTAL.iter().position(|&d| d == self) else {
+            return self;
+        };
+
+        match rotation {
+            Rotation::Clockwise => Direction::HORIZONTAL[(idx + 1) % 4],
+            Rotation::Counterclockwise => Direction::HORIZONTAL[(idx + 3) % 4],
+        }
+    }
+
+    /// Reflects the direction in the plane across `axis`, so only directions along it flip.
+    pub fn mirror(self, axis: Axis) -> Direction {
+        if self.axis() == axis {
+            self.opposite()
+        } else {
+            self
+        }
+    }
+
+    pub fn to_vec3(self) -> Vec3 {
+        match self {
+            Direction::Up => Vec3(0, 1, 0),
+            Direction::Down => Vec3(0, -1, 0),
+            Direction::West => Vec3(1, 0, 0),
+            Direction::East => Vec3(-1, 0, 0),
+            Direction::North => Vec3(0, 0, -1),
+            Direction::South => Vec3(0, 0, 1),
+        }
+    }
+
+    /// The direction that `vec3` is a single step in, if it is one.
+    pub fn from_vec3(vec3: Vec3) -> Option<Direction> {
+        Direction::ALL.into_iter().find(|d| d.to_vec3() == vec3)
+    }
+
+    /// The direction called `name`, the way it's displayed.
+    pub fn from_name(name: &str) -> Option<Direction> {
+        Direction::ALL.into_iter().find(|d| d.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Direction::Up => "up",
+            Direction::Down => "down",
+            Direction::West => "west",
+            Direction::East => "east",
+            Direction::North => "north",
+            Direction::South => "south",
+        }
+    }
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
     }
 }
 
@@ -0,0 +1,244 @@
+//! A terminal viewer that steps through a world one horizontal layer at a time. The viewer only
+//! keeps track of what to show; reading keys and drawing frames is left to whoever drives it.
+
+use crate::{
+    stimulus::{Action, Script},
+    trace::{Delayed, TraceEvent},
+    vec3::{Direction, Vec3},
+    voxels::Voxel,
+    world::World,
+    RedstoneNode,
+};
+
+/// How many events the viewer holds on to, across all redstone.
+const MAX_EVENTS: usize = 4096;
+
+/// How many events of the selected redstone are shown.
+const SHOWN_EVENTS: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Key {
+    /// Moves the cursor north, towards the top of the screen.
+    North,
+    /// Moves the cursor south, towards the bottom of the screen.
+    South,
+    /// Moves the cursor towards the left of the screen.
+    Left,
+    /// Moves the cursor towards the right of the screen.
+    Right,
+    /// Shows the layer above.
+    Up,
+    /// Shows the layer below.
+    Down,
+    /// Runs for a single redstone tick.
+    Step,
+    /// Runs until the world settles, or oscillates.
+    Settle,
+    /// Toggles the torch under the cursor.
+    Toggle,
+    Quit,
+}
+
+impl Key {
+    /// The key that a chunk of terminal input stands for, arrow keys included.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Key> {
+        match bytes {
+            b"\x1b[A" | b"k" => Some(Key::North),
+            b"\x1b[B" | b"j" => Some(Key::South),
+            b"\x1b[D" | b"h" => Some(Key::Left),
+            b"\x1b[C" | b"l" => Some(Key::Right),
+            b"u" | b">" => Some(Key::Up),
+            b"d" | b"<" => Some(Key::Down),
+            b" " | b"s" => Some(Key::Step),
+            b"r" => Some(Key::Settle),
+            b"t" => Some(Key::Toggle),
+            b"q" | b"\x1b" | b"\x03" => Some(Key::Quit),
+            _ => None,
+        }
+    }
+}
+
+pub const HELP: &str = "arrows/hjkl move  u/d layer  space step  r settle  t toggle torch  q quit";
+
+pub struct Viewer {
+    world: World,
+    /// Remembers the torches that were toggled off.
+    script: Script,
+    layer: i32,
+    cursor: (i32, i32),
+    events: Vec<TraceEvent>,
+}
+
+impl Viewer {
+    /// Shows `world` from its lowest layer, with the cursor in its north-west corner.
+    pub fn new(world: World) -> Viewer {
+        let (min, _) = Viewer::bounds(&world);
+
+        Viewer {
+            world,
+            script: Script::default(),
+            layer: min.y(),
+            cursor: (min.x(), min.z()),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn cursor(&self) -> Vec3 {
+        Vec3(self.cursor.0, self.layer, self.cursor.1)
+    }
+
+    /// Reacts to `key`, and returns `false` once the viewer should close.
+    pub fn press(&mut self, key: Key) -> bool {
+        match key {
+            Key::North => self.cursor.1 -= 1,
+            Key::South => self.cursor.1 += 1,
+            Key::Left => self.cursor.0 -= 1,
+            Key::Right => self.cursor.0 += 1,
+            Key::Up => self.layer += 1,
+            Key::Down => self.layer -= 1,
+            Key::Step => {
+                let ticks = self.world.tick();
+                let mut sink = Delayed {
+                    sink: &mut self.events,
+                    ticks,
+                };
+
+                self.world.run_for_traced(2, &mut sink);
+            }
+            Key::Settle => {
+                let ticks = self.world.tick();
+                let mut sink = Delayed {
+                    sink: &mut self.events,
+                    ticks,
+                };
+
+                self.world.run_traced(&mut sink);
+            }
+            Key::Toggle => {
+                let cursor = self.cursor();
+                self.script.apply(&mut self.world, &Action::Toggle(cursor));
+            }
+            Key::Quit => return false,
+        }
+
+        if self.events.len() > MAX_EVENTS {
+            self.events.drain(..self.events.len() - MAX_EVENTS);
+        }
+
+        true
+    }
+
+    /// The smallest and largest corner of every voxel that isn't air.
+    fn bounds(world: &World) -> (Vec3, Vec3) {
+        world
+            .bounds()
+            .map_or((Vec3(0, 0, 0), Vec3(0, 0, 0)), |bounds| {
+                (bounds.min(), bounds.max())
+            })
+    }
+
+    /// The glyph of the voxel at `vec3`. Dust shows its power as a hex digit, repeaters point the
+    /// way they power and are filled in while they're on, and everything else that can be lit is
+    /// upper case while it is.
+    fn glyph(&self, vec3: Vec3) -> char {
+        let is_on = self
+            .world
+            .get(vec3)
+            .is_some_and(|redstone| redstone.redstate().is_on());
+        let lit = |off: char, on: char| if is_on { on } else { off };
+
+        match &self.world[vec3] {
+            Voxel::Air(..) => '.',
+            Voxel::Stone(..) => lit('#', '@'),
+            Voxel::Torch(..) => lit('i', '*'),
+            Voxel::Dust(..) => {
+                let power = self.world.get(vec3).map_or(0, |r| r.redstate().get_power());
+                char::from_digit(u32::from(power.min(15)), 16).unwrap()
+            }
+            Voxel::Repeater(repeater) => match repeater.facing {
+                Direction::North => lit('^', '▲'),
+                Direction::South => lit('v', '▼'),
+                Direction::West => lit('>', '▶'),
+                _ => lit('<', '◀'),
+            },
+            Voxel::CopperBulb(..) => match self.world.get(vec3).map(|r| r.node()) {
+                Some(RedstoneNode::CopperBulb(bulb)) if bulb.is_lit() => 'B',
+                _ => 'b',
+            },
+            Voxel::Crafter(..) => lit('c', 'C'),
+            Voxel::NoteBlock(..) => lit('n', 'N'),
+            Voxel::Piston(..) => lit('p', 'P'),
+            Voxel::Dropper(..) => lit('r', 'R'),
+            Voxel::Dispenser(..) => lit('s', 'S'),
+        }
+    }
+
+    /// Draws the current layer, the redstone under the cursor and its latest events.
+    pub fn render(&self) -> String {
+        let (min, max) = Viewer::bounds(&self.world);
+        let cursor = self.cursor();
+
+        let (left, right) = (min.x().min(cursor.x()), max.x().max(cursor.x()));
+        let (top, bottom) = (min.z().min(cursor.z()), max.z().max(cursor.z()));
+
+        let mut frame = format!("tick {}  layer y={}\n", self.world.tick(), self.layer);
+
+        for z in top..=bottom {
+            for x in left..=right {
+                let glyph = self.glyph(Vec3(x, self.layer, z));
+
+                if (x, z) == self.cursor {
+                    frame.push('[');
+                    frame.push(glyph);
+                    frame.push(']');
+                } else {
+                    frame.push(' ');
+                    frame.push(glyph);
+                    frame.push(' ');
+                }
+            }
+
+            frame.push('\n');
+        }
+
+        frame.push('\n');
+
+        let Some(redstone) = self.world.get(cursor) else {
+            frame.push_str(&format!("{cursor}: nothing\n"));
+            return frame;
+        };
+
+        let redstate = redstone.redstate();
+        frame.push_str(&format!(
+            "{redstone}: {} ({})\n",
+            redstate.get_power(),
+            if redstate.is_on() { "on" } else { "off" }
+        ));
+
+        let events: Vec<&TraceEvent> = self
+            .events
+            .iter()
+            .filter(|event| Viewer::concerns(event, cursor))
+            .collect();
+
+        for event in &events[events.len().saturating_sub(SHOWN_EVENTS)..] {
+            frame.push_str(&format!("  {event}\n"));
+        }
+
+        frame
+    }
+
+    fn concerns(event: &TraceEvent, vec3: Vec3) -> bool {
+        match *event {
+            TraceEvent::Dispatched { vec3: at, .. }
+            | TraceEvent::Deferred { vec3: at, .. }
+            | TraceEvent::PowerChanged { vec3: at, .. } => at == Some(vec3),
+            TraceEvent::Dropped { vec3: at, .. } => at == vec3,
+            TraceEvent::FrameAdvanced { .. } => false,
+        }
+    }
+}
@@ -1,12 +1,9 @@
-use crate::vec3::Vec3;
-
-pub enum Facing {
-    North,
-    East,
-    West,
-    South,
-}
+use crate::{
+    vec3::{Axis, Direction, Rotation, Vec3},
+    CrafterSlot, Instrument, RuststoneError,
+};
 
+#[derive(Clone, PartialEq, Eq)]
 pub struct AirVoxel;
 
 impl AirVoxel {
@@ -15,6 +12,7 @@ impl AirVoxel {
     }
 }
 
+#[derive(Clone, PartialEq, Eq)]
 pub struct StoneVoxel;
 
 impl StoneVoxel {
@@ -23,10 +21,12 @@ impl StoneVoxel {
     }
 }
 
+#[derive(Clone, PartialEq, Eq)]
 pub struct TorchVoxel {
     /// The facing is the opposite, e.g. if this torch is placed on the east side of the block,
     /// then this facing is East, not West.
-    pub(crate) facing: Option<Facing>,
+    /// A torch standing on the block below has none.
+    pub(crate) facing: Option<Direction>,
 }
 
 impl TorchVoxel {
@@ -34,28 +34,46 @@ impl TorchVoxel {
         Voxel::Torch(self)
     }
 
-    fn set_facing(mut self, facing: Facing) -> TorchVoxel {
+    /// Puts the torch on a wall instead. Fails if `facing` isn't horizontal.
+    pub fn facing(mut self, facing: Direction) -> Result<TorchVoxel, RuststoneError> {
+        if !facing.is_horizontal() {
+            return Err(RuststoneError::NotHorizontal {
+                kind: "torch",
+                facing,
+            });
+        }
+
         self.facing = Some(facing);
+        Ok(self)
+    }
+
+    pub fn facing_north(mut self) -> TorchVoxel {
+        self.facing = Some(Direction::North);
         self
     }
 
-    pub fn facing_north(self) -> TorchVoxel {
-        self.set_facing(Facing::North)
+    pub fn facing_east(mut self) -> TorchVoxel {
+        self.facing = Some(Direction::East);
+        self
     }
 
-    pub fn facing_east(self) -> TorchVoxel {
-        self.set_facing(Facing::East)
+    pub fn facing_west(mut self) -> TorchVoxel {
+        self.facing = Some(Direction::West);
+        self
     }
 
-    pub fn facing_west(self) -> TorchVoxel {
-        self.set_facing(Facing::West)
+    pub fn facing_south(mut self) -> TorchVoxel {
+        self.facing = Some(Direction::South);
+        self
     }
 
-    pub fn facing_south(self) -> TorchVoxel {
-        self.set_facing(Facing::South)
+    /// The position of the block the torch at `vec3` is placed on.
+    pub fn placed_on(&self, vec3: Vec3) -> Vec3 {
+        vec3.offset(self.facing.unwrap_or(Direction::Down))
     }
 }
 
+#[derive(Clone, PartialEq, Eq)]
 pub struct DustVoxel;
 
 impl DustVoxel {
@@ -64,11 +82,162 @@ impl DustVoxel {
     }
 }
 
+#[derive(Clone, PartialEq, Eq)]
+pub struct RepeaterVoxel {
+    /// The side the repeater powers. It is powered from the opposite one.
+    pub(crate) facing: Direction,
+    /// How many redstone ticks it takes to pass the power on, from 1 to 4.
+    pub(crate) delay: u8,
+}
+
+impl RepeaterVoxel {
+    pub fn voxel(self) -> Voxel {
+        Voxel::Repeater(self)
+    }
+
+    /// Fails if `facing` isn't horizontal.
+    pub fn facing(mut self, facing: Direction) -> Result<RepeaterVoxel, RuststoneError> {
+        if !facing.is_horizontal() {
+            return Err(RuststoneError::NotHorizontal {
+                kind: "repeater",
+                facing,
+            });
+        }
+
+        self.facing = facing;
+        Ok(self)
+    }
+
+    /// Fails if `delay` isn't from 1 to 4.
+    pub fn delay(mut self, delay: u8) -> Result<RepeaterVoxel, RuststoneError> {
+        if !(1..=4).contains(&delay) {
+            return Err(RuststoneError::InvalidDelay { delay });
+        }
+
+        self.delay = delay;
+        Ok(self)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct CopperBulbVoxel;
+
+impl CopperBulbVoxel {
+    pub fn voxel(self) -> Voxel {
+        Voxel::CopperBulb(self)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct CrafterVoxel {
+    pub(crate) slots: [CrafterSlot; 9],
+}
+
+impl CrafterVoxel {
+    pub fn voxel(self) -> Voxel {
+        Voxel::Crafter(self)
+    }
+
+    /// Fails if there is no slot `idx`.
+    pub fn with_slot(
+        mut self,
+        idx: usize,
+        slot: CrafterSlot,
+    ) -> Result<CrafterVoxel, RuststoneError> {
+        let Some(at) = self.slots.get_mut(idx) else {
+            return Err(RuststoneError::NoSuchSlot { idx });
+        };
+
+        *at = slot;
+        Ok(self)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct NoteBlockVoxel {
+    pub(crate) pitch: u8,
+}
+
+impl NoteBlockVoxel {
+    pub fn voxel(self) -> Voxel {
+        Voxel::NoteBlock(self)
+    }
+
+    /// Fails if `pitch` isn't from 0 to 24.
+    pub fn pitch(mut self, pitch: u8) -> Result<NoteBlockVoxel, RuststoneError> {
+        if pitch > 24 {
+            return Err(RuststoneError::InvalidPitch { pitch });
+        }
+
+        self.pitch = pitch;
+        Ok(self)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct PistonVoxel {
+    /// The side the piston pushes towards.
+    pub(crate) facing: Direction,
+}
+
+impl PistonVoxel {
+    pub fn voxel(self) -> Voxel {
+        Voxel::Piston(self)
+    }
+
+    pub fn facing(mut self, facing: Direction) -> PistonVoxel {
+        self.facing = facing;
+        self
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct DropperVoxel {
+    /// The side the dropper drops items out of.
+    pub(crate) facing: Direction,
+}
+
+impl DropperVoxel {
+    pub fn voxel(self) -> Voxel {
+        Voxel::Dropper(self)
+    }
+
+    pub fn facing(mut self, facing: Direction) -> DropperVoxel {
+        self.facing = facing;
+        self
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct DispenserVoxel {
+    /// The side the dispenser shoots items out of.
+    pub(crate) facing: Direction,
+}
+
+impl DispenserVoxel {
+    pub fn voxel(self) -> Voxel {
+        Voxel::Dispenser(self)
+    }
+
+    pub fn facing(mut self, facing: Direction) -> DispenserVoxel {
+        self.facing = facing;
+        self
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
 pub enum Voxel {
     Air(AirVoxel),
     Stone(StoneVoxel),
     Torch(TorchVoxel),
     Dust(DustVoxel),
+    Repeater(RepeaterVoxel),
+    CopperBulb(CopperBulbVoxel),
+    Crafter(CrafterVoxel),
+    NoteBlock(NoteBlockVoxel),
+    Piston(PistonVoxel),
+    Dropper(DropperVoxel),
+    Dispenser(DispenserVoxel),
 }
 
 impl Voxel {
@@ -88,12 +257,108 @@ impl Voxel {
         DustVoxel
     }
 
+    /// Faces north with a delay of 1 unless told otherwise.
+    pub fn repeater() -> RepeaterVoxel {
+        RepeaterVoxel {
+            facing: Direction::North,
+            delay: 1,
+        }
+    }
+
+    pub fn copper_bulb() -> CopperBulbVoxel {
+        CopperBulbVoxel
+    }
+
+    pub fn crafter() -> CrafterVoxel {
+        CrafterVoxel {
+            slots: [CrafterSlot::Empty; 9],
+        }
+    }
+
+    pub fn note_block() -> NoteBlockVoxel {
+        NoteBlockVoxel { pitch: 0 }
+    }
+
+    /// Faces up unless told otherwise.
+    pub fn piston() -> PistonVoxel {
+        PistonVoxel {
+            facing: Direction::Up,
+        }
+    }
+
+    /// Faces up unless told otherwise.
+    pub fn dropper() -> DropperVoxel {
+        DropperVoxel {
+            facing: Direction::Up,
+        }
+    }
+
+    /// Faces up unless told otherwise.
+    pub fn dispenser() -> DispenserVoxel {
+        DispenserVoxel {
+            facing: Direction::Up,
+        }
+    }
+
     pub fn get_name(&self, vec3: Vec3) -> String {
         match self {
             Voxel::Air(..) => format!("air {vec3}"),
             Voxel::Stone(..) => format!("stone {vec3}"),
             Voxel::Torch(..) => format!("torch {vec3}"),
             Voxel::Dust(..) => format!("dust {vec3}"),
+            Voxel::Repeater(..) => format!("repeater {vec3}"),
+            Voxel::CopperBulb(..) => format!("copper bulb {vec3}"),
+            Voxel::Crafter(..) => format!("crafter {vec3}"),
+            Voxel::NoteBlock(..) => format!("note block {vec3}"),
+            Voxel::Piston(..) => format!("piston {vec3}"),
+            Voxel::Dropper(..) => format!("dropper {vec3}"),
+            Voxel::Dispenser(..) => format!("dispenser {vec3}"),
+        }
+    }
+
+    /// The way the voxel faces, if it's one that does. Torches standing on the block below
+    /// don't face anywhere.
+    pub fn facing(&self) -> Option<Direction> {
+        match self {
+            Voxel::Torch(torch) => torch.facing,
+            Voxel::Repeater(repeater) => Some(repeater.facing),
+            Voxel::Piston(piston) => Some(piston.facing),
+            Voxel::Dropper(dropper) => Some(dropper.facing),
+            Voxel::Dispenser(dispenser) => Some(dispenser.facing),
+            _ => None,
+        }
+    }
+
+    /// The voxel turned a quarter around the Y-axis.
+    pub fn rotate_y(&self, rotation: Rotation) -> Voxel {
+        self.map_facing(|facing| facing.rotate_y(rotation))
+    }
+
+    /// The voxel reflected across `axis`. Torches standing on the block below keep standing.
+    pub fn mirror(&self, axis: Axis) -> Voxel {
+        self.map_facing(|facing| facing.mirror(axis))
+    }
+
+    fn map_facing(&self, f: impl Fn(Direction) -> Direction) -> Voxel {
+        let mut voxel = self.clone();
+
+        match &mut voxel {
+            Voxel::Torch(torch) => torch.facing = torch.facing.map(f),
+            Voxel::Repeater(RepeaterVoxel { facing, .. })
+            | Voxel::Piston(PistonVoxel { facing })
+            | Voxel::Dropper(DropperVoxel { facing })
+            | Voxel::Dispenser(DispenserVoxel { facing }) => *facing = f(*facing),
+            _ => (),
+        }
+
+        voxel
+    }
+
+    /// The instrument a note block plays when it is placed on top of this voxel.
+    pub fn instrument(&self) -> Instrument {
+        match self {
+            Voxel::Stone(..) => Instrument::Basedrum,
+            _ => Instrument::Harp,
         }
     }
 
@@ -128,6 +393,92 @@ impl Voxel {
     pub fn is_dust(&self) -> bool {
         matches!(self, Self::Dust(..))
     }
+
+    /// Returns `true` if the voxel is [`Repeater`].
+    ///
+    /// [`Repeater`]: Voxel::Repeater
+    #[must_use]
+    pub fn is_repeater(&self) -> bool {
+        matches!(self, Self::Repeater(..))
+    }
+
+    /// Returns `true` if dust can be placed on top of the voxel.
+    #[must_use]
+    pub fn is_solid(&self) -> bool {
+        self.is_stone() || self.is_mechanism()
+    }
+
+    /// Returns `true` if the voxel is activated by redstone power rather than carrying it.
+    #[must_use]
+    pub fn is_mechanism(&self) -> bool {
+        matches!(
+            self,
+            Self::CopperBulb(..)
+                | Self::Crafter(..)
+                | Self::NoteBlock(..)
+                | Self::Piston(..)
+                | Self::Dropper(..)
+                | Self::Dispenser(..)
+        )
+    }
+
+    /// Returns `true` if the voxel is quasi-connected, i.e. it is also powered by whatever
+    /// would power the voxel above it.
+    #[must_use]
+    pub fn is_quasi_connected(&self) -> bool {
+        matches!(
+            self,
+            Self::Piston(..) | Self::Dropper(..) | Self::Dispenser(..)
+        )
+    }
+
+    /// Returns `true` if the voxel is [`CopperBulb`].
+    ///
+    /// [`CopperBulb`]: Voxel::CopperBulb
+    #[must_use]
+    pub fn is_copper_bulb(&self) -> bool {
+        matches!(self, Self::CopperBulb(..))
+    }
+
+    /// Returns `true` if the voxel is [`Crafter`].
+    ///
+    /// [`Crafter`]: Voxel::Crafter
+    #[must_use]
+    pub fn is_crafter(&self) -> bool {
+        matches!(self, Self::Crafter(..))
+    }
+
+    /// Returns `true` if the voxel is [`NoteBlock`].
+    ///
+    /// [`NoteBlock`]: Voxel::NoteBlock
+    #[must_use]
+    pub fn is_note_block(&self) -> bool {
+        matches!(self, Self::NoteBlock(..))
+    }
+
+    /// Returns `true` if the voxel is [`Piston`].
+    ///
+    /// [`Piston`]: Voxel::Piston
+    #[must_use]
+    pub fn is_piston(&self) -> bool {
+        matches!(self, Self::Piston(..))
+    }
+
+    /// Returns `true` if the voxel is [`Dropper`].
+    ///
+    /// [`Dropper`]: Voxel::Dropper
+    #[must_use]
+    pub fn is_dropper(&self) -> bool {
+        matches!(self, Self::Dropper(..))
+    }
+
+    /// Returns `true` if the voxel is [`Dispenser`].
+    ///
+    /// [`Dispenser`]: Voxel::Dispenser
+    #[must_use]
+    pub fn is_dispenser(&self) -> bool {
+        matches!(self, Self::Dispenser(..))
+    }
 }
 
 impl Default for &Voxel {
@@ -1,23 +1,38 @@
 use std::{
-    cell::RefCell,
-    collections::{HashSet, VecDeque},
+    cmp::Reverse,
+    collections::{HashMap, HashSet, VecDeque},
+    mem,
+    num::NonZeroUsize,
     ops::{Index, IndexMut},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Barrier, Mutex,
+    },
+    thread,
 };
 
-use fnv::FnvHashMap;
+use fnv::{FnvHashMap, FnvHashSet};
 
 use crate::{
-    add_weighted_edge,
-    vec3::Vec3,
-    voxels::{DustVoxel, Facing, TorchVoxel, Voxel},
-    RedstoneGraph, Redstone, RedstoneArena,
+    compiled::CompiledWorld,
+    dispatch::{Frame, RedstoneRun},
+    storage::VoxelStorage,
+    structure::Structure,
+    trace::{TraceEvent, TraceSink},
+    vec3::{Direction, Region, Rotation, Vec3},
+    voxels::{DustVoxel, RepeaterVoxel, TorchVoxel, Voxel},
+    Budget, Instrument, MechanismKind, Redstone, RedstoneArena, RedstoneGraph, RedstoneId,
+    RedstoneNode, RunOutcome, RuststoneError,
 };
 
+/// How many drops a world holds on to while nothing records or reports them.
+const MAX_DROPPED: usize = 4096;
+
 #[derive(Clone, Copy)]
-struct VoxelCtxt<'r> {
+struct VoxelCtxt<'w> {
     vec3: Vec3,
-    voxel: &'r Voxel,
-    redstone: Option<&'r Redstone<'r>>,
+    voxel: &'w Voxel,
+    redstone: Option<RedstoneId>,
 }
 
 struct Neighbors<T: Copy> {
@@ -25,10 +40,6 @@ struct Neighbors<T: Copy> {
 }
 
 impl<T: Copy> Neighbors<T> {
-    fn new(up: T, down: T, left: T, right: T, front: T, back: T) -> Neighbors<T> {
-        Neighbors::from_components([up, down, left, right, front, back])
-    }
-
     fn from_components(components: [T; 6]) -> Neighbors<T> {
         Neighbors { components }
     }
@@ -44,22 +55,6 @@ impl<T: Copy> Neighbors<T> {
     fn down(&self) -> &T {
         &self.components[1]
     }
-
-    fn left(&self) -> &T {
-        &self.components[2]
-    }
-
-    fn right(&self) -> &T {
-        &self.components[3]
-    }
-
-    fn front(&self) -> &T {
-        &self.components[4]
-    }
-
-    fn back(&self) -> &T {
-        &self.components[5]
-    }
 }
 
 struct NeighborsIter<'n, T: Copy> {
@@ -89,101 +84,763 @@ impl<'n, T: Copy> IntoIterator for &'n Neighbors<T> {
     }
 }
 
-#[derive(Default)]
-pub struct World<'r> {
-    redstone_arena: RedstoneArena<'r>,
-    voxels: FnvHashMap<Vec3, Voxel>,
-    redstones: RefCell<FnvHashMap<Vec3, &'r Redstone<'r>>>,
+/// A dispatch that a run left pending: the redstone, whatever caused it, and how many frames
+/// after the end of the run it becomes dispatchable.
+type Pending = (RedstoneId, Option<RedstoneId>, Frame);
+
+/// A note block that played, as heard from anywhere in the world.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoteEvent {
+    pub vec3: Vec3,
+    pub instrument: Instrument,
+    pub pitch: u8,
+    pub tick: u64,
+}
+
+/// A world owns all of its state and is both `Send` and `Sync`, so it can be simulated on one
+/// thread while another reads it through a lock. Cloning it takes a snapshot that stays as it is
+/// while the original keeps running.
+#[derive(Clone, Default)]
+pub struct World {
+    voxels: VoxelStorage,
+    redstone_arena: RedstoneArena,
+    redstones: FnvHashMap<Vec3, RedstoneId>,
+    /// Voxels that may have changed since the redstone graphs were last built.
+    edits: FnvHashSet<Vec3>,
+    /// Voxels that get a block update on the next run.
+    updates: FnvHashSet<Vec3>,
+    built: bool,
+    /// Whether the redstone was placed by the last build and hasn't been run since.
+    placed: bool,
+    /// The most threads a run may use, or zero to use one per core.
+    max_threads: usize,
+    budget: Budget,
+    /// The frame that runs have stepped the world up to.
+    frame: Frame,
+    /// Whatever the last run left pending, with the redstone that caused it and how many frames
+    /// from now it becomes dispatchable.
+    pending: Vec<(Vec3, Option<Vec3>, Frame)>,
+    /// Whatever couldn't be linked by the last build.
+    diagnostics: Vec<RuststoneError>,
+    /// Torches and dust that lost their support, in the order they dropped and with the game
+    /// tick they dropped at, until a traced run records them or they are validated. Only the
+    /// latest [`MAX_DROPPED`] are kept.
+    dropped: Vec<(u64, Vec3)>,
+}
+
+/// Everything a [`World`] simulates, as of when it was taken: its voxels, the state of every
+/// redstone, whatever its last run left pending and how far it was stepped. Unlike a clone of the
+/// world, a snapshot leaves out how the world is run, so it can be restored into any world.
+#[derive(Clone)]
+pub struct Snapshot {
+    voxels: VoxelStorage,
+    /// The redstone at every position, with its redstate and whatever else it remembers.
+    redstones: FnvHashMap<Vec3, Redstone>,
+    edits: FnvHashSet<Vec3>,
+    updates: FnvHashSet<Vec3>,
+    built: bool,
+    placed: bool,
+    frame: Frame,
+    pending: Vec<(Vec3, Option<Vec3>, Frame)>,
+}
+
+impl Snapshot {
+    /// The game tick that the world had been stepped up to.
+    pub fn tick(&self) -> u64 {
+        self.frame.ticks()
+    }
 }
 
-impl Index<Vec3> for World<'_> {
+impl Index<Vec3> for World {
     type Output = Voxel;
 
     fn index(&self, vec: Vec3) -> &Voxel {
-        self.voxels.get(&vec).unwrap_or_default()
+        self.voxels.get(vec)
     }
 }
 
-impl IndexMut<Vec3> for World<'_> {
+impl IndexMut<Vec3> for World {
     fn index_mut(&mut self, vec: Vec3) -> &mut Voxel {
-        self.voxels
-            .entry(vec)
-            .or_insert_with(|| Voxel::air().voxel())
+        self.edits.insert(vec);
+        self.voxels.get_mut(vec)
     }
 }
 
-impl<'r> World<'r> {
-    pub fn new() -> World<'r> {
+impl World {
+    pub fn new() -> World {
         World {
+            voxels: VoxelStorage::default(),
             redstone_arena: RedstoneArena::new(),
-            voxels: FnvHashMap::default(),
-            redstones: RefCell::new(FnvHashMap::default()),
+            redstones: FnvHashMap::default(),
+            edits: FnvHashSet::default(),
+            updates: FnvHashSet::default(),
+            built: false,
+            placed: false,
+            max_threads: 0,
+            budget: Budget::default(),
+            frame: Frame(0),
+            pending: Vec::new(),
+            diagnostics: Vec::new(),
+            dropped: Vec::new(),
         }
     }
 
-    pub fn run(&'r self) {
-        for rg in self.get_redstone_graphs() {
-            rg.run();
-        }
+    /// Runs every redstone graph until it settles, oscillates or runs out of budget. A world
+    /// only settles once all of its graphs do.
+    pub fn run(&mut self) -> RunOutcome {
+        self.run_with(None)
+    }
+
+    /// Runs like [`World::run`], and records everything that happens into `sink` along the way.
+    /// The events of every frame are recorded before those of the next, however many threads
+    /// the run is spread over.
+    pub fn run_traced(&mut self, sink: &mut dyn TraceSink) -> RunOutcome {
+        self.run_with(Some(sink))
+    }
+
+    /// Runs for `ticks` game ticks, rounded up to whole redstone ticks, and leaves whatever is
+    /// still pending by then for the next run to pick up. A clock keeps ticking from one call to
+    /// the next. Returns [`RunOutcome::Stable`] if everything settled in time, and
+    /// [`RunOutcome::BudgetExhausted`] otherwise.
+    pub fn run_for(&mut self, ticks: u64) -> RunOutcome {
+        self.run_for_with(ticks, None)
+    }
+
+    /// Runs like [`World::run_for`], and records everything that happens into `sink` along the
+    /// way. Ticks are counted from the start of this call, like they are for every run.
+    pub fn run_for_traced(&mut self, ticks: u64, sink: &mut dyn TraceSink) -> RunOutcome {
+        self.run_for_with(ticks, Some(sink))
+    }
+
+    /// The game tick that runs have stepped the world up to. A run that goes until the world
+    /// settles steps it up to just past the last redstone tick it dispatched.
+    pub fn tick(&self) -> u64 {
+        self.frame.ticks()
+    }
+
+    fn run_with(&mut self, sink: Option<&mut dyn TraceSink>) -> RunOutcome {
+        self.run_until(None, sink)
     }
 
-    pub fn get(&'r self, vec3: Vec3) -> Option<&'r Redstone<'r>> {
-        if let Some(redstone) = self.redstones.borrow().get(&vec3) {
-            return Some(redstone);
+    fn run_for_with(&mut self, ticks: u64, sink: Option<&mut dyn TraceSink>) -> RunOutcome {
+        self.run_until(Some(Frame(ticks.div_ceil(2))), sink)
+    }
+
+    fn run_until(
+        &mut self,
+        stop: Option<Frame>,
+        mut sink: Option<&mut dyn TraceSink>,
+    ) -> RunOutcome {
+        let rgs = self.prepare();
+
+        if let Some(sink) = sink.as_mut() {
+            for (tick, vec3) in mem::take(&mut self.dropped) {
+                sink.record(&TraceEvent::Dropped { tick, vec3 });
+            }
         }
 
-        let voxel = &self[vec3];
-        let redstone = match voxel {
-            Voxel::Air(..) => None,
-            Voxel::Stone(..) => Some(self.redstone_arena.make_block(&voxel.get_name(vec3))),
-            Voxel::Torch(..) => Some(self.redstone_arena.make_torch(&voxel.get_name(vec3))),
-            Voxel::Dust(..) => Some(self.redstone_arena.make_dust(&voxel.get_name(vec3))),
+        let threads = self.threads().min(rgs.len());
+
+        let (outcome, pending, ran) = if threads <= 1 {
+            self.run_in_lockstep(&rgs, stop, sink.unwrap_or(&mut ()))
+        } else {
+            self.run_in_parallel(&rgs, threads, stop, sink)
         };
 
-        if let Some(redstone) = redstone {
-            self.redstones.borrow_mut().insert(vec3, redstone);
+        // Only a run that was stopped on purpose gets picked up again.
+        if let Some(stop) = stop {
+            let arena = &self.redstone_arena;
+            let vec3 = |redstone: RedstoneId| arena[redstone].vec3().unwrap();
+
+            self.pending = pending
+                .into_iter()
+                .map(|(redstone, cause, delay)| (vec3(redstone), cause.map(vec3), delay))
+                .collect();
+            self.frame = self.frame + stop;
+        } else {
+            self.frame = self.frame + ran;
         }
 
-        redstone
+        outcome
     }
 
-    fn vec3_neighbors(vec3: Vec3) -> Neighbors<Vec3> {
-        Neighbors::new(
-            vec3.up(),
-            vec3.down(),
-            vec3.west(),
-            vec3.east(),
-            vec3.north(),
-            vec3.south(),
+    /// Sets how far every run may go before it gives up.
+    pub fn set_budget(&mut self, budget: Budget) {
+        self.budget = budget;
+    }
+
+    /// Lowers this world into a [`CompiledWorld`] that picks up exactly where the next run would.
+    pub fn compile(mut self) -> CompiledWorld {
+        let rgs = self.prepare();
+        CompiledWorld::lower(&self.redstone_arena, &rgs, &self.redstones)
+    }
+
+    /// Builds the redstone graphs and queues up whatever the next run has to dispatch.
+    fn prepare(&mut self) -> Vec<RedstoneGraph> {
+        self.build();
+        let placed = mem::take(&mut self.placed);
+
+        let mut updates: Vec<Vec3> = mem::take(&mut self.updates).into_iter().collect();
+        updates.sort_by_key(|v| (v.x(), v.y(), v.z()));
+
+        let pending = mem::take(&mut self.pending);
+        self.get_redstone_graphs(placed, &pending, &updates)
+    }
+
+    /// Caps how many threads a run uses to step disjoint redstone graphs. Zero, the default, uses
+    /// one per core.
+    pub fn set_max_threads(&mut self, max_threads: usize) {
+        self.max_threads = max_threads;
+    }
+
+    fn threads(&self) -> usize {
+        match self.max_threads {
+            0 => thread::available_parallelism().map_or(1, NonZeroUsize::get),
+            max_threads => max_threads,
+        }
+    }
+
+    fn run_in_lockstep(
+        &self,
+        rgs: &[RedstoneGraph],
+        stop: Option<Frame>,
+        sink: &mut dyn TraceSink,
+    ) -> (RunOutcome, Vec<Pending>, Frame) {
+        let arena = &self.redstone_arena;
+        let mut runs: Vec<_> = rgs
+            .iter()
+            .map(|rg| World::start(rg, self.budget, stop))
+            .collect();
+        let mut outcome = RunOutcome::Stable;
+        let mut pending = Vec::new();
+        let mut frame = Frame(0);
+
+        while !runs.is_empty() {
+            runs.retain_mut(|run| {
+                if run.frame() != frame && stop.is_none_or(|stop| run.frame() < stop) {
+                    return true;
+                }
+
+                match run.advance(arena, sink) {
+                    Some(o) => {
+                        outcome = outcome.merge(o);
+                        pending.extend(run.pending(arena, stop.unwrap_or(frame)));
+                        false
+                    }
+                    None => true,
+                }
+            });
+
+            frame = frame + Frame(1);
+        }
+
+        (outcome, pending, frame)
+    }
+
+    fn start(rg: &RedstoneGraph, budget: Budget, stop: Option<Frame>) -> RedstoneRun<'_> {
+        let run = rg.start(budget);
+        match stop {
+            Some(stop) => run.stop_at(stop),
+            None => run,
+        }
+    }
+
+    // Disjoint graphs share no redstone, so they can be dispatched concurrently without changing
+    // the outcome. The threads still move through the frames in lockstep, so that every frame is
+    // finished everywhere before any thread starts on the next one. That is also when the events
+    // of the frame are handed to the sink, in the order of the graphs.
+    fn run_in_parallel(
+        &self,
+        rgs: &[RedstoneGraph],
+        threads: usize,
+        stop: Option<Frame>,
+        sink: Option<&mut dyn TraceSink>,
+    ) -> (RunOutcome, Vec<Pending>, Frame) {
+        let arena = &self.redstone_arena;
+        let tracing = sink.is_some();
+
+        // Hand out the biggest graphs first, each to whichever thread has the least to do.
+        let mut order: Vec<(usize, &RedstoneGraph)> = rgs.iter().enumerate().collect();
+        order.sort_by_key(|(_, rg)| Reverse(rg.redstones().len()));
+
+        let mut buckets: Vec<(usize, Vec<(usize, &RedstoneGraph)>)> =
+            vec![(0, Vec::new()); threads];
+        for (idx, rg) in order {
+            let bucket = buckets.iter_mut().min_by_key(|(load, _)| *load).unwrap();
+            bucket.0 += rg.redstones().len();
+            bucket.1.push((idx, rg));
+        }
+
+        let remaining = AtomicUsize::new(rgs.len());
+        let barrier = Barrier::new(threads);
+        let events = Mutex::new(Vec::new());
+        let sink = Mutex::new(sink);
+
+        thread::scope(|scope| {
+            let workers: Vec<_> = buckets
+                .into_iter()
+                .map(|(_, bucket)| {
+                    let remaining = &remaining;
+                    let barrier = &barrier;
+                    let events = &events;
+                    let sink = &sink;
+
+                    scope.spawn(move || {
+                        let mut runs: Vec<_> = bucket
+                            .into_iter()
+                            .map(|(idx, rg)| (idx, World::start(rg, self.budget, stop)))
+                            .collect();
+                        let mut outcome = RunOutcome::Stable;
+                        let mut pending = Vec::new();
+                        let mut frame = Frame(0);
+
+                        loop {
+                            let running = runs.len();
+                            runs.retain_mut(|(idx, run)| {
+                                if run.frame() != frame
+                                    && stop.is_none_or(|stop| run.frame() < stop)
+                                {
+                                    return true;
+                                }
+
+                                let mut buffer = Vec::new();
+                                let result = if tracing {
+                                    run.advance(arena, &mut buffer)
+                                } else {
+                                    run.advance(arena, &mut ())
+                                };
+
+                                if !buffer.is_empty() {
+                                    events.lock().unwrap().push((*idx, buffer));
+                                }
+
+                                match result {
+                                    Some(o) => {
+                                        outcome = outcome.merge(o);
+                                        let left = run.pending(arena, stop.unwrap_or(frame));
+                                        pending.push((*idx, left));
+                                        false
+                                    }
+                                    None => true,
+                                }
+                            });
+                            remaining.fetch_sub(running - runs.len(), Ordering::Relaxed);
+
+                            if barrier.wait().is_leader() {
+                                let mut events = events.lock().unwrap();
+                                events.sort_by_key(|(idx, _)| *idx);
+
+                                if let Some(sink) = sink.lock().unwrap().as_mut() {
+                                    for event in events.iter().flat_map(|(_, buffer)| buffer) {
+                                        sink.record(event);
+                                    }
+                                }
+
+                                events.clear();
+                            }
+
+                            let done = remaining.load(Ordering::Relaxed) == 0;
+                            barrier.wait();
+
+                            if done {
+                                return (outcome, pending, frame + Frame(1));
+                            }
+
+                            frame = frame + Frame(1);
+                        }
+                    })
+                })
+                .collect();
+
+            let mut outcome = RunOutcome::Stable;
+            let mut pending = Vec::new();
+            let mut ran = Frame(0);
+
+            for worker in workers {
+                let (o, p, frame) = worker.join().unwrap();
+                outcome = outcome.merge(o);
+                pending.extend(p);
+                ran = ran.max(frame);
+            }
+
+            pending.sort_by_key(|(idx, _)| *idx);
+            (
+                outcome,
+                pending.into_iter().flat_map(|(_, p)| p).collect(),
+                ran,
+            )
+        })
+    }
+
+    /// Delivers a block update to the voxel at `vec3`, as if one of its neighbors had changed.
+    /// A quasi-connected mechanism that was left powered but not updated will react to it.
+    pub fn update(&mut self, vec3: Vec3) -> RunOutcome {
+        self.schedule_update(vec3);
+        self.run()
+    }
+
+    /// Delivers a block update to the voxel at `vec3` on the next run.
+    pub fn schedule_update(&mut self, vec3: Vec3) {
+        self.updates.insert(vec3);
+    }
+
+    /// The smallest region around every voxel that isn't air, if there are any.
+    pub fn bounds(&self) -> Option<Region> {
+        Region::around(
+            self.voxels()
+                .filter(|(_, voxel)| !voxel.is_air())
+                .map(|(vec3, _)| vec3),
         )
     }
 
-    fn neighbors(&'r self, vec3: Vec3) -> Neighbors<VoxelCtxt> {
+    /// Every position in `region` along with its voxel, air included, in the order
+    /// [`Region::iter`] goes.
+    pub fn iter_region(&self, region: Region) -> impl Iterator<Item = (Vec3, &Voxel)> {
+        region.iter().map(|vec3| (vec3, &self[vec3]))
+    }
+
+    /// How many of the 16×16×16 sections the world is stored in hold anything but air.
+    pub fn sections(&self) -> usize {
+        self.voxels.sections()
+    }
+
+    /// Every voxel that isn't air, in no particular order.
+    pub fn voxels(&self) -> impl Iterator<Item = (Vec3, &Voxel)> {
+        self.voxels.iter()
+    }
+
+    /// The redstone at `vec3` as of the last run.
+    pub fn get(&self, vec3: Vec3) -> Option<&Redstone> {
+        self.redstone_id(vec3).map(|id| &self.redstone_arena[id])
+    }
+
+    pub fn redstone_id(&self, vec3: Vec3) -> Option<RedstoneId> {
+        self.redstones.get(&vec3).copied()
+    }
+
+    /// Every disjoint redstone graph as of the last build, without anything queued up to run.
+    pub fn redstone_graphs(&self) -> Vec<RedstoneGraph> {
+        self.get_redstone_graphs(false, &[], &[])
+    }
+
+    /// Takes a snapshot of everything this world simulates.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            voxels: self.voxels.clone(),
+            redstones: self
+                .redstones
+                .iter()
+                .map(|(&vec3, &id)| (vec3, self.redstone_arena[id].clone()))
+                .collect(),
+            edits: self.edits.clone(),
+            updates: self.updates.clone(),
+            built: self.built,
+            placed: self.placed,
+            frame: self.frame,
+            pending: self.pending.clone(),
+        }
+    }
+
+    /// Puts this world back the way it was when `snapshot` was taken, whichever world it was
+    /// taken from. The budget and the number of threads stay as they are.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.voxels = snapshot.voxels.clone();
+        self.redstone_arena = RedstoneArena::new();
+        self.redstones.clear();
+        self.edits.clear();
+        self.built = false;
+
+        if snapshot.built {
+            self.build();
+
+            for (vec3, &id) in &self.redstones {
+                if let Some(redstone) = snapshot.redstones.get(vec3) {
+                    self.redstone_arena[id].inherit(redstone);
+                }
+            }
+        }
+
+        self.edits = snapshot.edits.clone();
+        self.updates = snapshot.updates.clone();
+        self.built = snapshot.built;
+        self.placed = snapshot.placed;
+        self.frame = snapshot.frame;
+        self.pending = snapshot.pending.clone();
+    }
+
+    /// Builds the world, and returns everything about it that can't be simulated the way it was
+    /// built, ordered by position. The world still runs, but not like it would in the game. Torches
+    /// and dust that dropped since the last traced run or validation are reported once too.
+    pub fn validate(&mut self) -> Vec<RuststoneError> {
+        self.build();
+
+        let mut diagnostics = self.diagnostics.clone();
+        for (vec3, voxel) in self.voxels.iter() {
+            if let Voxel::Torch(torch) = voxel {
+                if !self[torch.placed_on(vec3)].is_solid() {
+                    diagnostics.push(RuststoneError::FloatingTorch { vec3 });
+                }
+            }
+        }
+
+        diagnostics.extend(
+            mem::take(&mut self.dropped)
+                .into_iter()
+                .map(|(_, vec3)| RuststoneError::Dropped { vec3 }),
+        );
+
+        diagnostics.extend(self.redstone_arena.validate());
+        diagnostics.sort_by_key(|d| d.vec3().map(|v| (v.x(), v.y(), v.z())));
+        diagnostics
+    }
+
+    pub fn arena(&self) -> &RedstoneArena {
+        &self.redstone_arena
+    }
+
+    /// Every note played during the run so far, ordered by game tick and then by position.
+    pub fn notes(&self) -> Vec<NoteEvent> {
+        let mut notes = Vec::new();
+
+        for (&vec3, &redstone) in self.redstones.iter() {
+            let RedstoneNode::NoteBlock(note_block) = self.redstone_arena[redstone].node() else {
+                continue;
+            };
+
+            for tick in note_block.played() {
+                notes.push(NoteEvent {
+                    vec3,
+                    instrument: note_block.instrument(),
+                    pitch: note_block.pitch(),
+                    tick,
+                });
+            }
+        }
+
+        notes.sort_by_key(|n| (n.tick, n.vec3.x(), n.vec3.y(), n.vec3.z()));
+        notes
+    }
+
+    fn vec3_neighbors(vec3: Vec3) -> Neighbors<Vec3> {
+        Neighbors::from_components(Direction::ALL.map(|direction| vec3.offset(direction)))
+    }
+
+    fn neighbors(&self, vec3: Vec3) -> Neighbors<VoxelCtxt<'_>> {
         World::vec3_neighbors(vec3).map(|v| VoxelCtxt {
             vec3: v,
             voxel: &self[v],
-            redstone: self.get(v),
+            redstone: self.redstone_id(v),
         })
     }
 
-    fn get_redstone_graphs(&'r self) -> Vec<RedstoneGraph<'r>> {
-        for (vec3, voxel) in &self.voxels {
+    fn make_redstone(
+        &self,
+        arena: &mut RedstoneArena,
+        vec3: Vec3,
+        voxel: &Voxel,
+    ) -> Option<RedstoneId> {
+        let name = voxel.get_name(vec3);
+        let redstone = match voxel {
+            Voxel::Air(..) => return None,
+            Voxel::Stone(..) => arena.make_block(&name),
+            Voxel::Torch(..) => arena.make_torch(&name),
+            Voxel::Dust(..) => arena.make_dust(&name),
+            Voxel::Repeater(repeater) => arena
+                .make_repeater(&name, repeater.delay)
+                .expect("a repeater voxel only holds delays from 1 to 4"),
+            Voxel::CopperBulb(..) => arena.make_copper_bulb(&name),
+            Voxel::Crafter(crafter) => {
+                let redstone = arena.make_crafter(&name);
+                if let RedstoneNode::Crafter(node) = arena[redstone].node() {
+                    for (idx, slot) in crafter.slots.iter().enumerate() {
+                        node.set_slot(idx, *slot)
+                            .expect("a crafter voxel has as many slots as its redstone");
+                    }
+                }
+
+                redstone
+            }
+            Voxel::NoteBlock(note_block) => arena
+                .make_note_block(&name, self[vec3.down()].instrument(), note_block.pitch)
+                .expect("a note block voxel only holds pitches from 0 to 24"),
+            Voxel::Piston(..) => arena.make_mechanism(&name, MechanismKind::Piston),
+            Voxel::Dropper(..) => arena.make_mechanism(&name, MechanismKind::Dropper),
+            Voxel::Dispenser(..) => arena.make_mechanism(&name, MechanismKind::Dispenser),
+        };
+
+        Some(redstone)
+    }
+
+    /// Rebuilds the redstone graphs if any voxel changed since they were last built. Every
+    /// redstone takes over the state of whatever was in its place before, and every voxel that
+    /// changed gets a block update along with its neighbors. Torches and dust that an edit left
+    /// without a solid block to sit on drop first, like they do in the game, and the next traced
+    /// run records them as [`TraceEvent::Dropped`].
+    ///
+    /// Runs build the world on their own, but building it beforehand lets you look up the
+    /// redstone the next run will dispatch.
+    pub fn build(&mut self) {
+        if self.built && self.edits.is_empty() {
+            return;
+        }
+
+        self.voxels.settle();
+
+        // The world as it was first built is taken as it is, mistakes and all.
+        if self.built {
+            self.drop_unsupported();
+        }
+
+        let old_arena = mem::take(&mut self.redstone_arena);
+        let old_redstones = mem::take(&mut self.redstones);
+
+        let mut arena = RedstoneArena::new();
+        for (vec3, voxel) in self.voxels.iter() {
+            let Some(redstone) = self.make_redstone(&mut arena, vec3, voxel) else {
+                continue;
+            };
+
+            arena.place(redstone, vec3);
+
+            if let Some(&old) = old_redstones.get(&vec3) {
+                arena[redstone].inherit(&old_arena[old]);
+            }
+
+            self.redstones.insert(vec3, redstone);
+        }
+
+        self.diagnostics = self.link(&mut arena);
+        self.redstone_arena = arena;
+
+        for vec3 in mem::take(&mut self.edits) {
+            if self.built {
+                self.updates.insert(vec3);
+                self.updates.extend(&World::vec3_neighbors(vec3));
+            }
+        }
+
+        self.placed |= !self.built;
+        self.built = true;
+    }
+
+    /// Whether the voxel at `vec3` has what it needs to stay where it is.
+    fn is_supported(&self, vec3: Vec3) -> bool {
+        match &self[vec3] {
+            Voxel::Torch(torch) => self[torch.placed_on(vec3)].is_solid(),
+            Voxel::Dust(_) => self[vec3.down()].is_solid(),
+            _ => true,
+        }
+    }
+
+    /// Turns every torch and dust that was edited, or next to an edit, into air if it lost its
+    /// support.
+    fn drop_unsupported(&mut self) {
+        let mut queue: Vec<Vec3> = self.edits.iter().copied().collect();
+        queue.sort_by_key(|v| Reverse((v.x(), v.y(), v.z())));
+
+        while let Some(edit) = queue.pop() {
+            for vec3 in std::iter::once(edit).chain(World::vec3_neighbors(edit).components) {
+                if self.is_supported(vec3) {
+                    continue;
+                }
+
+                self.voxels.set(vec3, Voxel::air().voxel());
+                self.edits.insert(vec3);
+                self.dropped.push((self.tick(), vec3));
+                queue.push(vec3);
+            }
+        }
+
+        if self.dropped.len() > MAX_DROPPED {
+            self.dropped.drain(..self.dropped.len() - MAX_DROPPED);
+        }
+    }
+
+    /// Puts `voxel` at `vec3` if it can stay there, like placing it in the game would. Torches
+    /// and dust need a solid block to sit on.
+    pub fn place(&mut self, vec3: Vec3, voxel: Voxel) -> Result<(), RuststoneError> {
+        let support = match &voxel {
+            Voxel::Torch(torch) => Some((
+                torch.placed_on(vec3),
+                RuststoneError::FloatingTorch { vec3 },
+            )),
+            Voxel::Dust(_) => Some((vec3.down(), RuststoneError::UnsupportedDust { vec3 })),
+            _ => None,
+        };
+
+        if let Some((support, error)) = support {
+            if !self[support].is_solid() {
+                return Err(error);
+            }
+        }
+
+        self[vec3] = voxel;
+        Ok(())
+    }
+
+    /// Copies every voxel in `region`.
+    pub fn copy(&self, region: Region) -> Structure {
+        let voxels = self
+            .iter_region(region)
+            .filter(|(_, voxel)| !voxel.is_air())
+            .map(|(vec3, voxel)| (vec3 - region.min(), voxel.clone()))
+            .collect();
+
+        Structure {
+            size: region.size(),
+            voxels,
+            ports: Vec::new(),
+        }
+    }
+
+    /// Pastes `structure` with its lowest corner at `at`, replacing everything in the region it
+    /// covers, air included.
+    pub fn paste(&mut self, structure: &Structure, at: Vec3) {
+        let region = Region::from_size(at, structure.size());
+        let covered: Vec<Vec3> = self
+            .voxels()
+            .filter(|&(vec3, voxel)| !voxel.is_air() && region.contains(vec3))
+            .map(|(vec3, _)| vec3)
+            .collect();
+
+        for vec3 in covered {
+            self[vec3] = Voxel::air().voxel();
+        }
+
+        for (vec3, voxel) in structure.voxels() {
+            self[at + vec3] = voxel.clone();
+        }
+    }
+
+    /// Links every voxel to its neighbors, and returns whatever couldn't be linked.
+    fn link(&self, arena: &mut RedstoneArena) -> Vec<RuststoneError> {
+        let mut diagnostics = Vec::new();
+        let d = &mut diagnostics;
+
+        for (vec3, voxel) in self.voxels.iter() {
             match voxel {
                 Voxel::Air(..) => continue,
-                Voxel::Stone(_) => (), // I think this is no-op in general.
-                Voxel::Torch(torch) => self.visit_torch_voxel(*vec3, torch),
-                Voxel::Dust(dust) => self.visit_dust_voxel(*vec3, dust),
+                Voxel::Stone(_) => self.visit_stone_voxel(arena, vec3, d),
+                Voxel::Torch(torch) => self.visit_torch_voxel(arena, vec3, torch, d),
+                Voxel::Dust(dust) => self.visit_dust_voxel(arena, vec3, dust, d),
+                Voxel::Repeater(repeater) => self.visit_repeater_voxel(arena, vec3, repeater, d),
+                Voxel::CopperBulb(..) => (),
+                Voxel::Crafter(..) => (),
+                Voxel::NoteBlock(..) => (),
+                Voxel::Piston(..) => self.visit_quasi_connected_voxel(arena, vec3, d),
+                Voxel::Dropper(..) => self.visit_quasi_connected_voxel(arena, vec3, d),
+                Voxel::Dispenser(..) => self.visit_quasi_connected_voxel(arena, vec3, d),
             }
         }
 
-        for (vec3, voxel) in &self.voxels {
+        for (vec3, voxel) in self.voxels.iter() {
             if !voxel.is_dust() {
                 continue;
             }
 
-            let dust = self.get(*vec3).unwrap();
+            let dust = self.redstones[&vec3];
             let mut queue = VecDeque::new();
-            queue.push_front((0, *vec3));
+            queue.push_front((0, vec3, vec3));
 
             let mut anticycle = HashSet::new();
 
@@ -195,68 +852,98 @@ impl<'r> World<'r> {
                 anticycle.insert(current.1);
 
                 let voxel = &self[current.1];
-                let source = self.get(current.1);
+                let source = self.redstone_id(current.1);
 
-                match voxel {
+                let linked = match voxel {
                     Voxel::Air(_) => continue,
-                    Voxel::Stone(_) => add_weighted_edge(dust, source.unwrap(), current.0),
-                    Voxel::Torch(_) => add_weighted_edge(dust, source.unwrap(), current.0),
+                    Voxel::Stone(_) => arena.add_weighted_edge(dust, source.unwrap(), current.0),
+                    Voxel::Torch(_) => arena.add_weighted_edge(dust, source.unwrap(), current.0),
+                    // A repeater only powers the dust it faces.
+                    Voxel::Repeater(repeater) if current.1.offset(repeater.facing) == current.2 => {
+                        arena.add_weighted_edge(dust, source.unwrap(), current.0)
+                    }
                     Voxel::Dust(_) => {
                         for neighbor in World::vec3_neighbors(current.1).into_iter() {
-                            queue.push_back((current.0 + 1, *neighbor));
+                            queue.push_back((current.0 + 1, *neighbor, current.1));
                         }
+
+                        continue;
                     }
-                }
+                    // Mechanisms never power the dust.
+                    _ => continue,
+                };
+
+                diagnostics.extend(linked.err());
             }
         }
 
-        // We need to be able to find all the disjoint redstone graphs so that we
-        // know how to collect redstone dispatchables from each redstone graphs
-        // in order to dispatch all of them.
-        let mut seen = HashSet::new();
+        diagnostics
+    }
+
+    fn get_redstone_graphs(
+        &self,
+        placed: bool,
+        pending: &[(Vec3, Option<Vec3>, Frame)],
+        updates: &[Vec3],
+    ) -> Vec<RedstoneGraph> {
+        // Every group of connected redstones becomes a graph of its own, so that no redstone is
+        // in more than one and graphs can be dispatched without stepping on each other.
+        let mut seen = HashMap::new();
         let mut rgs = Vec::new();
 
-        for &redstone in self.redstones.borrow().values() {
-            if seen.contains(&(redstone as *const Redstone)) {
-                continue;
+        for redstones in self.redstone_arena.components() {
+            for &redstone in redstones.iter() {
+                seen.insert(redstone, rgs.len());
             }
 
-            for redstone in redstone.into_iter() {
-                seen.insert(redstone as *const Redstone);
+            let mut rg = RedstoneGraph::of(redstones);
+            if placed {
+                rg.place(&self.redstone_arena);
             }
 
-            rgs.push(RedstoneGraph::collect(redstone));
+            rgs.push(rg);
+        }
+
+        // Whatever was pending goes first, since it was due before anything that changed since.
+        for &(vec3, cause, delay) in pending {
+            if let Some(redstone) = self.redstone_id(vec3) {
+                let cause = cause.and_then(|cause| self.redstone_id(cause));
+                rgs[seen[&redstone]].resume(redstone, cause, delay);
+            }
+        }
+
+        for vec3 in updates {
+            if let Some(redstone) = self.redstone_id(*vec3) {
+                rgs[seen[&redstone]].update(redstone);
+            }
         }
 
         rgs
     }
 
     fn is_linkable_from_torch(&self, torch: (Vec3, &TorchVoxel), other: (Vec3, &Voxel)) -> bool {
-        let placed_on_vec3 = match &torch.1.facing {
-            Some(Facing::North) => torch.0.north(),
-            Some(Facing::East) => torch.0.east(),
-            Some(Facing::West) => torch.0.west(),
-            Some(Facing::South) => torch.0.south(),
-            None => torch.0.down(),
-        };
-
         // The torch is never linked to the voxel for which it is placed upon.
-        if other.0 == placed_on_vec3 {
+        if other.0 == torch.1.placed_on(torch.0) {
             return false;
         }
 
-        match (&other.1, (torch.0 - other.0)) {
-            (Voxel::Air(_), _) => false,
-            (Voxel::Stone(_), Vec3(_, 1, _)) => true,
-            (Voxel::Stone(_), Vec3(_, _, _)) => false,
-            (Voxel::Torch(_), Vec3(_, 0, _)) => false,
+        match (&other.1, Direction::from_vec3(other.0 - torch.0)) {
+            (_, None) => false,
+            // Standing or on a wall, a torch strongly powers the block right above it.
+            (Voxel::Stone(_), Some(Direction::Up)) => true,
             (Voxel::Dust(_), _) => true,
-            (_, _) => false,
+            (voxel, _) => voxel.is_mechanism(),
         }
     }
 
-    fn visit_torch_voxel(&'r self, vec3: Vec3, torch: &TorchVoxel) {
-        let redstone = self.get(vec3).unwrap();
+    fn visit_torch_voxel(
+        &self,
+        arena: &mut RedstoneArena,
+        vec3: Vec3,
+        torch: &TorchVoxel,
+        diagnostics: &mut Vec<RuststoneError>,
+    ) {
+        let redstone = self.redstones[&vec3];
 
         for neighbor in self
             .neighbors(vec3)
@@ -264,14 +951,103 @@ impl<'r> World<'r> {
             .filter(|n| self.is_linkable_from_torch((vec3, torch), (n.vec3, n.voxel)))
         {
             if let Some(target) = neighbor.redstone {
-                redstone.link(target);
+                diagnostics.extend(arena.link(redstone, target).err());
+            }
+        }
+    }
+
+    fn visit_stone_voxel(
+        &self,
+        arena: &mut RedstoneArena,
+        vec3: Vec3,
+        diagnostics: &mut Vec<RuststoneError>,
+    ) {
+        let redstone = self.redstones[&vec3];
+
+        // A powered block activates every mechanism right next to it, and turns off every torch
+        // placed upon it.
+        for neighbor in self.neighbors(vec3).into_iter() {
+            let is_linkable = match neighbor.voxel {
+                Voxel::Torch(torch) => torch.placed_on(neighbor.vec3) == vec3,
+                voxel => voxel.is_mechanism(),
+            };
+
+            if is_linkable {
+                diagnostics.extend(arena.link(redstone, neighbor.redstone.unwrap()).err());
+            }
+        }
+    }
+
+    fn visit_repeater_voxel(
+        &self,
+        arena: &mut RedstoneArena,
+        vec3: Vec3,
+        repeater: &RepeaterVoxel,
+        diagnostics: &mut Vec<RuststoneError>,
+    ) {
+        let redstone = self.redstones[&vec3];
+
+        // Dust, a block or a torch behind the repeater powers it. A repeater behind it does too,
+        // but that one links itself to whatever it faces.
+        let behind = vec3.offset(repeater.facing.opposite());
+        if self[behind].is_dust() || self[behind].is_stone() || self[behind].is_torch() {
+            diagnostics.extend(arena.link(self.redstones[&behind], redstone).err());
+        }
+
+        // It powers whatever it faces that can take power, including a repeater facing the same
+        // way, which it powers from behind.
+        let front = vec3.offset(repeater.facing);
+        let is_linkable = match &self[front] {
+            Voxel::Repeater(other) => other.facing == repeater.facing,
+            voxel => voxel.is_dust() || voxel.is_solid(),
+        };
+        if is_linkable {
+            diagnostics.extend(arena.link(redstone, self.redstones[&front]).err());
+        }
+
+        // Another repeater facing into its side locks it.
+        for side in [Rotation::Clockwise, Rotation::Counterclockwise] {
+            let side = vec3.offset(repeater.facing.rotate_y(side));
+            if let Voxel::Repeater(other) = &self[side] {
+                if side.offset(other.facing) == vec3 {
+                    diagnostics.extend(arena.lock(redstone, self.redstones[&side]).err());
+                }
+            }
+        }
+    }
+
+    fn visit_quasi_connected_voxel(
+        &self,
+        arena: &mut RedstoneArena,
+        vec3: Vec3,
+        diagnostics: &mut Vec<RuststoneError>,
+    ) {
+        let redstone = self.redstones[&vec3];
+        let above = vec3.up();
+
+        // Anything that would power a mechanism in the voxel above also powers this one.
+        for neighbor in self.neighbors(above).into_iter() {
+            if neighbor.vec3 == vec3 {
+                continue;
+            }
+
+            let is_quasi_source = match neighbor.voxel {
+                Voxel::Torch(torch) => torch.placed_on(neighbor.vec3) != above,
+                Voxel::Dust(_) => neighbor.vec3 == above.up(),
+                Voxel::Stone(_) => true,
+                _ => false,
+            };
+
+            if is_quasi_source {
+                diagnostics.extend(arena.quasi_link(neighbor.redstone.unwrap(), redstone).err());
             }
         }
     }
 
     fn is_linkable_from_dust(&self, dust: (Vec3, &DustVoxel), other: (Vec3, &Voxel)) -> bool {
         match (dust.0 - other.0).abs() {
-            Vec3(0, 0, 0) => panic!("Same voxel?"),
+            // A voxel is never its own neighbor.
+            Vec3(0, 0, 0) => false,
             Vec3(0, 0, 1) if other.1.is_dust() => true,
             Vec3(0, 1, 0) if other.1.is_dust() => true,
             Vec3(1, 0, 0) if other.1.is_dust() => true,
@@ -279,25 +1055,39 @@ impl<'r> World<'r> {
         }
     }
 
-    fn visit_dust_voxel(&'r self, vec3: Vec3, dust: &DustVoxel) {
-        let redstone = self.get(vec3).unwrap();
+    fn visit_dust_voxel(
+        &self,
+        arena: &mut RedstoneArena,
+        vec3: Vec3,
+        dust: &DustVoxel,
+        diagnostics: &mut Vec<RuststoneError>,
+    ) {
+        let redstone = self.redstones[&vec3];
         let neighbors = self.neighbors(vec3);
 
-        // A dust must be placed on a stone at all times.
-        assert!(neighbors.down().voxel.is_stone());
-        redstone.link(neighbors.down().redstone.unwrap());
+        // A dust must be placed on a solid block at all times. Without one, it still carries
+        // power, it just doesn't power anything below it.
+        if neighbors.down().voxel.is_solid() {
+            diagnostics.extend(
+                arena
+                    .link(redstone, neighbors.down().redstone.unwrap())
+                    .err(),
+            );
+        } else {
+            diagnostics.push(RuststoneError::UnsupportedDust { vec3 });
+        }
 
         let (viable, nonviable): (Vec<&VoxelCtxt>, Vec<&VoxelCtxt>) = neighbors
             .into_iter()
             .partition(|n| self.is_linkable_from_dust((vec3, dust), (n.vec3, n.voxel)));
 
         for ctxt in viable {
-            redstone.link(ctxt.redstone.unwrap());
+            diagnostics.extend(arena.link(redstone, ctxt.redstone.unwrap()).err());
         }
 
         if neighbors.up().voxel.is_air() {
             for ctxt in nonviable.iter().filter(|ctx| ctx.voxel.is_dust()) {
-                redstone.link(ctxt.redstone.unwrap());
+                diagnostics.extend(arena.link(redstone, ctxt.redstone.unwrap()).err());
             }
         }
     }
@@ -0,0 +1,132 @@
+use ruststone::{
+    ascii::{format_world, parse_world},
+    text,
+    vec3::{Direction, Vec3},
+    voxels::Voxel,
+    world::World,
+    CrafterSlot,
+};
+
+const INVERTER: &str = "\
+// A torch on a block, and another one on its side.
+origin 0 0 0
+layer 0
+##
+layer 1
+*+<
+";
+
+#[test]
+fn parses_layers_into_voxels() {
+    let world = parse_world(INVERTER).unwrap();
+
+    assert!(world[Vec3(0, 0, 0)].is_stone());
+    assert!(world[Vec3(1, 0, 0)].is_stone());
+    assert!(world[Vec3(0, 1, 0)].is_torch());
+    assert!(world[Vec3(1, 1, 0)].is_dust());
+    assert!(world[Vec3(2, 1, 0)].is_torch());
+    assert!(world[Vec3(2, 0, 0)].is_air());
+}
+
+#[test]
+fn parses_the_same_world_as_the_text_format() {
+    let layered = parse_world(INVERTER).unwrap();
+    let listed =
+        text::parse_world("0 0 0 stone\n1 0 0 stone\n0 1 0 torch\n1 1 0 dust\n2 1 0 torch:east")
+            .unwrap();
+
+    assert_eq!(text::format_world(&layered), text::format_world(&listed));
+}
+
+#[test]
+fn places_the_grid_at_the_origin() {
+    let world = parse_world(
+        "origin 10 -5 3
+legend N note_block:7
+layer 2
+..
+.N",
+    )
+    .unwrap();
+
+    assert_eq!(text::format_world(&world), "11 -3 4 note_block:7\n");
+}
+
+#[test]
+fn formats_what_it_parses() {
+    let layers = "\
+origin -1 0 2
+legend A note_block:12
+layer 0
+#.A
+..#
+layer 1
+*+>
+v.^
+";
+
+    assert_eq!(format_world(&parse_world(layers).unwrap()), layers);
+}
+
+#[test]
+fn formats_from_the_smallest_corner() {
+    let mut world = World::new();
+    world[Vec3(3, 4, 5)] = Voxel::stone().voxel();
+    world[Vec3(4, 5, 6)] = Voxel::dust().voxel();
+    world[Vec3(0, 0, 0)] = Voxel::air().voxel();
+
+    assert_eq!(
+        format_world(&world),
+        "origin 3 4 5\nlayer 0\n#\n.\nlayer 1\n.\n.+\n"
+    );
+}
+
+#[test]
+fn rejects_what_it_cannot_read() {
+    assert_eq!(parse_world("##").err().map(|e| e.line), Some(1));
+    assert_eq!(parse_world("layer 0\n#x").err().map(|e| e.line), Some(2));
+    assert_eq!(
+        parse_world("legend AB stone").err().map(|e| e.line),
+        Some(1)
+    );
+    assert_eq!(parse_world("legend A lever").err().map(|e| e.line), Some(1));
+    assert_eq!(parse_world("layer up").err().map(|e| e.line), Some(1));
+}
+
+#[test]
+fn formats_more_kinds_of_voxels_than_there_are_spare_characters() {
+    let mut world = World::new();
+    for pitch in 0..=24 {
+        world[Vec3(i32::from(pitch), 0, 0)] = Voxel::note_block().pitch(pitch).unwrap().voxel();
+    }
+    for count in 0..20 {
+        world[Vec3(count, 0, 1)] = Voxel::crafter()
+            .with_slot(0, CrafterSlot::Filled(count as u8))
+            .unwrap()
+            .voxel();
+    }
+
+    let layers = format_world(&world);
+    assert!(!layers.is_ascii());
+
+    let parsed = parse_world(&layers).unwrap();
+
+    assert_eq!(text::format_world(&parsed), text::format_world(&world));
+}
+
+#[test]
+fn formats_repeaters_through_the_legend() {
+    let layers = "\
+origin 0 0 0
+legend A repeater:west
+legend B repeater:south:3
+layer 0
+#A+
+B
+";
+
+    let world = parse_world(layers).unwrap();
+
+    assert_eq!(world[Vec3(1, 0, 0)].facing(), Some(Direction::West));
+    assert_eq!(format_world(&world), layers);
+}
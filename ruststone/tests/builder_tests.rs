@@ -0,0 +1,178 @@
+use ruststone::{
+    builder::Builder,
+    vec3::{Direction, Rotation, Vec3},
+    voxels::Voxel,
+};
+
+/// A block with a torch standing on it, which is lit while the block isn't powered.
+fn inverter(builder: &mut Builder) {
+    builder
+        .place(Voxel::stone().voxel())
+        .port("in")
+        .torch_on(Direction::Up)
+        .step(Direction::Up, 1)
+        .port("out");
+}
+
+/// A block with a torch on its north side.
+fn wall_inverter(builder: &mut Builder) {
+    builder
+        .place(Voxel::stone().voxel())
+        .port("in")
+        .torch_on(Direction::North)
+        .step(Direction::North, 1)
+        .port("out");
+}
+
+/// Two inverters, one on top of the other.
+fn buffer(builder: &mut Builder) {
+    builder.module("low", &inverter);
+    let low = builder.port_at("low.out");
+
+    builder.at(low.up()).module("high", &inverter);
+
+    let (input, output) = (builder.port_at("low.in"), builder.port_at("high.out"));
+    builder.at(input).port("in").at(output).port("out");
+}
+
+#[test]
+fn laid_dust_carries_power_from_a_torch() {
+    let mut builder = Builder::new();
+    builder
+        .place(Voxel::stone().voxel())
+        .torch_on(Direction::Up)
+        .step(Direction::Up, 1)
+        .step(Direction::West, 1)
+        .face(Direction::West)
+        .port("start")
+        .lay_dust(10)
+        .port("end");
+
+    assert_eq!(builder.port_at("start"), Vec3(1, 1, 0));
+    assert_eq!(builder.port_at("end"), Vec3(10, 1, 0));
+
+    let mut world = builder.into_world();
+    assert!(world.validate().is_empty());
+    world.run();
+
+    let power = |x| world.get(Vec3(x, 1, 0)).unwrap().redstate().get_power();
+    assert!((2..=10).all(|x| power(x) < power(x - 1)));
+    assert!(world.get(Vec3(10, 1, 0)).unwrap().redstate().is_on());
+}
+
+#[test]
+fn turning_and_moving_goes_like_a_turtle() {
+    let mut builder = Builder::new();
+
+    builder
+        .forward(2)
+        .turn(Rotation::Clockwise)
+        .forward(3)
+        .step(Direction::Down, 1);
+
+    assert_eq!(builder.facing(), Direction::East);
+    assert_eq!(builder.cursor(), Vec3(-3, -1, -2));
+
+    builder.lay(Voxel::stone().voxel(), 4);
+
+    assert_eq!(builder.cursor(), Vec3(-6, -1, -2));
+    assert_eq!(builder.world().voxels().count(), 4);
+}
+
+#[test]
+fn torches_go_on_the_side_of_the_block_they_are_put_on() {
+    let mut builder = Builder::new();
+    builder.place(Voxel::stone().voxel());
+
+    for side in Direction::HORIZONTAL {
+        builder.torch_on(side);
+    }
+
+    let mut world = builder.into_world();
+    assert!(world.validate().is_empty());
+
+    for side in Direction::HORIZONTAL {
+        assert_eq!(
+            world[Vec3(0, 0, 0).offset(side)].facing(),
+            Some(side.opposite())
+        );
+    }
+}
+
+#[test]
+fn modules_turn_to_face_the_way_the_cursor_does() {
+    for facing in Direction::HORIZONTAL {
+        let mut builder = Builder::new();
+        builder
+            .at(Vec3(5, 0, 5))
+            .face(facing)
+            .module("not", &wall_inverter);
+
+        let input = builder.port_at("not.in");
+        let output = builder.port_at("not.out");
+        assert_eq!(input.offset(facing), output, "facing {facing}");
+
+        let mut world = builder.into_world();
+        assert!(world.validate().is_empty());
+        world.run();
+
+        assert!(world.get(output).unwrap().redstate().is_on());
+    }
+}
+
+#[test]
+fn modules_are_built_out_of_modules() {
+    let mut builder = Builder::new();
+    builder.module("buffer", &buffer);
+
+    assert_eq!(builder.port_at("buffer.in"), Vec3(0, 0, 0));
+    assert_eq!(builder.port_at("buffer.low.out"), Vec3(0, 1, 0));
+    assert_eq!(builder.port_at("buffer.out"), Vec3(0, 3, 0));
+
+    let output = builder.port_at("buffer.out");
+    let mut world = builder.into_world();
+    world.run();
+
+    assert!(world.get(Vec3(0, 1, 0)).unwrap().redstate().is_on());
+    assert!(world.get(output).unwrap().redstate().is_off());
+}
+
+/// The game tick the dust after a repeater with `delay` first turns on, fed by a torch.
+fn repeated_at(delay: u8) -> u64 {
+    let mut builder = Builder::new();
+    builder
+        .place(Voxel::stone().voxel())
+        .torch_on(Direction::Up)
+        .step(Direction::Up, 1)
+        .step(Direction::West, 1)
+        .face(Direction::West)
+        .lay_dust(2)
+        .forward(1)
+        .repeater(Direction::West, delay)
+        .step(Direction::Down, 1)
+        .place(Voxel::stone().voxel())
+        .step(Direction::Up, 1)
+        .forward(1)
+        .lay_dust(2)
+        .port("out");
+
+    let out = builder.port_at("out");
+    let mut world = builder.into_world();
+    assert_eq!(world.validate(), []);
+
+    while world.get(out).unwrap().redstate().is_off() {
+        assert!(world.tick() < 40, "the dust never turned on");
+        world.run_for(1);
+    }
+
+    world.tick()
+}
+
+#[test]
+fn repeaters_pass_power_on_after_their_delay() {
+    let fastest = repeated_at(1);
+
+    for delay in 2..=4 {
+        assert_eq!(repeated_at(delay), fastest + 2 * u64::from(delay - 1));
+    }
+}
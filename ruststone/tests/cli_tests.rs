@@ -0,0 +1,85 @@
+use std::{
+    env, fs,
+    path::PathBuf,
+    process::{Command, Output},
+};
+
+fn scratch(name: &str, contents: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("ruststone-cli-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let path = dir.join(name);
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+fn ruststone(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ruststone"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn world() -> String {
+    let world = scratch(
+        "world.txt",
+        "0 0 0 stone\n0 1 0 torch\n1 0 0 stone\n1 1 0 dust\n",
+    );
+
+    world.to_str().unwrap().to_owned()
+}
+
+#[test]
+fn prints_the_states_of_the_probes() {
+    let output = ruststone(&[&world(), "--probe", "1,1,0", "--probe", "torch (0, 1, 0)"]);
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "dust (1, 1, 0): 15 (on)\ntorch (0, 1, 0): 16 (on)\n"
+    );
+    assert_eq!(String::from_utf8(output.stderr).unwrap(), "settled\n");
+}
+
+#[test]
+fn runs_a_script_and_writes_a_waveform() {
+    let script = scratch("script.txt", "10 toggle 0 1 0\n");
+    let vcd = env::temp_dir().join(format!("ruststone-cli-{}.vcd", std::process::id()));
+
+    let output = ruststone(&[
+        &world(),
+        "--script",
+        script.to_str().unwrap(),
+        "--ticks",
+        "20",
+        "--probe",
+        "1,1,0",
+        "--format",
+        "vcd",
+        "--output",
+        vcd.to_str().unwrap(),
+    ]);
+
+    assert!(output.status.success());
+
+    let vcd = fs::read_to_string(vcd).unwrap();
+    assert!(vcd.contains("#2\nb1111 !\n1\"\n#10\nb0 !\n0\"\n#20\n"));
+}
+
+#[test]
+fn rejects_bad_arguments() {
+    assert_eq!(ruststone(&[]).status.code(), Some(2));
+    assert_eq!(
+        ruststone(&[&world(), "--format", "png"]).status.code(),
+        Some(2)
+    );
+    assert_eq!(
+        ruststone(&[&world(), "--probe", "9,9,9"]).status.code(),
+        Some(1)
+    );
+    assert_eq!(
+        ruststone(&["/nonexistent/world.txt"]).status.code(),
+        Some(1)
+    );
+    assert!(ruststone(&["--help"]).status.success());
+}
@@ -0,0 +1,359 @@
+use ruststone::{
+    compiled::CompiledWorld,
+    vec3::{Direction, Vec3},
+    voxels::Voxel,
+    world::{NoteEvent, World},
+    Budget, CrafterSlot, RunOutcome,
+};
+
+fn vec3s() -> impl Iterator<Item = Vec3> {
+    (-2..20).flat_map(|x| (-2..4).flat_map(move |y| (-2..20).map(move |z| Vec3(x, y, z))))
+}
+
+/// Every redstate of `world`, along with where it played notes.
+fn interpreted_state(world: &World) -> (Vec<Option<(u8, bool)>>, Vec<Vec3>) {
+    let redstates = vec3s()
+        .map(|vec3| {
+            world
+                .get(vec3)
+                .map(|r| (r.redstate().get_power(), r.redstate().is_forced()))
+        })
+        .collect();
+
+    (redstates, notes_played(world.notes()))
+}
+
+fn compiled_state(compiled: &CompiledWorld) -> (Vec<Option<(u8, bool)>>, Vec<Vec3>) {
+    let redstates = vec3s()
+        .map(|vec3| {
+            compiled
+                .redstate(vec3)
+                .map(|r| (r.get_power(), r.is_forced()))
+        })
+        .collect();
+
+    (redstates, notes_played(compiled.notes()))
+}
+
+/// Where `notes` were played, leaving out when. The interpreter counts the ticks of its notes
+/// from the start of the run that played them.
+fn notes_played(notes: Vec<NoteEvent>) -> Vec<Vec3> {
+    let mut vec3s: Vec<Vec3> = notes.into_iter().map(|n| n.vec3).collect();
+    vec3s.sort_by_key(|v| (v.x(), v.y(), v.z()));
+    vec3s
+}
+
+/// Steps `world` through the compiled backend, and checks that every step leaves it the way the
+/// interpreter leaves it when run up to the same tick, a redstone tick at a time. Once it is done,
+/// it has to have played the same notes at the same ticks as a single run of the interpreter.
+fn assert_compiles_the_same(world: World) -> CompiledWorld {
+    let mut interpreted = world.clone();
+    interpreted.set_max_threads(1);
+
+    let mut compiled = world.clone().compile();
+    // The compiled world counts its ticks from where the world was compiled.
+    let start = interpreted.tick();
+
+    while !compiled.is_done() {
+        let tick = compiled.tick();
+        assert!(tick < 1000, "the world never settled");
+        compiled.step();
+
+        // Once done, the compiled world stays on the last tick it dispatched.
+        let reached = if compiled.is_done() {
+            tick + 2
+        } else {
+            compiled.tick()
+        };
+
+        while interpreted.tick() - start < reached {
+            interpreted.run_for(2);
+        }
+
+        assert!(
+            interpreted_state(&interpreted) == compiled_state(&compiled),
+            "the backends disagree at tick {reached}"
+        );
+    }
+
+    interpreted.run();
+    assert!(interpreted_state(&interpreted) == compiled_state(&compiled));
+
+    let mut interpreted = world;
+    interpreted.set_max_threads(1);
+    interpreted.run();
+    assert_eq!(interpreted.notes(), compiled.notes());
+
+    compiled
+}
+
+#[test]
+fn torch_powers_up_the_dust() {
+    let mut world = World::new();
+
+    world[Vec3(0, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 0)] = Voxel::torch().voxel();
+    world[Vec3(0, 0, 1)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 1)] = Voxel::dust().voxel();
+
+    let compiled = assert_compiles_the_same(world);
+
+    assert!(compiled.redstate(Vec3(0, 1, 1)).unwrap().is_on());
+    assert!(compiled.is_done());
+}
+
+#[test]
+fn wall_torches_and_dust() {
+    let mut world = World::new();
+
+    world[Vec3(0, 0, 2)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 2)] = Voxel::torch().voxel();
+    world[Vec3(1, 0, 2)] = Voxel::stone().voxel();
+    world[Vec3(1, 1, 2)] = Voxel::dust().voxel();
+
+    world[Vec3(2, 1, 0)] = Voxel::stone().voxel();
+    world[Vec3(2, 2, 0)] = Voxel::torch().voxel();
+    world[Vec3(2, 1, 1)] = Voxel::stone().voxel();
+    world[Vec3(2, 2, 1)] = Voxel::dust().voxel();
+    world[Vec3(2, 1, 2)] = Voxel::stone().voxel();
+    world[Vec3(2, 2, 2)] = Voxel::torch().voxel();
+
+    world[Vec3(3, 1, 1)] = Voxel::torch().facing_east().voxel();
+    world[Vec3(4, 0, 1)] = Voxel::stone().voxel();
+    world[Vec3(4, 1, 1)] = Voxel::dust().voxel();
+
+    assert_compiles_the_same(world);
+}
+
+#[test]
+fn torch_towers() {
+    let mut world = World::new();
+
+    for (x, torch) in [Voxel::torch(), Voxel::torch().facing_west()]
+        .into_iter()
+        .enumerate()
+    {
+        let x = 2 * x as i32;
+        world[Vec3(x, 0, 0)] = Voxel::stone().voxel();
+        world[Vec3(x, 1, 0)] = Voxel::torch().voxel();
+        world[Vec3(x, 2, 0)] = Voxel::stone().voxel();
+        world[Vec3(x, 3, 0)] = torch.voxel();
+    }
+    world[Vec3(1, 3, 0)] = Voxel::stone().voxel();
+
+    let compiled = assert_compiles_the_same(world);
+
+    assert!(compiled.redstate(Vec3(0, 3, 0)).unwrap().is_off());
+    assert!(compiled.redstate(Vec3(2, 3, 0)).unwrap().is_on());
+}
+
+#[test]
+fn repeaters() {
+    let mut world = World::new();
+
+    world[Vec3(0, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 0)] = Voxel::torch().voxel();
+    for x in 1..=6 {
+        world[Vec3(x, 0, 0)] = Voxel::stone().voxel();
+    }
+    world[Vec3(1, 1, 0)] = Voxel::dust().voxel();
+    world[Vec3(2, 1, 0)] = Voxel::repeater()
+        .facing(Direction::West)
+        .unwrap()
+        .delay(3)
+        .unwrap()
+        .voxel();
+    world[Vec3(3, 1, 0)] = Voxel::dust().voxel();
+    world[Vec3(4, 1, 0)] = Voxel::repeater()
+        .facing(Direction::West)
+        .unwrap()
+        .delay(2)
+        .unwrap()
+        .voxel();
+    world[Vec3(5, 1, 0)] = Voxel::stone().voxel();
+    world[Vec3(6, 1, 0)] = Voxel::note_block().voxel();
+
+    let compiled = assert_compiles_the_same(world);
+
+    assert!(compiled.redstate(Vec3(5, 1, 0)).unwrap().is_on());
+    assert_eq!(compiled.notes().len(), 1);
+}
+
+#[test]
+fn repeaters_in_a_row() {
+    let mut world = World::new();
+
+    for x in 0..=4 {
+        world[Vec3(x, 0, 0)] = Voxel::stone().voxel();
+    }
+    world[Vec3(0, 1, 0)] = Voxel::torch().voxel();
+    world[Vec3(1, 1, 0)] = Voxel::dust().voxel();
+    for (x, delay) in [(2, 3), (3, 1)] {
+        world[Vec3(x, 1, 0)] = Voxel::repeater()
+            .facing(Direction::West)
+            .unwrap()
+            .delay(delay)
+            .unwrap()
+            .voxel();
+    }
+    world[Vec3(4, 1, 0)] = Voxel::dust().voxel();
+    assert!(world.validate().is_empty());
+
+    let compiled = assert_compiles_the_same(world);
+
+    assert!(compiled.redstate(Vec3(3, 1, 0)).unwrap().is_on());
+    assert_eq!(compiled.redstate(Vec3(4, 1, 0)).unwrap().get_power(), 15);
+}
+
+#[test]
+fn mechanisms() {
+    let mut world = World::new();
+
+    world[Vec3(0, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 0)] = Voxel::torch().voxel();
+    world[Vec3(1, 1, 0)] = Voxel::copper_bulb().voxel();
+    world[Vec3(-1, 1, 0)] = Voxel::note_block().pitch(3).unwrap().voxel();
+    world[Vec3(0, 1, 1)] = Voxel::piston().voxel();
+
+    world[Vec3(4, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(4, 1, 0)] = Voxel::torch().voxel();
+    world[Vec3(5, 0, 0)] = Voxel::crafter()
+        .with_slot(0, CrafterSlot::Filled(2))
+        .unwrap()
+        .voxel();
+    world[Vec3(5, 1, 0)] = Voxel::dust().voxel();
+
+    // A BUD switch: the torch only quasi-powers the piston below it.
+    world[Vec3(8, 1, 0)] = Voxel::piston().voxel();
+    world[Vec3(9, 1, 0)] = Voxel::stone().voxel();
+    world[Vec3(9, 2, 0)] = Voxel::torch().voxel();
+
+    let compiled = assert_compiles_the_same(world);
+
+    assert_eq!(compiled.notes().len(), 1);
+}
+
+#[test]
+fn notes_play_at_the_same_tick() {
+    let mut world = World::new();
+
+    for z in 0..8 {
+        let z = z * 2;
+
+        world[Vec3(0, 0, z)] = Voxel::stone().voxel();
+        world[Vec3(0, 1, z)] = Voxel::torch().voxel();
+
+        for x in 1..=z + 1 {
+            world[Vec3(x, 0, z)] = Voxel::stone().voxel();
+            world[Vec3(x, 1, z)] = Voxel::dust().voxel();
+        }
+
+        world[Vec3(z + 2, 0, z)] = Voxel::note_block().voxel();
+    }
+
+    let compiled = assert_compiles_the_same(world);
+
+    assert_eq!(compiled.notes().len(), 8);
+}
+
+#[test]
+fn compiling_after_a_run_picks_up_where_it_left_off() {
+    let mut world = World::new();
+
+    world[Vec3(0, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 0)] = Voxel::torch().voxel();
+    world[Vec3(0, 0, 1)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 1)] = Voxel::dust().voxel();
+
+    world.run();
+    world[Vec3(0, 1, 0)] = Voxel::air().voxel();
+
+    let compiled = assert_compiles_the_same(world);
+
+    assert!(compiled.redstate(Vec3(0, 1, 0)).is_none());
+    assert!(compiled.redstate(Vec3(0, 1, 1)).unwrap().is_off());
+}
+
+#[test]
+fn stepping_advances_by_frames() {
+    let mut world = World::new();
+
+    world[Vec3(0, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 0)] = Voxel::torch().voxel();
+    world[Vec3(0, 0, 1)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 1)] = Voxel::dust().voxel();
+
+    let mut compiled = world.compile();
+
+    assert_eq!(compiled.tick(), 0);
+    assert!(compiled.redstate(Vec3(0, 1, 0)).unwrap().is_off());
+
+    // The torch takes a redstone tick to light up.
+    compiled.step();
+    assert_eq!(compiled.tick(), 2);
+    assert!(compiled.redstate(Vec3(0, 1, 0)).unwrap().is_off());
+
+    compiled.step();
+    assert!(compiled.redstate(Vec3(0, 1, 0)).unwrap().is_on());
+    assert!(compiled.redstate(Vec3(0, 1, 1)).unwrap().is_on());
+    assert!(compiled.is_done());
+}
+
+#[test]
+fn compiled_world_runs_out_of_dispatches() {
+    let mut world = World::new();
+
+    world[Vec3(0, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 0)] = Voxel::torch().voxel();
+    world[Vec3(0, 0, 1)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 1)] = Voxel::dust().voxel();
+
+    let mut compiled = world.compile();
+
+    assert_eq!(
+        compiled.run_with_budget(Budget::new().dispatches(1)),
+        RunOutcome::BudgetExhausted
+    );
+    assert!(compiled.redstate(Vec3(0, 1, 1)).unwrap().is_off());
+
+    assert_eq!(compiled.run(), RunOutcome::Stable);
+    assert!(compiled.redstate(Vec3(0, 1, 1)).unwrap().is_on());
+}
+
+#[test]
+fn clocks_oscillate_the_same_compiled() {
+    let mut world = World::new();
+
+    // A torch on the side of a block, with dust leading around to a repeater into the block.
+    world[Vec3(0, 1, 0)] = Voxel::stone().voxel();
+    world[Vec3(1, 1, 0)] = Voxel::torch().facing(Direction::East).unwrap().voxel();
+    world[Vec3(0, 1, 1)] = Voxel::repeater()
+        .facing(Direction::North)
+        .unwrap()
+        .delay(4)
+        .unwrap()
+        .voxel();
+    world[Vec3(0, 0, 1)] = Voxel::stone().voxel();
+    for vec3 in [
+        Vec3(2, 1, 0),
+        Vec3(2, 1, 1),
+        Vec3(2, 1, 2),
+        Vec3(1, 1, 2),
+        Vec3(0, 1, 2),
+    ] {
+        world[vec3.down()] = Voxel::stone().voxel();
+        world[vec3] = Voxel::dust().voxel();
+    }
+
+    let mut interpreted = world.clone();
+    let outcome = interpreted.run();
+    assert_eq!(
+        outcome,
+        RunOutcome::Oscillating {
+            period: 20,
+            phase: 10
+        }
+    );
+
+    assert_eq!(world.compile().run(), outcome);
+}
@@ -0,0 +1,85 @@
+use ruststone::{dot::Dot, vec3::Vec3, voxels::Voxel, world::World, MechanismKind, RedstoneArena};
+
+#[test]
+fn torch_and_dust() {
+    let mut arena = RedstoneArena::new();
+    let torch = arena.make_torch("torch");
+    let dust = arena.make_dust("dust");
+    let block = arena.make_block("block");
+    arena.link(torch, dust).unwrap();
+    arena.link(dust, block).unwrap();
+    arena.add_weighted_edge(dust, torch, 0).unwrap();
+
+    let dot = Dot::arena(&arena).to_string();
+
+    assert_eq!(
+        dot,
+        "digraph redstone {
+    node [shape=box];
+    n0 [label=\"torch\\ntorch\"];
+    n1 [label=\"dust\\ndust\"];
+    n2 [label=\"block\\nblock\"];
+    n0 -> n1;
+    n1 -> n2 [dir=none];
+    n0 -> n1 [style=dashed, label=\"0\"];
+}
+"
+    );
+}
+
+#[test]
+fn repeaters_point_at_what_they_power() {
+    let mut arena = RedstoneArena::new();
+    let block = arena.make_block("block");
+    let repeater = arena.make_repeater("repeater", 3).unwrap();
+    let lamp = arena.make_block("lamp");
+    arena.link(block, repeater).unwrap();
+    arena.link(repeater, lamp).unwrap();
+
+    let dot = Dot::arena(&arena).to_string();
+
+    assert!(dot.contains("n1 [label=\"repeater\\nrepeater (3)\"];\n"));
+    assert!(dot.contains("n0 -> n1;\n"));
+    assert!(dot.contains("n1 -> n2;\n"));
+}
+
+#[test]
+fn quasi_links_are_drawn_once() {
+    let mut arena = RedstoneArena::new();
+    let torch = arena.make_torch("torch");
+    let piston = arena.make_mechanism("piston", MechanismKind::Piston);
+    arena.quasi_link(torch, piston).unwrap();
+
+    let dot = Dot::arena(&arena).to_string();
+
+    assert!(dot.contains("n1 [label=\"piston\\npiston\"];\n"));
+    assert!(dot.contains("n0 -> n1 [style=dotted, label=\"quasi\"];\n"));
+    assert!(!dot.contains("n0 -> n1;\n"));
+}
+
+#[test]
+fn world_graphs_are_coloured_by_redstate() {
+    let mut world = World::new();
+
+    world[Vec3(0, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 0)] = Voxel::torch().voxel();
+    world[Vec3(5, 0, 0)] = Voxel::stone().voxel();
+
+    world.run();
+
+    let rgs = world.redstone_graphs();
+
+    let torch = world.redstone_id(Vec3(0, 1, 0)).unwrap();
+    let rg = rgs
+        .iter()
+        .find(|rg| rg.redstones().contains(&torch))
+        .unwrap();
+    let dot = Dot::graph(world.arena(), rg).with_redstate().to_string();
+
+    assert!(dot.contains(&format!(
+        "n{} [label=\"torch {}\\ntorch\\npower 16\", style=filled, fillcolor=\"0.0 1.000 1.0\"];\n",
+        torch.index(),
+        Vec3(0, 1, 0)
+    )));
+    assert!(!dot.contains(&format!("stone {}", Vec3(5, 0, 0))));
+}
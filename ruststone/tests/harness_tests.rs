@@ -0,0 +1,70 @@
+use ruststone::{ascii::parse_world, harness::Harness, vec3::Vec3};
+
+/// Two torches at either end of a line of dust.
+fn or_gate() -> Harness {
+    let world = parse_world(
+        "layer 0
+#####
+layer 1
+*+++*
+",
+    )
+    .unwrap();
+
+    Harness::new(world)
+        .input("a", Vec3(0, 1, 0))
+        .input("b", Vec3(4, 1, 0))
+        .output("out", Vec3(2, 1, 0))
+}
+
+#[test]
+fn rows_start_over_from_the_world() {
+    let harness = or_gate();
+
+    assert_eq!(harness.row(&[true, false]), [true]);
+    assert_eq!(harness.row(&[false, false]), [false]);
+    assert_eq!(harness.row(&[false, true]), [true]);
+}
+
+#[test]
+fn truth_table_covers_every_row() {
+    or_gate().assert_truth_table(|inputs| vec![inputs[0] || inputs[1]]);
+}
+
+#[test]
+#[should_panic(expected = "row 3 (a=1 b=1) gave out=1, expected out=0")]
+fn truth_table_reports_the_offending_row() {
+    or_gate().assert_truth_table(|inputs| vec![inputs[0] ^ inputs[1]]);
+}
+
+#[test]
+fn edges_are_timed_from_the_input() {
+    let harness = or_gate();
+
+    let mut session = harness.session(&[false, false]);
+    session.run_for(4).set("a", true).run_for(10);
+    session.assert_rises_after("a", "out", 2);
+    assert_eq!(session.edges("out"), [(6, true)]);
+
+    session.set("a", false).run_for(10);
+    session.assert_falls_after("a", "out", 0);
+}
+
+#[test]
+#[should_panic(expected = "out rose 2 ticks after a changed at 0, expected it to rise after 3")]
+fn edges_that_are_late_or_early_fail() {
+    let harness = or_gate();
+
+    let mut session = harness.session(&[false, false]);
+    session.set("a", true).run_for(10);
+    session.assert_rises_after("a", "out", 3);
+}
+
+#[test]
+fn pulses_are_measured() {
+    let harness = or_gate();
+
+    let mut session = harness.session(&[false, false]);
+    session.pulse("b", 6).run_for(10);
+    session.assert_pulse("out", 4);
+}
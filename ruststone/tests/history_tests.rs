@@ -0,0 +1,104 @@
+use ruststone::{
+    history::{Change, History},
+    stimulus::Action,
+    text::parse_world,
+    vec3::Vec3,
+    world::World,
+};
+
+fn torch_and_dust() -> World {
+    parse_world(
+        "0 0 0 stone
+        0 1 0 torch
+        1 0 0 stone
+        1 1 0 dust
+        2 0 0 stone
+        2 1 0 dust",
+    )
+    .unwrap()
+}
+
+fn is_on(history: &History, vec3: Vec3) -> bool {
+    history.world().get(vec3).unwrap().redstate().is_on()
+}
+
+#[test]
+fn causes_are_traced_back_to_the_torch() {
+    let mut history = History::new(torch_and_dust());
+    history.run_for(10);
+
+    assert_eq!(
+        history.cause(Vec3(2, 1, 0), 2).map(|change| change.vec3),
+        Some(Vec3(1, 1, 0))
+    );
+
+    let chain: Vec<(Vec3, Option<Vec3>)> = history
+        .causal_chain(Vec3(2, 1, 0), 2)
+        .iter()
+        .map(|change| (change.vec3, change.cause))
+        .collect();
+    assert_eq!(
+        chain,
+        [
+            (Vec3(2, 1, 0), Some(Vec3(1, 1, 0))),
+            (Vec3(1, 1, 0), Some(Vec3(0, 1, 0))),
+            (Vec3(0, 1, 0), None),
+        ]
+    );
+
+    assert_eq!(history.cause(Vec3(2, 1, 0), 4), None);
+    assert!(history.causal_chain(Vec3(2, 1, 0), 4).is_empty());
+}
+
+#[test]
+fn rewinding_replays_what_was_applied() {
+    let mut history = History::new(torch_and_dust());
+
+    history.run_for(10);
+    history.apply(Action::Toggle(Vec3(0, 1, 0)));
+    history.run_for(90);
+    history.apply(Action::Toggle(Vec3(0, 1, 0)));
+    history.run_for(100);
+
+    assert_eq!(history.tick(), 200);
+    assert!(is_on(&history, Vec3(2, 1, 0)));
+
+    let recorded: Vec<Change> = (0..200)
+        .flat_map(|tick| history.changes_at(tick).to_vec())
+        .collect();
+
+    history.rewind_to(51);
+    assert_eq!(history.tick(), 50);
+    assert!(history.world()[Vec3(0, 1, 0)].is_air());
+    assert!(!is_on(&history, Vec3(2, 1, 0)));
+    assert!(history.changes_at(100).is_empty());
+
+    history.step_back();
+    assert_eq!(history.tick(), 48);
+
+    history.run_for(152);
+    let replayed: Vec<Change> = (0..200)
+        .flat_map(|tick| history.changes_at(tick).to_vec())
+        .collect();
+
+    assert_eq!(replayed, recorded);
+    assert!(is_on(&history, Vec3(2, 1, 0)));
+}
+
+#[test]
+fn applying_after_a_rewind_forgets_what_came_after() {
+    let mut history = History::new(torch_and_dust());
+
+    history.run_for(10);
+    history.apply(Action::Toggle(Vec3(0, 1, 0)));
+    history.run_for(90);
+
+    history.rewind_to(4);
+    history.apply(Action::Break(Vec3(2, 1, 0)));
+    history.run_for(96);
+
+    assert!(history.world()[Vec3(0, 1, 0)].is_torch());
+    assert!(history.world()[Vec3(2, 1, 0)].is_air());
+    assert!(is_on(&history, Vec3(1, 1, 0)));
+    assert!(history.changes_at(10).is_empty());
+}
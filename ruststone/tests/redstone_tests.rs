@@ -1,52 +1,55 @@
-use ruststone::{RedstoneGraph, RedstoneArena};
+use ruststone::{
+    trace::TraceEvent, Budget, CrafterSlot, Instrument, MechanismKind, RedstoneArena,
+    RedstoneGraph, RedstoneId, RedstoneNode, RunOutcome, RuststoneError,
+};
 
 #[test]
 fn torch_and_dust() {
-    let arena = RedstoneArena::new();
+    let mut arena = RedstoneArena::new();
 
     let torch = arena.make_torch("torch");
     let dust = arena.make_dust("dust");
 
-    torch.link(dust);
+    arena.link(torch, dust).unwrap();
 
-    ruststone::add_weighted_edge(dust, torch, 1);
+    arena.add_weighted_edge(dust, torch, 1).unwrap();
 
-    let rg = RedstoneGraph::collect(torch);
-    rg.run();
+    let rg = RedstoneGraph::collect(&arena, torch);
+    rg.run(&arena);
 
-    assert_eq!(torch.redstate().get_power(), 16);
-    assert_eq!(dust.redstate().get_power(), 15);
+    assert_eq!(arena[torch].redstate().get_power(), 16);
+    assert_eq!(arena[dust].redstate().get_power(), 15);
 }
 
 #[test]
 fn torch_and_dust_and_dust_and_dust() {
-    let arena = RedstoneArena::new();
+    let mut arena = RedstoneArena::new();
 
     let torch = arena.make_torch("torch");
     let dust1 = arena.make_dust("dust1");
     let dust2 = arena.make_dust("dust2");
     let dust3 = arena.make_dust("dust3");
 
-    torch.link(dust1);
-    dust1.link(dust2);
-    dust2.link(dust3);
+    arena.link(torch, dust1).unwrap();
+    arena.link(dust1, dust2).unwrap();
+    arena.link(dust2, dust3).unwrap();
 
-    ruststone::add_weighted_edge(dust1, torch, 1);
-    ruststone::add_weighted_edge(dust2, torch, 2);
-    ruststone::add_weighted_edge(dust3, torch, 3);
+    arena.add_weighted_edge(dust1, torch, 1).unwrap();
+    arena.add_weighted_edge(dust2, torch, 2).unwrap();
+    arena.add_weighted_edge(dust3, torch, 3).unwrap();
 
-    let rg = RedstoneGraph::collect(torch);
-    rg.run();
+    let rg = RedstoneGraph::collect(&arena, torch);
+    rg.run(&arena);
 
-    assert_eq!(torch.redstate().get_power(), 16);
-    assert_eq!(dust1.redstate().get_power(), 15);
-    assert_eq!(dust2.redstate().get_power(), 14);
-    assert_eq!(dust3.redstate().get_power(), 13);
+    assert_eq!(arena[torch].redstate().get_power(), 16);
+    assert_eq!(arena[dust1].redstate().get_power(), 15);
+    assert_eq!(arena[dust2].redstate().get_power(), 14);
+    assert_eq!(arena[dust3].redstate().get_power(), 13);
 }
 
 #[test]
 fn torch_and_dust_until_it_runs_out_of_redpower() {
-    let arena = RedstoneArena::new();
+    let mut arena = RedstoneArena::new();
 
     let torch = arena.make_torch("torch");
     let dust1 = arena.make_dust("dust1");
@@ -67,54 +70,54 @@ fn torch_and_dust_until_it_runs_out_of_redpower() {
     let dust16 = arena.make_dust("dust16");
     let dust17 = arena.make_dust("dust17");
 
-    torch.link(dust1);
-    dust1.link(dust2);
-    dust2.link(dust3);
-    dust3.link(dust4);
-    dust4.link(dust5);
-    dust5.link(dust6);
-    dust6.link(dust7);
-    dust7.link(dust8);
-    dust8.link(dust9);
-    dust9.link(dust10);
-    dust10.link(dust11);
-    dust11.link(dust12);
-    dust12.link(dust13);
-    dust13.link(dust14);
-    dust14.link(dust15);
-    dust15.link(dust16);
-    dust16.link(dust17);
-
-    ruststone::add_weighted_edge(dust1, torch, 1);
-    ruststone::add_weighted_edge(dust2, torch, 2);
-    ruststone::add_weighted_edge(dust3, torch, 3);
-    ruststone::add_weighted_edge(dust4, torch, 4);
-    ruststone::add_weighted_edge(dust5, torch, 5);
-    ruststone::add_weighted_edge(dust6, torch, 6);
-    ruststone::add_weighted_edge(dust7, torch, 7);
-    ruststone::add_weighted_edge(dust8, torch, 8);
-    ruststone::add_weighted_edge(dust9, torch, 9);
-    ruststone::add_weighted_edge(dust10, torch, 10);
-    ruststone::add_weighted_edge(dust11, torch, 11);
-    ruststone::add_weighted_edge(dust12, torch, 12);
-    ruststone::add_weighted_edge(dust13, torch, 13);
-    ruststone::add_weighted_edge(dust14, torch, 14);
-    ruststone::add_weighted_edge(dust15, torch, 15);
-    ruststone::add_weighted_edge(dust16, torch, 16);
-    ruststone::add_weighted_edge(dust17, torch, 17);
-
-    let rg = RedstoneGraph::collect(torch);
-    rg.run();
-
-    assert_eq!(torch.redstate().get_power(), 16);
-    assert_eq!(dust15.redstate().get_power(), 1);
-    assert_eq!(dust16.redstate().get_power(), 0);
-    assert_eq!(dust17.redstate().get_power(), 0);
+    arena.link(torch, dust1).unwrap();
+    arena.link(dust1, dust2).unwrap();
+    arena.link(dust2, dust3).unwrap();
+    arena.link(dust3, dust4).unwrap();
+    arena.link(dust4, dust5).unwrap();
+    arena.link(dust5, dust6).unwrap();
+    arena.link(dust6, dust7).unwrap();
+    arena.link(dust7, dust8).unwrap();
+    arena.link(dust8, dust9).unwrap();
+    arena.link(dust9, dust10).unwrap();
+    arena.link(dust10, dust11).unwrap();
+    arena.link(dust11, dust12).unwrap();
+    arena.link(dust12, dust13).unwrap();
+    arena.link(dust13, dust14).unwrap();
+    arena.link(dust14, dust15).unwrap();
+    arena.link(dust15, dust16).unwrap();
+    arena.link(dust16, dust17).unwrap();
+
+    arena.add_weighted_edge(dust1, torch, 1).unwrap();
+    arena.add_weighted_edge(dust2, torch, 2).unwrap();
+    arena.add_weighted_edge(dust3, torch, 3).unwrap();
+    arena.add_weighted_edge(dust4, torch, 4).unwrap();
+    arena.add_weighted_edge(dust5, torch, 5).unwrap();
+    arena.add_weighted_edge(dust6, torch, 6).unwrap();
+    arena.add_weighted_edge(dust7, torch, 7).unwrap();
+    arena.add_weighted_edge(dust8, torch, 8).unwrap();
+    arena.add_weighted_edge(dust9, torch, 9).unwrap();
+    arena.add_weighted_edge(dust10, torch, 10).unwrap();
+    arena.add_weighted_edge(dust11, torch, 11).unwrap();
+    arena.add_weighted_edge(dust12, torch, 12).unwrap();
+    arena.add_weighted_edge(dust13, torch, 13).unwrap();
+    arena.add_weighted_edge(dust14, torch, 14).unwrap();
+    arena.add_weighted_edge(dust15, torch, 15).unwrap();
+    arena.add_weighted_edge(dust16, torch, 16).unwrap();
+    arena.add_weighted_edge(dust17, torch, 17).unwrap();
+
+    let rg = RedstoneGraph::collect(&arena, torch);
+    rg.run(&arena);
+
+    assert_eq!(arena[torch].redstate().get_power(), 16);
+    assert_eq!(arena[dust15].redstate().get_power(), 1);
+    assert_eq!(arena[dust16].redstate().get_power(), 0);
+    assert_eq!(arena[dust17].redstate().get_power(), 0);
 }
 
 #[test]
 fn dust_in_the_middle_of_two_torches() {
-    let arena = RedstoneArena::new();
+    let mut arena = RedstoneArena::new();
 
     let torch_l = arena.make_torch("torch_l");
     let dust1 = arena.make_dust("dust1");
@@ -124,90 +127,90 @@ fn dust_in_the_middle_of_two_torches() {
     let dust5 = arena.make_dust("dust5");
     let torch_r = arena.make_torch("torch_r");
 
-    torch_l.link(dust1);
-    dust1.link(dust2);
-    dust2.link(dust3);
-    dust3.link(dust4);
-    dust4.link(dust5);
-    torch_r.link(dust5);
-
-    ruststone::add_weighted_edge(dust1, torch_l, 1);
-    ruststone::add_weighted_edge(dust2, torch_l, 2);
-    ruststone::add_weighted_edge(dust3, torch_l, 3);
-    ruststone::add_weighted_edge(dust4, torch_l, 4);
-    ruststone::add_weighted_edge(dust5, torch_l, 5);
-
-    ruststone::add_weighted_edge(dust5, torch_r, 1);
-    ruststone::add_weighted_edge(dust4, torch_r, 2);
-    ruststone::add_weighted_edge(dust3, torch_r, 3);
-    ruststone::add_weighted_edge(dust2, torch_r, 4);
-    ruststone::add_weighted_edge(dust1, torch_r, 5);
-
-    let rg = RedstoneGraph::collect(torch_l);
-    rg.run();
-
-    assert_eq!(torch_l.redstate().get_power(), 16);
-    assert_eq!(dust1.redstate().get_power(), 15);
-    assert_eq!(dust2.redstate().get_power(), 14);
-    assert_eq!(dust3.redstate().get_power(), 13);
-    assert_eq!(dust4.redstate().get_power(), 14);
-    assert_eq!(dust5.redstate().get_power(), 15);
-    assert_eq!(torch_r.redstate().get_power(), 16);
+    arena.link(torch_l, dust1).unwrap();
+    arena.link(dust1, dust2).unwrap();
+    arena.link(dust2, dust3).unwrap();
+    arena.link(dust3, dust4).unwrap();
+    arena.link(dust4, dust5).unwrap();
+    arena.link(torch_r, dust5).unwrap();
+
+    arena.add_weighted_edge(dust1, torch_l, 1).unwrap();
+    arena.add_weighted_edge(dust2, torch_l, 2).unwrap();
+    arena.add_weighted_edge(dust3, torch_l, 3).unwrap();
+    arena.add_weighted_edge(dust4, torch_l, 4).unwrap();
+    arena.add_weighted_edge(dust5, torch_l, 5).unwrap();
+
+    arena.add_weighted_edge(dust5, torch_r, 1).unwrap();
+    arena.add_weighted_edge(dust4, torch_r, 2).unwrap();
+    arena.add_weighted_edge(dust3, torch_r, 3).unwrap();
+    arena.add_weighted_edge(dust2, torch_r, 4).unwrap();
+    arena.add_weighted_edge(dust1, torch_r, 5).unwrap();
+
+    let rg = RedstoneGraph::collect(&arena, torch_l);
+    rg.run(&arena);
+
+    assert_eq!(arena[torch_l].redstate().get_power(), 16);
+    assert_eq!(arena[dust1].redstate().get_power(), 15);
+    assert_eq!(arena[dust2].redstate().get_power(), 14);
+    assert_eq!(arena[dust3].redstate().get_power(), 13);
+    assert_eq!(arena[dust4].redstate().get_power(), 14);
+    assert_eq!(arena[dust5].redstate().get_power(), 15);
+    assert_eq!(arena[torch_r].redstate().get_power(), 16);
 }
 
 #[test]
 fn torch_is_off_if_its_incoming_edge_is_on() {
-    let arena = RedstoneArena::new();
+    let mut arena = RedstoneArena::new();
 
     let torch = arena.make_torch("torch");
     let dust = arena.make_dust("dust");
     let normal_block = arena.make_block("normal_block");
     let output = arena.make_torch("output");
 
-    torch.link(dust);
-    dust.link(normal_block);
-    normal_block.link(output);
+    arena.link(torch, dust).unwrap();
+    arena.link(dust, normal_block).unwrap();
+    arena.link(normal_block, output).unwrap();
 
-    ruststone::add_weighted_edge(dust, torch, 1);
+    arena.add_weighted_edge(dust, torch, 1).unwrap();
 
-    let rg = RedstoneGraph::collect(torch);
-    rg.run();
+    let rg = RedstoneGraph::collect(&arena, torch);
+    rg.run(&arena);
 
-    assert_eq!(torch.redstate().get_power(), 16);
-    assert_eq!(dust.redstate().get_power(), 15);
-    assert_eq!(normal_block.redstate().get_power(), 0);
-    assert!(normal_block.redstate().is_forced());
-    assert_eq!(output.redstate().get_power(), 0);
+    assert_eq!(arena[torch].redstate().get_power(), 16);
+    assert_eq!(arena[dust].redstate().get_power(), 15);
+    assert_eq!(arena[normal_block].redstate().get_power(), 0);
+    assert!(arena[normal_block].redstate().is_forced());
+    assert_eq!(arena[output].redstate().get_power(), 0);
 }
 
 #[test]
 fn torch_and_dust_and_block_and_dust() {
-    let arena = RedstoneArena::new();
+    let mut arena = RedstoneArena::new();
 
     let torch = arena.make_torch("torch");
     let dust1 = arena.make_dust("dust1");
     let normal_block = arena.make_block("normal_block");
     let dust2 = arena.make_dust("dust2");
 
-    torch.link(dust1);
-    dust1.link(normal_block);
-    normal_block.link(dust2);
+    arena.link(torch, dust1).unwrap();
+    arena.link(dust1, normal_block).unwrap();
+    arena.link(normal_block, dust2).unwrap();
 
-    ruststone::add_weighted_edge(dust1, torch, 1);
+    arena.add_weighted_edge(dust1, torch, 1).unwrap();
 
-    let rg = RedstoneGraph::collect(torch);
-    rg.run();
+    let rg = RedstoneGraph::collect(&arena, torch);
+    rg.run(&arena);
 
-    assert_eq!(torch.redstate().get_power(), 16);
-    assert_eq!(dust1.redstate().get_power(), 15);
-    assert_eq!(normal_block.redstate().get_power(), 0);
-    assert!(normal_block.redstate().is_forced());
-    assert_eq!(dust2.redstate().get_power(), 0);
+    assert_eq!(arena[torch].redstate().get_power(), 16);
+    assert_eq!(arena[dust1].redstate().get_power(), 15);
+    assert_eq!(arena[normal_block].redstate().get_power(), 0);
+    assert!(arena[normal_block].redstate().is_forced());
+    assert_eq!(arena[dust2].redstate().get_power(), 0);
 }
 
 #[test]
 fn and_gate() {
-    let arena = RedstoneArena::new();
+    let mut arena = RedstoneArena::new();
 
     let input_l = arena.make_torch("input_l");
     let input_r = arena.make_torch("input_r");
@@ -223,38 +226,38 @@ fn and_gate() {
     let block_m = arena.make_block("block_m");
     let block_r = arena.make_block("block_r");
 
-    input_l.link(dust_l);
-    dust_l.link(block_l);
-    block_l.link(and_l);
+    arena.link(input_l, dust_l).unwrap();
+    arena.link(dust_l, block_l).unwrap();
+    arena.link(block_l, and_l).unwrap();
 
-    input_r.link(dust_r);
-    dust_r.link(block_r);
-    block_r.link(and_r);
+    arena.link(input_r, dust_r).unwrap();
+    arena.link(dust_r, block_r).unwrap();
+    arena.link(block_r, and_r).unwrap();
 
-    and_l.link(dust_m);
-    and_r.link(dust_m);
-    dust_m.link(block_m);
+    arena.link(and_l, dust_m).unwrap();
+    arena.link(and_r, dust_m).unwrap();
+    arena.link(dust_m, block_m).unwrap();
 
-    block_m.link(output);
+    arena.link(block_m, output).unwrap();
 
-    ruststone::add_weighted_edge(dust_l, input_l, 1);
-    ruststone::add_weighted_edge(dust_r, input_r, 1);
-    ruststone::add_weighted_edge(dust_m, and_l, 1);
-    ruststone::add_weighted_edge(dust_m, and_r, 1);
+    arena.add_weighted_edge(dust_l, input_l, 1).unwrap();
+    arena.add_weighted_edge(dust_r, input_r, 1).unwrap();
+    arena.add_weighted_edge(dust_m, and_l, 1).unwrap();
+    arena.add_weighted_edge(dust_m, and_r, 1).unwrap();
 
-    let rg = RedstoneGraph::collect(output);
-    rg.run();
+    let rg = RedstoneGraph::collect(&arena, output);
+    rg.run(&arena);
 
-    assert!(input_l.redstate().is_on());
-    assert!(input_r.redstate().is_on());
-    assert!(and_l.redstate().is_off());
-    assert!(and_r.redstate().is_off());
-    assert!(output.redstate().is_on());
+    assert!(arena[input_l].redstate().is_on());
+    assert!(arena[input_r].redstate().is_on());
+    assert!(arena[and_l].redstate().is_off());
+    assert!(arena[and_r].redstate().is_off());
+    assert!(arena[output].redstate().is_on());
 }
 
 #[test]
 fn and_gate_with_one_arm_off() {
-    let arena = RedstoneArena::new();
+    let mut arena = RedstoneArena::new();
 
     let input_r = arena.make_torch("input_r");
     let and_l = arena.make_torch("and_l");
@@ -269,35 +272,35 @@ fn and_gate_with_one_arm_off() {
     let block_m = arena.make_block("block_m");
     let block_r = arena.make_block("block_r");
 
-    dust_l.link(block_l);
-    block_l.link(and_l);
+    arena.link(dust_l, block_l).unwrap();
+    arena.link(block_l, and_l).unwrap();
 
-    input_r.link(dust_r);
-    dust_r.link(block_r);
-    block_r.link(and_r);
+    arena.link(input_r, dust_r).unwrap();
+    arena.link(dust_r, block_r).unwrap();
+    arena.link(block_r, and_r).unwrap();
 
-    and_l.link(dust_m);
-    and_r.link(dust_m);
-    dust_m.link(block_m);
+    arena.link(and_l, dust_m).unwrap();
+    arena.link(and_r, dust_m).unwrap();
+    arena.link(dust_m, block_m).unwrap();
 
-    block_m.link(output);
+    arena.link(block_m, output).unwrap();
 
-    ruststone::add_weighted_edge(dust_r, input_r, 1);
-    ruststone::add_weighted_edge(dust_m, and_l, 1);
-    ruststone::add_weighted_edge(dust_m, and_r, 1);
+    arena.add_weighted_edge(dust_r, input_r, 1).unwrap();
+    arena.add_weighted_edge(dust_m, and_l, 1).unwrap();
+    arena.add_weighted_edge(dust_m, and_r, 1).unwrap();
 
-    let rg = RedstoneGraph::collect(output);
-    rg.run();
+    let rg = RedstoneGraph::collect(&arena, output);
+    rg.run(&arena);
 
-    assert!(input_r.redstate().is_on());
-    assert!(and_l.redstate().is_on());
-    assert!(and_r.redstate().is_off());
-    assert!(output.redstate().is_off());
+    assert!(arena[input_r].redstate().is_on());
+    assert!(arena[and_l].redstate().is_on());
+    assert!(arena[and_r].redstate().is_off());
+    assert!(arena[output].redstate().is_off());
 }
 
 #[test]
 fn and_gate_with_both_arms_off() {
-    let arena = RedstoneArena::new();
+    let mut arena = RedstoneArena::new();
 
     let and_l = arena.make_torch("and_l");
     let and_r = arena.make_torch("and_r");
@@ -311,32 +314,32 @@ fn and_gate_with_both_arms_off() {
     let block_m = arena.make_block("block_m");
     let block_r = arena.make_block("block_r");
 
-    dust_l.link(block_l);
-    block_l.link(and_l);
+    arena.link(dust_l, block_l).unwrap();
+    arena.link(block_l, and_l).unwrap();
 
-    dust_r.link(block_r);
-    block_r.link(and_r);
+    arena.link(dust_r, block_r).unwrap();
+    arena.link(block_r, and_r).unwrap();
 
-    and_l.link(dust_m);
-    and_r.link(dust_m);
-    dust_m.link(block_m);
+    arena.link(and_l, dust_m).unwrap();
+    arena.link(and_r, dust_m).unwrap();
+    arena.link(dust_m, block_m).unwrap();
 
-    block_m.link(output);
+    arena.link(block_m, output).unwrap();
 
-    ruststone::add_weighted_edge(dust_m, and_l, 1);
-    ruststone::add_weighted_edge(dust_m, and_r, 1);
+    arena.add_weighted_edge(dust_m, and_l, 1).unwrap();
+    arena.add_weighted_edge(dust_m, and_r, 1).unwrap();
 
-    let rg = RedstoneGraph::collect(output);
-    rg.run();
+    let rg = RedstoneGraph::collect(&arena, output);
+    rg.run(&arena);
 
-    assert!(and_l.redstate().is_on());
-    assert!(and_r.redstate().is_on());
-    assert!(output.redstate().is_off());
+    assert!(arena[and_l].redstate().is_on());
+    assert!(arena[and_r].redstate().is_on());
+    assert!(arena[output].redstate().is_off());
 }
 
 #[test]
 fn xor_gate() {
-    let arena = RedstoneArena::new();
+    let mut arena = RedstoneArena::new();
 
     let input_l = arena.make_torch("input_l");
     let input_dust_l = arena.make_dust("input_dust_l");
@@ -348,18 +351,30 @@ fn xor_gate() {
     let block_after_inversion_l = arena.make_block("block_after_inversion_l");
     let torch_after_dust_inversion_l = arena.make_torch("torch_after_dust_inversion_l");
 
-    input_l.link(input_dust_l);
-    input_dust_l.link(dust_block_l);
-    dust_block_l.link(torch_on_top_block_l);
-    dust_block_l.link(torch_in_front_block_l);
-    torch_in_front_block_l.link(dust_after_inversion_l);
-    dust_after_inversion_l.link(dust_after_inversion_l2);
-    dust_after_inversion_l2.link(block_after_inversion_l);
-    block_after_inversion_l.link(torch_after_dust_inversion_l);
-
-    ruststone::add_weighted_edge(input_dust_l, input_l, 1);
-    ruststone::add_weighted_edge(dust_after_inversion_l, torch_in_front_block_l, 1);
-    ruststone::add_weighted_edge(dust_after_inversion_l2, torch_in_front_block_l, 2);
+    arena.link(input_l, input_dust_l).unwrap();
+    arena.link(input_dust_l, dust_block_l).unwrap();
+    arena.link(dust_block_l, torch_on_top_block_l).unwrap();
+    arena.link(dust_block_l, torch_in_front_block_l).unwrap();
+    arena
+        .link(torch_in_front_block_l, dust_after_inversion_l)
+        .unwrap();
+    arena
+        .link(dust_after_inversion_l, dust_after_inversion_l2)
+        .unwrap();
+    arena
+        .link(dust_after_inversion_l2, block_after_inversion_l)
+        .unwrap();
+    arena
+        .link(block_after_inversion_l, torch_after_dust_inversion_l)
+        .unwrap();
+
+    arena.add_weighted_edge(input_dust_l, input_l, 1).unwrap();
+    arena
+        .add_weighted_edge(dust_after_inversion_l, torch_in_front_block_l, 1)
+        .unwrap();
+    arena
+        .add_weighted_edge(dust_after_inversion_l2, torch_in_front_block_l, 2)
+        .unwrap();
 
     let input_r = arena.make_torch("input_r");
     let input_dust_r = arena.make_dust("input_dust_r");
@@ -371,69 +386,105 @@ fn xor_gate() {
     let block_after_inversion_r = arena.make_block("block_after_inversion_r");
     let torch_after_dust_inversion_r = arena.make_torch("torch_after_dust_inversion_r");
 
-    input_r.link(input_dust_r);
-    input_dust_r.link(dust_block_r);
-    dust_block_r.link(torch_on_top_block_r);
-    dust_block_r.link(torch_in_front_block_r);
-    torch_in_front_block_r.link(dust_after_inversion_r);
-    dust_after_inversion_r.link(dust_after_inversion_r2);
-    dust_after_inversion_r2.link(block_after_inversion_r);
-    block_after_inversion_r.link(torch_after_dust_inversion_r);
-
-    ruststone::add_weighted_edge(input_dust_r, input_r, 1);
-    ruststone::add_weighted_edge(dust_after_inversion_r, torch_in_front_block_r, 1);
-    ruststone::add_weighted_edge(dust_after_inversion_r2, torch_in_front_block_r, 2);
+    arena.link(input_r, input_dust_r).unwrap();
+    arena.link(input_dust_r, dust_block_r).unwrap();
+    arena.link(dust_block_r, torch_on_top_block_r).unwrap();
+    arena.link(dust_block_r, torch_in_front_block_r).unwrap();
+    arena
+        .link(torch_in_front_block_r, dust_after_inversion_r)
+        .unwrap();
+    arena
+        .link(dust_after_inversion_r, dust_after_inversion_r2)
+        .unwrap();
+    arena
+        .link(dust_after_inversion_r2, block_after_inversion_r)
+        .unwrap();
+    arena
+        .link(block_after_inversion_r, torch_after_dust_inversion_r)
+        .unwrap();
+
+    arena.add_weighted_edge(input_dust_r, input_r, 1).unwrap();
+    arena
+        .add_weighted_edge(dust_after_inversion_r, torch_in_front_block_r, 1)
+        .unwrap();
+    arena
+        .add_weighted_edge(dust_after_inversion_r2, torch_in_front_block_r, 2)
+        .unwrap();
 
     let and_dust_1 = arena.make_dust("and_dust_1");
     let and_dust_2 = arena.make_dust("and_dust_2");
     let and_block = arena.make_block("and_block");
     let inversion_of_and = arena.make_torch("inversion_of_and");
 
-    torch_on_top_block_l.link(and_dust_1);
-    torch_on_top_block_r.link(and_dust_1);
-    and_dust_1.link(and_dust_2);
-    and_dust_2.link(and_block);
-    and_block.link(inversion_of_and);
-
-    ruststone::add_weighted_edge(and_dust_1, torch_in_front_block_l, 1);
-    ruststone::add_weighted_edge(and_dust_1, torch_in_front_block_r, 1);
-    ruststone::add_weighted_edge(and_dust_2, torch_in_front_block_l, 2);
-    ruststone::add_weighted_edge(and_dust_2, torch_in_front_block_r, 2);
-
-    inversion_of_and.link(dust_after_inversion_l);
-    inversion_of_and.link(dust_after_inversion_r);
-
-    ruststone::add_weighted_edge(dust_after_inversion_l, inversion_of_and, 1);
-    ruststone::add_weighted_edge(dust_after_inversion_l2, inversion_of_and, 2);
-    ruststone::add_weighted_edge(dust_after_inversion_r, inversion_of_and, 1);
-    ruststone::add_weighted_edge(dust_after_inversion_r2, inversion_of_and, 2);
+    arena.link(torch_on_top_block_l, and_dust_1).unwrap();
+    arena.link(torch_on_top_block_r, and_dust_1).unwrap();
+    arena.link(and_dust_1, and_dust_2).unwrap();
+    arena.link(and_dust_2, and_block).unwrap();
+    arena.link(and_block, inversion_of_and).unwrap();
+
+    arena
+        .add_weighted_edge(and_dust_1, torch_in_front_block_l, 1)
+        .unwrap();
+    arena
+        .add_weighted_edge(and_dust_1, torch_in_front_block_r, 1)
+        .unwrap();
+    arena
+        .add_weighted_edge(and_dust_2, torch_in_front_block_l, 2)
+        .unwrap();
+    arena
+        .add_weighted_edge(and_dust_2, torch_in_front_block_r, 2)
+        .unwrap();
+
+    arena
+        .link(inversion_of_and, dust_after_inversion_l)
+        .unwrap();
+    arena
+        .link(inversion_of_and, dust_after_inversion_r)
+        .unwrap();
+
+    arena
+        .add_weighted_edge(dust_after_inversion_l, inversion_of_and, 1)
+        .unwrap();
+    arena
+        .add_weighted_edge(dust_after_inversion_l2, inversion_of_and, 2)
+        .unwrap();
+    arena
+        .add_weighted_edge(dust_after_inversion_r, inversion_of_and, 1)
+        .unwrap();
+    arena
+        .add_weighted_edge(dust_after_inversion_r2, inversion_of_and, 2)
+        .unwrap();
 
     let output = arena.make_dust("output");
 
-    torch_after_dust_inversion_l.link(output);
-    torch_after_dust_inversion_r.link(output);
-
-    ruststone::add_weighted_edge(output, torch_after_dust_inversion_l, 1);
-    ruststone::add_weighted_edge(output, torch_after_dust_inversion_r, 1);
-
-    let rg = RedstoneGraph::collect(output);
-    rg.run();
-
-    assert!(input_l.redstate().is_on());
-    assert!(input_r.redstate().is_on());
-    assert!(torch_on_top_block_l.redstate().is_off());
-    assert!(torch_on_top_block_r.redstate().is_off());
-    assert!(torch_in_front_block_l.redstate().is_off());
-    assert!(torch_in_front_block_r.redstate().is_off());
-    assert!(inversion_of_and.redstate().is_on());
-    assert!(torch_after_dust_inversion_l.redstate().is_off());
-    assert!(torch_after_dust_inversion_r.redstate().is_off());
-    assert!(output.redstate().is_off());
+    arena.link(torch_after_dust_inversion_l, output).unwrap();
+    arena.link(torch_after_dust_inversion_r, output).unwrap();
+
+    arena
+        .add_weighted_edge(output, torch_after_dust_inversion_l, 1)
+        .unwrap();
+    arena
+        .add_weighted_edge(output, torch_after_dust_inversion_r, 1)
+        .unwrap();
+
+    let rg = RedstoneGraph::collect(&arena, output);
+    rg.run(&arena);
+
+    assert!(arena[input_l].redstate().is_on());
+    assert!(arena[input_r].redstate().is_on());
+    assert!(arena[torch_on_top_block_l].redstate().is_off());
+    assert!(arena[torch_on_top_block_r].redstate().is_off());
+    assert!(arena[torch_in_front_block_l].redstate().is_off());
+    assert!(arena[torch_in_front_block_r].redstate().is_off());
+    assert!(arena[inversion_of_and].redstate().is_on());
+    assert!(arena[torch_after_dust_inversion_l].redstate().is_off());
+    assert!(arena[torch_after_dust_inversion_r].redstate().is_off());
+    assert!(arena[output].redstate().is_off());
 }
 
 #[test]
 fn xor_gate_with_left_off() {
-    let arena = RedstoneArena::new();
+    let mut arena = RedstoneArena::new();
 
     let input_dust_l = arena.make_dust("input_dust_l");
     let dust_block_l = arena.make_block("dust_block_l");
@@ -444,16 +495,28 @@ fn xor_gate_with_left_off() {
     let block_after_inversion_l = arena.make_block("block_after_inversion_l");
     let torch_after_dust_inversion_l = arena.make_torch("torch_after_dust_inversion_l");
 
-    input_dust_l.link(dust_block_l);
-    dust_block_l.link(torch_on_top_block_l);
-    dust_block_l.link(torch_in_front_block_l);
-    torch_in_front_block_l.link(dust_after_inversion_l);
-    dust_after_inversion_l.link(dust_after_inversion_l2);
-    dust_after_inversion_l2.link(block_after_inversion_l);
-    block_after_inversion_l.link(torch_after_dust_inversion_l);
-
-    ruststone::add_weighted_edge(dust_after_inversion_l, torch_in_front_block_l, 1);
-    ruststone::add_weighted_edge(dust_after_inversion_l2, torch_in_front_block_l, 2);
+    arena.link(input_dust_l, dust_block_l).unwrap();
+    arena.link(dust_block_l, torch_on_top_block_l).unwrap();
+    arena.link(dust_block_l, torch_in_front_block_l).unwrap();
+    arena
+        .link(torch_in_front_block_l, dust_after_inversion_l)
+        .unwrap();
+    arena
+        .link(dust_after_inversion_l, dust_after_inversion_l2)
+        .unwrap();
+    arena
+        .link(dust_after_inversion_l2, block_after_inversion_l)
+        .unwrap();
+    arena
+        .link(block_after_inversion_l, torch_after_dust_inversion_l)
+        .unwrap();
+
+    arena
+        .add_weighted_edge(dust_after_inversion_l, torch_in_front_block_l, 1)
+        .unwrap();
+    arena
+        .add_weighted_edge(dust_after_inversion_l2, torch_in_front_block_l, 2)
+        .unwrap();
 
     let input_r = arena.make_torch("input_r");
     let input_dust_r = arena.make_dust("input_dust_r");
@@ -465,68 +528,104 @@ fn xor_gate_with_left_off() {
     let block_after_inversion_r = arena.make_block("block_after_inversion_r");
     let torch_after_dust_inversion_r = arena.make_torch("torch_after_dust_inversion_r");
 
-    input_r.link(input_dust_r);
-    input_dust_r.link(dust_block_r);
-    dust_block_r.link(torch_on_top_block_r);
-    dust_block_r.link(torch_in_front_block_r);
-    torch_in_front_block_r.link(dust_after_inversion_r);
-    dust_after_inversion_r.link(dust_after_inversion_r2);
-    dust_after_inversion_r2.link(block_after_inversion_r);
-    block_after_inversion_r.link(torch_after_dust_inversion_r);
-
-    ruststone::add_weighted_edge(input_dust_r, input_r, 1);
-    ruststone::add_weighted_edge(dust_after_inversion_r, torch_in_front_block_r, 1);
-    ruststone::add_weighted_edge(dust_after_inversion_r2, torch_in_front_block_r, 2);
+    arena.link(input_r, input_dust_r).unwrap();
+    arena.link(input_dust_r, dust_block_r).unwrap();
+    arena.link(dust_block_r, torch_on_top_block_r).unwrap();
+    arena.link(dust_block_r, torch_in_front_block_r).unwrap();
+    arena
+        .link(torch_in_front_block_r, dust_after_inversion_r)
+        .unwrap();
+    arena
+        .link(dust_after_inversion_r, dust_after_inversion_r2)
+        .unwrap();
+    arena
+        .link(dust_after_inversion_r2, block_after_inversion_r)
+        .unwrap();
+    arena
+        .link(block_after_inversion_r, torch_after_dust_inversion_r)
+        .unwrap();
+
+    arena.add_weighted_edge(input_dust_r, input_r, 1).unwrap();
+    arena
+        .add_weighted_edge(dust_after_inversion_r, torch_in_front_block_r, 1)
+        .unwrap();
+    arena
+        .add_weighted_edge(dust_after_inversion_r2, torch_in_front_block_r, 2)
+        .unwrap();
 
     let and_dust_1 = arena.make_dust("and_dust_1");
     let and_dust_2 = arena.make_dust("and_dust_2");
     let and_block = arena.make_block("and_block");
     let inversion_of_and = arena.make_torch("inversion_of_and");
 
-    torch_on_top_block_l.link(and_dust_1);
-    torch_on_top_block_r.link(and_dust_1);
-    and_dust_1.link(and_dust_2);
-    and_dust_2.link(and_block);
-    and_block.link(inversion_of_and);
-
-    ruststone::add_weighted_edge(and_dust_1, torch_in_front_block_l, 1);
-    ruststone::add_weighted_edge(and_dust_1, torch_in_front_block_r, 1);
-    ruststone::add_weighted_edge(and_dust_2, torch_in_front_block_l, 2);
-    ruststone::add_weighted_edge(and_dust_2, torch_in_front_block_r, 2);
-
-    inversion_of_and.link(dust_after_inversion_l);
-    inversion_of_and.link(dust_after_inversion_r);
-
-    ruststone::add_weighted_edge(dust_after_inversion_l, inversion_of_and, 1);
-    ruststone::add_weighted_edge(dust_after_inversion_l2, inversion_of_and, 2);
-    ruststone::add_weighted_edge(dust_after_inversion_r, inversion_of_and, 1);
-    ruststone::add_weighted_edge(dust_after_inversion_r2, inversion_of_and, 2);
+    arena.link(torch_on_top_block_l, and_dust_1).unwrap();
+    arena.link(torch_on_top_block_r, and_dust_1).unwrap();
+    arena.link(and_dust_1, and_dust_2).unwrap();
+    arena.link(and_dust_2, and_block).unwrap();
+    arena.link(and_block, inversion_of_and).unwrap();
+
+    arena
+        .add_weighted_edge(and_dust_1, torch_in_front_block_l, 1)
+        .unwrap();
+    arena
+        .add_weighted_edge(and_dust_1, torch_in_front_block_r, 1)
+        .unwrap();
+    arena
+        .add_weighted_edge(and_dust_2, torch_in_front_block_l, 2)
+        .unwrap();
+    arena
+        .add_weighted_edge(and_dust_2, torch_in_front_block_r, 2)
+        .unwrap();
+
+    arena
+        .link(inversion_of_and, dust_after_inversion_l)
+        .unwrap();
+    arena
+        .link(inversion_of_and, dust_after_inversion_r)
+        .unwrap();
+
+    arena
+        .add_weighted_edge(dust_after_inversion_l, inversion_of_and, 1)
+        .unwrap();
+    arena
+        .add_weighted_edge(dust_after_inversion_l2, inversion_of_and, 2)
+        .unwrap();
+    arena
+        .add_weighted_edge(dust_after_inversion_r, inversion_of_and, 1)
+        .unwrap();
+    arena
+        .add_weighted_edge(dust_after_inversion_r2, inversion_of_and, 2)
+        .unwrap();
 
     let output = arena.make_dust("output");
 
-    torch_after_dust_inversion_l.link(output);
-    torch_after_dust_inversion_r.link(output);
-
-    ruststone::add_weighted_edge(output, torch_after_dust_inversion_l, 1);
-    ruststone::add_weighted_edge(output, torch_after_dust_inversion_r, 1);
-
-    let rg = RedstoneGraph::collect(output);
-    rg.run();
-
-    assert!(input_r.redstate().is_on());
-    assert!(torch_on_top_block_l.redstate().is_on());
-    assert!(torch_on_top_block_r.redstate().is_off());
-    assert!(torch_in_front_block_l.redstate().is_on());
-    assert!(torch_in_front_block_r.redstate().is_off());
-    assert!(inversion_of_and.redstate().is_off());
-    assert!(torch_after_dust_inversion_l.redstate().is_off());
-    assert!(torch_after_dust_inversion_r.redstate().is_on());
-    assert!(output.redstate().is_on());
+    arena.link(torch_after_dust_inversion_l, output).unwrap();
+    arena.link(torch_after_dust_inversion_r, output).unwrap();
+
+    arena
+        .add_weighted_edge(output, torch_after_dust_inversion_l, 1)
+        .unwrap();
+    arena
+        .add_weighted_edge(output, torch_after_dust_inversion_r, 1)
+        .unwrap();
+
+    let rg = RedstoneGraph::collect(&arena, output);
+    rg.run(&arena);
+
+    assert!(arena[input_r].redstate().is_on());
+    assert!(arena[torch_on_top_block_l].redstate().is_on());
+    assert!(arena[torch_on_top_block_r].redstate().is_off());
+    assert!(arena[torch_in_front_block_l].redstate().is_on());
+    assert!(arena[torch_in_front_block_r].redstate().is_off());
+    assert!(arena[inversion_of_and].redstate().is_off());
+    assert!(arena[torch_after_dust_inversion_l].redstate().is_off());
+    assert!(arena[torch_after_dust_inversion_r].redstate().is_on());
+    assert!(arena[output].redstate().is_on());
 }
 
 #[test]
 fn xor_gate_with_right_off() {
-    let arena = RedstoneArena::new();
+    let mut arena = RedstoneArena::new();
 
     let input_l = arena.make_torch("input_l");
     let input_dust_l = arena.make_dust("input_dust_l");
@@ -538,18 +637,30 @@ fn xor_gate_with_right_off() {
     let block_after_inversion_l = arena.make_block("block_after_inversion_l");
     let torch_after_dust_inversion_l = arena.make_torch("torch_after_dust_inversion_l");
 
-    input_l.link(input_dust_l);
-    input_dust_l.link(dust_block_l);
-    dust_block_l.link(torch_on_top_block_l);
-    dust_block_l.link(torch_in_front_block_l);
-    torch_in_front_block_l.link(dust_after_inversion_l);
-    dust_after_inversion_l.link(dust_after_inversion_l2);
-    dust_after_inversion_l2.link(block_after_inversion_l);
-    block_after_inversion_l.link(torch_after_dust_inversion_l);
-
-    ruststone::add_weighted_edge(input_dust_l, input_l, 1);
-    ruststone::add_weighted_edge(dust_after_inversion_l, torch_in_front_block_l, 1);
-    ruststone::add_weighted_edge(dust_after_inversion_l2, torch_in_front_block_l, 2);
+    arena.link(input_l, input_dust_l).unwrap();
+    arena.link(input_dust_l, dust_block_l).unwrap();
+    arena.link(dust_block_l, torch_on_top_block_l).unwrap();
+    arena.link(dust_block_l, torch_in_front_block_l).unwrap();
+    arena
+        .link(torch_in_front_block_l, dust_after_inversion_l)
+        .unwrap();
+    arena
+        .link(dust_after_inversion_l, dust_after_inversion_l2)
+        .unwrap();
+    arena
+        .link(dust_after_inversion_l2, block_after_inversion_l)
+        .unwrap();
+    arena
+        .link(block_after_inversion_l, torch_after_dust_inversion_l)
+        .unwrap();
+
+    arena.add_weighted_edge(input_dust_l, input_l, 1).unwrap();
+    arena
+        .add_weighted_edge(dust_after_inversion_l, torch_in_front_block_l, 1)
+        .unwrap();
+    arena
+        .add_weighted_edge(dust_after_inversion_l2, torch_in_front_block_l, 2)
+        .unwrap();
 
     let input_dust_r = arena.make_dust("input_dust_r");
     let dust_block_r = arena.make_block("dust_block_r");
@@ -560,66 +671,102 @@ fn xor_gate_with_right_off() {
     let block_after_inversion_r = arena.make_block("block_after_inversion_r");
     let torch_after_dust_inversion_r = arena.make_torch("torch_after_dust_inversion_r");
 
-    input_dust_r.link(dust_block_r);
-    dust_block_r.link(torch_on_top_block_r);
-    dust_block_r.link(torch_in_front_block_r);
-    torch_in_front_block_r.link(dust_after_inversion_r);
-    dust_after_inversion_r.link(dust_after_inversion_r2);
-    dust_after_inversion_r2.link(block_after_inversion_r);
-    block_after_inversion_r.link(torch_after_dust_inversion_r);
-
-    ruststone::add_weighted_edge(dust_after_inversion_r, torch_in_front_block_r, 1);
-    ruststone::add_weighted_edge(dust_after_inversion_r2, torch_in_front_block_r, 2);
+    arena.link(input_dust_r, dust_block_r).unwrap();
+    arena.link(dust_block_r, torch_on_top_block_r).unwrap();
+    arena.link(dust_block_r, torch_in_front_block_r).unwrap();
+    arena
+        .link(torch_in_front_block_r, dust_after_inversion_r)
+        .unwrap();
+    arena
+        .link(dust_after_inversion_r, dust_after_inversion_r2)
+        .unwrap();
+    arena
+        .link(dust_after_inversion_r2, block_after_inversion_r)
+        .unwrap();
+    arena
+        .link(block_after_inversion_r, torch_after_dust_inversion_r)
+        .unwrap();
+
+    arena
+        .add_weighted_edge(dust_after_inversion_r, torch_in_front_block_r, 1)
+        .unwrap();
+    arena
+        .add_weighted_edge(dust_after_inversion_r2, torch_in_front_block_r, 2)
+        .unwrap();
 
     let and_dust_1 = arena.make_dust("and_dust_1");
     let and_dust_2 = arena.make_dust("and_dust_2");
     let and_block = arena.make_block("and_block");
     let inversion_of_and = arena.make_torch("inversion_of_and");
 
-    torch_on_top_block_l.link(and_dust_1);
-    torch_on_top_block_r.link(and_dust_1);
-    and_dust_1.link(and_dust_2);
-    and_dust_2.link(and_block);
-    and_block.link(inversion_of_and);
-
-    ruststone::add_weighted_edge(and_dust_1, torch_in_front_block_l, 1);
-    ruststone::add_weighted_edge(and_dust_1, torch_in_front_block_r, 1);
-    ruststone::add_weighted_edge(and_dust_2, torch_in_front_block_l, 2);
-    ruststone::add_weighted_edge(and_dust_2, torch_in_front_block_r, 2);
-
-    inversion_of_and.link(dust_after_inversion_l);
-    inversion_of_and.link(dust_after_inversion_r);
-
-    ruststone::add_weighted_edge(dust_after_inversion_l, inversion_of_and, 1);
-    ruststone::add_weighted_edge(dust_after_inversion_l2, inversion_of_and, 2);
-    ruststone::add_weighted_edge(dust_after_inversion_r, inversion_of_and, 1);
-    ruststone::add_weighted_edge(dust_after_inversion_r2, inversion_of_and, 2);
+    arena.link(torch_on_top_block_l, and_dust_1).unwrap();
+    arena.link(torch_on_top_block_r, and_dust_1).unwrap();
+    arena.link(and_dust_1, and_dust_2).unwrap();
+    arena.link(and_dust_2, and_block).unwrap();
+    arena.link(and_block, inversion_of_and).unwrap();
+
+    arena
+        .add_weighted_edge(and_dust_1, torch_in_front_block_l, 1)
+        .unwrap();
+    arena
+        .add_weighted_edge(and_dust_1, torch_in_front_block_r, 1)
+        .unwrap();
+    arena
+        .add_weighted_edge(and_dust_2, torch_in_front_block_l, 2)
+        .unwrap();
+    arena
+        .add_weighted_edge(and_dust_2, torch_in_front_block_r, 2)
+        .unwrap();
+
+    arena
+        .link(inversion_of_and, dust_after_inversion_l)
+        .unwrap();
+    arena
+        .link(inversion_of_and, dust_after_inversion_r)
+        .unwrap();
+
+    arena
+        .add_weighted_edge(dust_after_inversion_l, inversion_of_and, 1)
+        .unwrap();
+    arena
+        .add_weighted_edge(dust_after_inversion_l2, inversion_of_and, 2)
+        .unwrap();
+    arena
+        .add_weighted_edge(dust_after_inversion_r, inversion_of_and, 1)
+        .unwrap();
+    arena
+        .add_weighted_edge(dust_after_inversion_r2, inversion_of_and, 2)
+        .unwrap();
 
     let output = arena.make_dust("output");
 
-    torch_after_dust_inversion_l.link(output);
-    torch_after_dust_inversion_r.link(output);
-
-    ruststone::add_weighted_edge(output, torch_after_dust_inversion_l, 1);
-    ruststone::add_weighted_edge(output, torch_after_dust_inversion_r, 1);
-
-    let rg = RedstoneGraph::collect(output);
-    rg.run();
-
-    assert!(input_l.redstate().is_on());
-    assert!(torch_on_top_block_l.redstate().is_off());
-    assert!(torch_on_top_block_r.redstate().is_on());
-    assert!(torch_in_front_block_l.redstate().is_off());
-    assert!(torch_in_front_block_r.redstate().is_on());
-    assert!(inversion_of_and.redstate().is_off());
-    assert!(torch_after_dust_inversion_l.redstate().is_on());
-    assert!(torch_after_dust_inversion_r.redstate().is_off());
-    assert!(output.redstate().is_on());
+    arena.link(torch_after_dust_inversion_l, output).unwrap();
+    arena.link(torch_after_dust_inversion_r, output).unwrap();
+
+    arena
+        .add_weighted_edge(output, torch_after_dust_inversion_l, 1)
+        .unwrap();
+    arena
+        .add_weighted_edge(output, torch_after_dust_inversion_r, 1)
+        .unwrap();
+
+    let rg = RedstoneGraph::collect(&arena, output);
+    rg.run(&arena);
+
+    assert!(arena[input_l].redstate().is_on());
+    assert!(arena[torch_on_top_block_l].redstate().is_off());
+    assert!(arena[torch_on_top_block_r].redstate().is_on());
+    assert!(arena[torch_in_front_block_l].redstate().is_off());
+    assert!(arena[torch_in_front_block_r].redstate().is_on());
+    assert!(arena[inversion_of_and].redstate().is_off());
+    assert!(arena[torch_after_dust_inversion_l].redstate().is_on());
+    assert!(arena[torch_after_dust_inversion_r].redstate().is_off());
+    assert!(arena[output].redstate().is_on());
 }
 
 #[test]
 fn xor_gate_with_both_off() {
-    let arena = RedstoneArena::new();
+    let mut arena = RedstoneArena::new();
 
     let input_dust_l = arena.make_dust("input_dust_l");
     let dust_block_l = arena.make_block("dust_block_l");
@@ -630,16 +777,28 @@ fn xor_gate_with_both_off() {
     let block_after_inversion_l = arena.make_block("block_after_inversion_l");
     let torch_after_dust_inversion_l = arena.make_torch("torch_after_dust_inversion_l");
 
-    input_dust_l.link(dust_block_l);
-    dust_block_l.link(torch_on_top_block_l);
-    dust_block_l.link(torch_in_front_block_l);
-    torch_in_front_block_l.link(dust_after_inversion_l);
-    dust_after_inversion_l.link(dust_after_inversion_l2);
-    dust_after_inversion_l2.link(block_after_inversion_l);
-    block_after_inversion_l.link(torch_after_dust_inversion_l);
-
-    ruststone::add_weighted_edge(dust_after_inversion_l, torch_in_front_block_l, 1);
-    ruststone::add_weighted_edge(dust_after_inversion_l2, torch_in_front_block_l, 2);
+    arena.link(input_dust_l, dust_block_l).unwrap();
+    arena.link(dust_block_l, torch_on_top_block_l).unwrap();
+    arena.link(dust_block_l, torch_in_front_block_l).unwrap();
+    arena
+        .link(torch_in_front_block_l, dust_after_inversion_l)
+        .unwrap();
+    arena
+        .link(dust_after_inversion_l, dust_after_inversion_l2)
+        .unwrap();
+    arena
+        .link(dust_after_inversion_l2, block_after_inversion_l)
+        .unwrap();
+    arena
+        .link(block_after_inversion_l, torch_after_dust_inversion_l)
+        .unwrap();
+
+    arena
+        .add_weighted_edge(dust_after_inversion_l, torch_in_front_block_l, 1)
+        .unwrap();
+    arena
+        .add_weighted_edge(dust_after_inversion_l2, torch_in_front_block_l, 2)
+        .unwrap();
 
     let input_dust_r = arena.make_dust("input_dust_r");
     let dust_block_r = arena.make_block("dust_block_r");
@@ -650,65 +809,101 @@ fn xor_gate_with_both_off() {
     let block_after_inversion_r = arena.make_block("block_after_inversion_r");
     let torch_after_dust_inversion_r = arena.make_torch("torch_after_dust_inversion_r");
 
-    input_dust_r.link(dust_block_r);
-    dust_block_r.link(torch_on_top_block_r);
-    dust_block_r.link(torch_in_front_block_r);
-    torch_in_front_block_r.link(dust_after_inversion_r);
-    dust_after_inversion_r.link(dust_after_inversion_r2);
-    dust_after_inversion_r2.link(block_after_inversion_r);
-    block_after_inversion_r.link(torch_after_dust_inversion_r);
-
-    ruststone::add_weighted_edge(dust_after_inversion_r, torch_in_front_block_r, 1);
-    ruststone::add_weighted_edge(dust_after_inversion_r2, torch_in_front_block_r, 2);
+    arena.link(input_dust_r, dust_block_r).unwrap();
+    arena.link(dust_block_r, torch_on_top_block_r).unwrap();
+    arena.link(dust_block_r, torch_in_front_block_r).unwrap();
+    arena
+        .link(torch_in_front_block_r, dust_after_inversion_r)
+        .unwrap();
+    arena
+        .link(dust_after_inversion_r, dust_after_inversion_r2)
+        .unwrap();
+    arena
+        .link(dust_after_inversion_r2, block_after_inversion_r)
+        .unwrap();
+    arena
+        .link(block_after_inversion_r, torch_after_dust_inversion_r)
+        .unwrap();
+
+    arena
+        .add_weighted_edge(dust_after_inversion_r, torch_in_front_block_r, 1)
+        .unwrap();
+    arena
+        .add_weighted_edge(dust_after_inversion_r2, torch_in_front_block_r, 2)
+        .unwrap();
 
     let and_dust_1 = arena.make_dust("and_dust_1");
     let and_dust_2 = arena.make_dust("and_dust_2");
     let and_block = arena.make_block("and_block");
     let inversion_of_and = arena.make_torch("inversion_of_and");
 
-    torch_on_top_block_l.link(and_dust_1);
-    torch_on_top_block_r.link(and_dust_1);
-    and_dust_1.link(and_dust_2);
-    and_dust_2.link(and_block);
-    and_block.link(inversion_of_and);
-
-    ruststone::add_weighted_edge(and_dust_1, torch_in_front_block_l, 1);
-    ruststone::add_weighted_edge(and_dust_1, torch_in_front_block_r, 1);
-    ruststone::add_weighted_edge(and_dust_2, torch_in_front_block_l, 2);
-    ruststone::add_weighted_edge(and_dust_2, torch_in_front_block_r, 2);
-
-    inversion_of_and.link(dust_after_inversion_l);
-    inversion_of_and.link(dust_after_inversion_r);
-
-    ruststone::add_weighted_edge(dust_after_inversion_l, inversion_of_and, 1);
-    ruststone::add_weighted_edge(dust_after_inversion_l2, inversion_of_and, 2);
-    ruststone::add_weighted_edge(dust_after_inversion_r, inversion_of_and, 1);
-    ruststone::add_weighted_edge(dust_after_inversion_r2, inversion_of_and, 2);
+    arena.link(torch_on_top_block_l, and_dust_1).unwrap();
+    arena.link(torch_on_top_block_r, and_dust_1).unwrap();
+    arena.link(and_dust_1, and_dust_2).unwrap();
+    arena.link(and_dust_2, and_block).unwrap();
+    arena.link(and_block, inversion_of_and).unwrap();
+
+    arena
+        .add_weighted_edge(and_dust_1, torch_in_front_block_l, 1)
+        .unwrap();
+    arena
+        .add_weighted_edge(and_dust_1, torch_in_front_block_r, 1)
+        .unwrap();
+    arena
+        .add_weighted_edge(and_dust_2, torch_in_front_block_l, 2)
+        .unwrap();
+    arena
+        .add_weighted_edge(and_dust_2, torch_in_front_block_r, 2)
+        .unwrap();
+
+    arena
+        .link(inversion_of_and, dust_after_inversion_l)
+        .unwrap();
+    arena
+        .link(inversion_of_and, dust_after_inversion_r)
+        .unwrap();
+
+    arena
+        .add_weighted_edge(dust_after_inversion_l, inversion_of_and, 1)
+        .unwrap();
+    arena
+        .add_weighted_edge(dust_after_inversion_l2, inversion_of_and, 2)
+        .unwrap();
+    arena
+        .add_weighted_edge(dust_after_inversion_r, inversion_of_and, 1)
+        .unwrap();
+    arena
+        .add_weighted_edge(dust_after_inversion_r2, inversion_of_and, 2)
+        .unwrap();
 
     let output = arena.make_dust("output");
 
-    torch_after_dust_inversion_l.link(output);
-    torch_after_dust_inversion_r.link(output);
-
-    ruststone::add_weighted_edge(output, torch_after_dust_inversion_l, 1);
-    ruststone::add_weighted_edge(output, torch_after_dust_inversion_r, 1);
-
-    let rg = RedstoneGraph::collect(output);
-    rg.run();
-
-    assert!(torch_on_top_block_l.redstate().is_on());
-    assert!(torch_on_top_block_r.redstate().is_on());
-    assert!(torch_in_front_block_l.redstate().is_on());
-    assert!(torch_in_front_block_r.redstate().is_on());
-    assert!(inversion_of_and.redstate().is_off());
-    assert!(torch_after_dust_inversion_l.redstate().is_off());
-    assert!(torch_after_dust_inversion_r.redstate().is_off());
-    assert!(output.redstate().is_off());
+    arena.link(torch_after_dust_inversion_l, output).unwrap();
+    arena.link(torch_after_dust_inversion_r, output).unwrap();
+
+    arena
+        .add_weighted_edge(output, torch_after_dust_inversion_l, 1)
+        .unwrap();
+    arena
+        .add_weighted_edge(output, torch_after_dust_inversion_r, 1)
+        .unwrap();
+
+    let rg = RedstoneGraph::collect(&arena, output);
+    rg.run(&arena);
+
+    assert!(arena[torch_on_top_block_l].redstate().is_on());
+    assert!(arena[torch_on_top_block_r].redstate().is_on());
+    assert!(arena[torch_in_front_block_l].redstate().is_on());
+    assert!(arena[torch_in_front_block_r].redstate().is_on());
+    assert!(arena[inversion_of_and].redstate().is_off());
+    assert!(arena[torch_after_dust_inversion_l].redstate().is_off());
+    assert!(arena[torch_after_dust_inversion_r].redstate().is_off());
+    assert!(arena[output].redstate().is_off());
 }
 
 #[test]
 fn memory_cell() {
-    let arena = RedstoneArena::new();
+    let mut arena = RedstoneArena::new();
 
     let block_a = arena.make_block("block_a");
     let torch_a = arena.make_torch("torch_a");
@@ -720,32 +915,32 @@ fn memory_cell() {
     let dust_b1 = arena.make_dust("dust_b1");
     let dust_b2 = arena.make_dust("dust_b2");
 
-    block_a.link(torch_a);
-    torch_a.link(dust_a1);
-    dust_a1.link(dust_a2);
-    dust_a2.link(block_b);
+    arena.link(block_a, torch_a).unwrap();
+    arena.link(torch_a, dust_a1).unwrap();
+    arena.link(dust_a1, dust_a2).unwrap();
+    arena.link(dust_a2, block_b).unwrap();
 
-    ruststone::add_weighted_edge(dust_a1, torch_a, 1);
-    ruststone::add_weighted_edge(dust_a2, torch_a, 2);
+    arena.add_weighted_edge(dust_a1, torch_a, 1).unwrap();
+    arena.add_weighted_edge(dust_a2, torch_a, 2).unwrap();
 
-    block_b.link(torch_b);
-    torch_b.link(dust_b1);
-    dust_b1.link(dust_b2);
-    dust_b2.link(block_a);
+    arena.link(block_b, torch_b).unwrap();
+    arena.link(torch_b, dust_b1).unwrap();
+    arena.link(dust_b1, dust_b2).unwrap();
+    arena.link(dust_b2, block_a).unwrap();
 
-    ruststone::add_weighted_edge(dust_b1, torch_b, 1);
-    ruststone::add_weighted_edge(dust_b2, torch_b, 2);
+    arena.add_weighted_edge(dust_b1, torch_b, 1).unwrap();
+    arena.add_weighted_edge(dust_b2, torch_b, 2).unwrap();
 
-    let rg = RedstoneGraph::collect(block_a);
-    rg.run();
+    let rg = RedstoneGraph::collect(&arena, block_a);
+    rg.run(&arena);
 
-    assert!(torch_a.redstate().is_on());
-    assert!(torch_b.redstate().is_off());
+    assert!(arena[torch_a].redstate().is_on());
+    assert!(arena[torch_b].redstate().is_off());
 }
 
 #[test]
 fn memory_cell_alt() {
-    let arena = RedstoneArena::new();
+    let mut arena = RedstoneArena::new();
 
     let block_a = arena.make_block("block_a");
     let torch_a = arena.make_torch("torch_a");
@@ -757,230 +952,755 @@ fn memory_cell_alt() {
     let dust_b1 = arena.make_dust("dust_b1");
     let dust_b2 = arena.make_dust("dust_b2");
 
-    block_a.link(torch_a);
-    torch_a.link(dust_a1);
-    dust_a1.link(dust_a2);
-    dust_a2.link(block_b);
+    arena.link(block_a, torch_a).unwrap();
+    arena.link(torch_a, dust_a1).unwrap();
+    arena.link(dust_a1, dust_a2).unwrap();
+    arena.link(dust_a2, block_b).unwrap();
 
-    ruststone::add_weighted_edge(dust_a1, torch_a, 1);
-    ruststone::add_weighted_edge(dust_a2, torch_a, 2);
+    arena.add_weighted_edge(dust_a1, torch_a, 1).unwrap();
+    arena.add_weighted_edge(dust_a2, torch_a, 2).unwrap();
 
-    block_b.link(torch_b);
-    torch_b.link(dust_b1);
-    dust_b1.link(dust_b2);
-    dust_b2.link(block_a);
+    arena.link(block_b, torch_b).unwrap();
+    arena.link(torch_b, dust_b1).unwrap();
+    arena.link(dust_b1, dust_b2).unwrap();
+    arena.link(dust_b2, block_a).unwrap();
 
-    ruststone::add_weighted_edge(dust_b1, torch_b, 1);
-    ruststone::add_weighted_edge(dust_b2, torch_b, 2);
+    arena.add_weighted_edge(dust_b1, torch_b, 1).unwrap();
+    arena.add_weighted_edge(dust_b2, torch_b, 2).unwrap();
 
-    let rg = RedstoneGraph::collect(block_b);
-    rg.run();
+    let rg = RedstoneGraph::collect(&arena, block_b);
+    rg.run(&arena);
 
-    assert!(torch_a.redstate().is_off());
-    assert!(torch_b.redstate().is_on());
+    assert!(arena[torch_a].redstate().is_off());
+    assert!(arena[torch_b].redstate().is_on());
 }
 
 #[test]
 fn torch_and_dust_and_block_and_repeater() {
-    let arena = RedstoneArena::new();
+    let mut arena = RedstoneArena::new();
 
     let torch = arena.make_torch("torch");
     let dust = arena.make_dust("dust");
     let block = arena.make_block("block");
-    let repeater = arena.make_repeater("repeater", 1);
+    let repeater = arena.make_repeater("repeater", 1).unwrap();
 
-    torch.link(dust);
-    dust.link(block);
-    block.link(repeater);
+    arena.link(torch, dust).unwrap();
+    arena.link(dust, block).unwrap();
+    arena.link(block, repeater).unwrap();
 
-    ruststone::add_weighted_edge(dust, torch, 1);
+    arena.add_weighted_edge(dust, torch, 1).unwrap();
 
-    let rg = RedstoneGraph::collect(torch);
-    rg.run();
+    let rg = RedstoneGraph::collect(&arena, torch);
+    rg.run(&arena);
 
-    assert_eq!(torch.redstate().get_power(), 16);
-    assert_eq!(dust.redstate().get_power(), 15);
-    assert_eq!(block.redstate().get_power(), 0);
-    assert!(block.redstate().is_forced());
-    assert_eq!(repeater.redstate().get_power(), 16);
+    assert_eq!(arena[torch].redstate().get_power(), 16);
+    assert_eq!(arena[dust].redstate().get_power(), 15);
+    assert_eq!(arena[block].redstate().get_power(), 0);
+    assert!(arena[block].redstate().is_forced());
+    assert_eq!(arena[repeater].redstate().get_power(), 16);
 }
 
 #[test]
 fn torch_and_dust_and_block_and_repeater_and_block_and_dust() {
-    let arena = RedstoneArena::new();
+    let mut arena = RedstoneArena::new();
 
     let torch = arena.make_torch("torch");
     let dust1 = arena.make_dust("dust1");
     let block1 = arena.make_block("block1");
-    let repeater = arena.make_repeater("repeater", 1);
+    let repeater = arena.make_repeater("repeater", 1).unwrap();
     let block2 = arena.make_block("block2");
     let dust2 = arena.make_dust("dust2");
 
-    torch.link(dust1);
-    dust1.link(block1);
-    block1.link(repeater);
-    repeater.link(block2);
-    block2.link(dust2);
+    arena.link(torch, dust1).unwrap();
+    arena.link(dust1, block1).unwrap();
+    arena.link(block1, repeater).unwrap();
+    arena.link(repeater, block2).unwrap();
+    arena.link(block2, dust2).unwrap();
+
+    arena.add_weighted_edge(dust1, torch, 1).unwrap();
+    arena.add_weighted_edge(dust2, block2, 1).unwrap();
+
+    let rg = RedstoneGraph::collect(&arena, torch);
+    rg.run(&arena);
+
+    assert_eq!(arena[torch].redstate().get_power(), 16);
+    assert_eq!(arena[dust1].redstate().get_power(), 15);
+    assert_eq!(arena[block1].redstate().get_power(), 0);
+    assert!(arena[block1].redstate().is_forced());
+    assert_eq!(arena[repeater].redstate().get_power(), 16);
+    assert_eq!(arena[block2].redstate().get_power(), 16);
+    assert!(arena[block2].redstate().is_forced());
+    assert_eq!(arena[dust2].redstate().get_power(), 15);
+}
+
+#[test]
+fn torch_and_repeater_and_repeater_and_dust() {
+    let mut arena = RedstoneArena::new();
+
+    let torch = arena.make_torch("torch");
+    let repeater1 = arena.make_repeater("repeater1", 2).unwrap();
+    let repeater2 = arena.make_repeater("repeater2", 1).unwrap();
+    let dust = arena.make_dust("dust");
+
+    arena.link(torch, repeater1).unwrap();
+    arena.link(repeater1, repeater2).unwrap();
+    arena.link(repeater2, dust).unwrap();
 
-    ruststone::add_weighted_edge(dust1, torch, 1);
-    ruststone::add_weighted_edge(dust2, block2, 1);
+    arena.add_weighted_edge(dust, repeater2, 1).unwrap();
 
-    let rg = RedstoneGraph::collect(torch);
-    rg.run();
+    // A repeater is only ever powered from behind.
+    let other = arena.make_torch("other");
+    assert_eq!(
+        arena.link(other, repeater2),
+        Err(RuststoneError::AlreadyPowered {
+            redstone: repeater2,
+            vec3: None
+        })
+    );
 
-    assert_eq!(torch.redstate().get_power(), 16);
-    assert_eq!(dust1.redstate().get_power(), 15);
-    assert_eq!(block1.redstate().get_power(), 0);
-    assert!(block1.redstate().is_forced());
-    assert_eq!(repeater.redstate().get_power(), 16);
-    assert_eq!(block2.redstate().get_power(), 16);
-    assert!(block2.redstate().is_forced());
-    assert_eq!(dust2.redstate().get_power(), 15);
+    let rg = RedstoneGraph::collect(&arena, torch);
+    rg.run(&arena);
+
+    assert!(arena[repeater1].redstate().is_on());
+    assert!(arena[repeater2].redstate().is_on());
+    assert_eq!(arena[dust].redstate().get_power(), 15);
 }
 
 #[test]
 fn repeater_locked_by_its_neighbor() {
-    let arena = RedstoneArena::new();
+    let mut arena = RedstoneArena::new();
 
     let torch = arena.make_torch("torch");
     let dust1 = arena.make_dust("dust1");
     let dust2 = arena.make_dust("dust2");
     let dust3 = arena.make_dust("dust3");
     let dust4 = arena.make_dust("dust4");
-    let throughput = arena.make_repeater("throughput", 2);
-    let locker = arena.make_repeater("locker", 1);
+    let throughput = arena.make_repeater("throughput", 2).unwrap();
+    let locker = arena.make_repeater("locker", 1).unwrap();
     let output = arena.make_dust("output");
 
     // |
     // ^<+
     // +++
     //  *
-    torch.link(dust1);
-    dust1.link(dust2);
-    dust1.link(dust3);
-    dust3.link(dust4);
+    arena.link(torch, dust1).unwrap();
+    arena.link(dust1, dust2).unwrap();
+    arena.link(dust1, dust3).unwrap();
+    arena.link(dust3, dust4).unwrap();
 
-    dust2.link(throughput);
-    dust4.link(locker);
+    arena.link(dust2, throughput).unwrap();
+    arena.link(dust4, locker).unwrap();
 
-    throughput.link(output);
+    arena.link(throughput, output).unwrap();
 
-    ruststone::add_weighted_edge(dust1, torch, 1);
-    ruststone::add_weighted_edge(dust2, torch, 2);
-    ruststone::add_weighted_edge(dust3, torch, 2);
-    ruststone::add_weighted_edge(dust4, torch, 3);
+    arena.add_weighted_edge(dust1, torch, 1).unwrap();
+    arena.add_weighted_edge(dust2, torch, 2).unwrap();
+    arena.add_weighted_edge(dust3, torch, 2).unwrap();
+    arena.add_weighted_edge(dust4, torch, 3).unwrap();
 
-    ruststone::add_weighted_edge(output, throughput, 1);
+    arena.add_weighted_edge(output, throughput, 1).unwrap();
 
-    ruststone::lock(throughput, locker);
+    arena.lock(throughput, locker).unwrap();
 
-    let rg = RedstoneGraph::collect(output);
-    rg.run();
+    let rg = RedstoneGraph::collect(&arena, output);
+    rg.run(&arena);
 
-    assert!(torch.redstate().is_on());
-    assert_eq!(dust1.redstate().get_power(), 15);
-    assert_eq!(dust2.redstate().get_power(), 14);
-    assert_eq!(dust3.redstate().get_power(), 14);
-    assert_eq!(dust4.redstate().get_power(), 13);
-    assert!(locker.redstate().is_on());
-    assert!(throughput.redstate().is_off());
-    assert_eq!(output.redstate().get_power(), 0);
+    assert!(arena[torch].redstate().is_on());
+    assert_eq!(arena[dust1].redstate().get_power(), 15);
+    assert_eq!(arena[dust2].redstate().get_power(), 14);
+    assert_eq!(arena[dust3].redstate().get_power(), 14);
+    assert_eq!(arena[dust4].redstate().get_power(), 13);
+    assert!(arena[locker].redstate().is_on());
+    assert!(arena[throughput].redstate().is_off());
+    assert_eq!(arena[output].redstate().get_power(), 0);
 }
 
 #[test]
 fn repeater_locked_by_its_slower_neighbor() {
-    let arena = RedstoneArena::new();
+    let mut arena = RedstoneArena::new();
 
     let torch = arena.make_torch("torch");
     let dust1 = arena.make_dust("dust1");
     let dust2 = arena.make_dust("dust2");
     let dust3 = arena.make_dust("dust3");
     let dust4 = arena.make_dust("dust4");
-    let throughput = arena.make_repeater("throughput", 1);
-    let locker = arena.make_repeater("locker", 2);
+    let throughput = arena.make_repeater("throughput", 1).unwrap();
+    let locker = arena.make_repeater("locker", 2).unwrap();
     let output = arena.make_dust("output");
 
     // |
     // ^<+
     // +++
     //  *
-    torch.link(dust1);
-    dust1.link(dust2);
-    dust1.link(dust3);
-    dust3.link(dust4);
+    arena.link(torch, dust1).unwrap();
+    arena.link(dust1, dust2).unwrap();
+    arena.link(dust1, dust3).unwrap();
+    arena.link(dust3, dust4).unwrap();
 
-    dust2.link(throughput);
-    dust4.link(locker);
+    arena.link(dust2, throughput).unwrap();
+    arena.link(dust4, locker).unwrap();
 
-    throughput.link(output);
+    arena.link(throughput, output).unwrap();
 
-    ruststone::add_weighted_edge(dust1, torch, 1);
-    ruststone::add_weighted_edge(dust2, torch, 2);
-    ruststone::add_weighted_edge(dust3, torch, 2);
-    ruststone::add_weighted_edge(dust4, torch, 3);
+    arena.add_weighted_edge(dust1, torch, 1).unwrap();
+    arena.add_weighted_edge(dust2, torch, 2).unwrap();
+    arena.add_weighted_edge(dust3, torch, 2).unwrap();
+    arena.add_weighted_edge(dust4, torch, 3).unwrap();
 
-    ruststone::add_weighted_edge(output, throughput, 1);
+    arena.add_weighted_edge(output, throughput, 1).unwrap();
 
-    ruststone::lock(throughput, locker);
+    arena.lock(throughput, locker).unwrap();
 
-    let rg = RedstoneGraph::collect(output);
-    rg.run();
+    let rg = RedstoneGraph::collect(&arena, output);
+    rg.run(&arena);
 
-    assert!(torch.redstate().is_on());
-    assert_eq!(dust1.redstate().get_power(), 15);
-    assert_eq!(dust2.redstate().get_power(), 14);
-    assert_eq!(dust3.redstate().get_power(), 14);
-    assert_eq!(dust4.redstate().get_power(), 13);
-    assert!(locker.redstate().is_on());
-    assert!(throughput.redstate().is_on());
-    assert_eq!(output.redstate().get_power(), 15);
+    assert!(arena[torch].redstate().is_on());
+    assert_eq!(arena[dust1].redstate().get_power(), 15);
+    assert_eq!(arena[dust2].redstate().get_power(), 14);
+    assert_eq!(arena[dust3].redstate().get_power(), 14);
+    assert_eq!(arena[dust4].redstate().get_power(), 13);
+    assert!(arena[locker].redstate().is_on());
+    assert!(arena[throughput].redstate().is_on());
+    assert_eq!(arena[output].redstate().get_power(), 15);
 }
 
 #[test]
 fn repeater_locked_simultaneously_by_its_neighbors() {
-    let arena = RedstoneArena::new();
+    let mut arena = RedstoneArena::new();
 
     let torch = arena.make_torch("torch");
     let dust1 = arena.make_dust("dust1");
     let dust2 = arena.make_dust("dust2");
     let dust3 = arena.make_dust("dust3");
     let dust4 = arena.make_dust("dust4");
-    let throughput = arena.make_repeater("throughput", 1);
-    let locker = arena.make_repeater("locker", 1);
+    let throughput = arena.make_repeater("throughput", 1).unwrap();
+    let locker = arena.make_repeater("locker", 1).unwrap();
     let output = arena.make_dust("output");
 
     // |
     // ^<+
     // +++
     //  *
-    torch.link(dust1);
-    dust1.link(dust2);
-    dust1.link(dust3);
-    dust3.link(dust4);
+    arena.link(torch, dust1).unwrap();
+    arena.link(dust1, dust2).unwrap();
+    arena.link(dust1, dust3).unwrap();
+    arena.link(dust3, dust4).unwrap();
+
+    arena.link(dust2, throughput).unwrap();
+    arena.link(dust4, locker).unwrap();
+
+    arena.link(throughput, output).unwrap();
+
+    arena.add_weighted_edge(dust1, torch, 1).unwrap();
+    arena.add_weighted_edge(dust2, torch, 2).unwrap();
+    arena.add_weighted_edge(dust3, torch, 2).unwrap();
+    arena.add_weighted_edge(dust4, torch, 3).unwrap();
+
+    arena.add_weighted_edge(output, throughput, 1).unwrap();
+
+    arena.lock(throughput, locker).unwrap();
+
+    let rg = RedstoneGraph::collect(&arena, output);
+    rg.run(&arena);
+
+    assert!(arena[torch].redstate().is_on());
+    assert_eq!(arena[dust1].redstate().get_power(), 15);
+    assert_eq!(arena[dust2].redstate().get_power(), 14);
+    assert_eq!(arena[dust3].redstate().get_power(), 14);
+    assert_eq!(arena[dust4].redstate().get_power(), 13);
+    assert!(arena[locker].redstate().is_on());
+    assert!(arena[throughput].redstate().is_off());
+    assert_eq!(arena[output].redstate().get_power(), 0);
+}
+
+#[test]
+fn torch_lights_up_copper_bulb() {
+    let mut arena = RedstoneArena::new();
+
+    let torch = arena.make_torch("torch");
+    let bulb = arena.make_copper_bulb("bulb");
+
+    arena.link(torch, bulb).unwrap();
+
+    let rg = RedstoneGraph::collect(&arena, torch);
+    rg.run(&arena);
+
+    assert!(arena[torch].redstate().is_on());
+    assert!(arena[bulb].redstate().is_on());
+    assert_eq!(arena[bulb].comparator_output(), Some(15));
+}
+
+#[test]
+fn copper_bulb_stays_lit_once_the_torch_lighting_it_turns_off() {
+    let mut arena = RedstoneArena::new();
+
+    let torch_a = arena.make_torch("torch_a");
+    let torch_b = arena.make_torch("torch_b");
+    let bulb = arena.make_copper_bulb("bulb");
+
+    arena.link(torch_a, torch_b).unwrap();
+    arena.link(torch_b, bulb).unwrap();
+
+    let rg = RedstoneGraph::collect(&arena, torch_b);
+    rg.run(&arena);
+
+    assert!(arena[torch_a].redstate().is_on());
+    assert!(arena[torch_b].redstate().is_off());
+    assert!(arena[bulb].redstate().is_on());
+    assert_eq!(arena[bulb].comparator_output(), Some(15));
+}
+
+#[test]
+fn unpowered_copper_bulb_is_unlit() {
+    let mut arena = RedstoneArena::new();
+
+    let torch_a = arena.make_torch("torch_a");
+    let torch_b = arena.make_torch("torch_b");
+    let bulb = arena.make_copper_bulb("bulb");
+
+    arena.link(torch_a, torch_b).unwrap();
+    arena.link(torch_b, bulb).unwrap();
+
+    let rg = RedstoneGraph::collect(&arena, torch_a);
+    rg.run(&arena);
+
+    assert!(arena[torch_a].redstate().is_on());
+    assert!(arena[torch_b].redstate().is_off());
+    assert!(arena[bulb].redstate().is_off());
+    assert_eq!(arena[bulb].comparator_output(), Some(0));
+}
+
+#[test]
+fn crafter_fires_on_rising_edge() {
+    let mut arena = RedstoneArena::new();
+
+    let torch = arena.make_torch("torch");
+    let crafter = arena.make_crafter("crafter");
+
+    arena.link(torch, crafter).unwrap();
+
+    let RedstoneNode::Crafter(node) = arena[crafter].node() else {
+        unreachable!();
+    };
+
+    node.set_slot(0, CrafterSlot::Filled(2)).unwrap();
+    node.set_slot(1, CrafterSlot::Disabled).unwrap();
+    node.set_slot(2, CrafterSlot::Filled(1)).unwrap();
+
+    assert_eq!(arena[crafter].comparator_output(), Some(3));
+
+    let rg = RedstoneGraph::collect(&arena, torch);
+    rg.run(&arena);
+
+    assert!(arena[crafter].redstate().is_on());
+    assert_eq!(node.crafted(), 1);
+    assert_eq!(node.slots()[0], CrafterSlot::Filled(1));
+    assert_eq!(node.slots()[2], CrafterSlot::Empty);
+    assert_eq!(arena[crafter].comparator_output(), Some(2));
+}
+
+#[test]
+fn empty_crafter_crafts_nothing() {
+    let mut arena = RedstoneArena::new();
+
+    let torch = arena.make_torch("torch");
+    let crafter = arena.make_crafter("crafter");
+
+    arena.link(torch, crafter).unwrap();
+
+    let rg = RedstoneGraph::collect(&arena, torch);
+    rg.run(&arena);
+
+    let RedstoneNode::Crafter(node) = arena[crafter].node() else {
+        unreachable!();
+    };
+
+    assert!(arena[crafter].redstate().is_on());
+    assert_eq!(node.crafted(), 0);
+    assert_eq!(arena[crafter].comparator_output(), Some(0));
+}
 
-    dust2.link(throughput);
-    dust4.link(locker);
+#[test]
+fn torch_plays_the_note_block() {
+    let mut arena = RedstoneArena::new();
+
+    let torch = arena.make_torch("torch");
+    let note_block = arena
+        .make_note_block("note_block", Instrument::Bell, 12)
+        .unwrap();
+
+    arena.link(torch, note_block).unwrap();
+
+    let rg = RedstoneGraph::collect(&arena, torch);
+    rg.run(&arena);
+
+    let RedstoneNode::NoteBlock(node) = arena[note_block].node() else {
+        unreachable!();
+    };
+
+    assert!(arena[note_block].redstate().is_on());
+    assert_eq!(node.instrument(), Instrument::Bell);
+    assert_eq!(node.pitch(), 12);
+    assert_eq!(node.played(), vec![2]);
+}
+
+#[test]
+fn torch_extends_the_piston() {
+    let mut arena = RedstoneArena::new();
+
+    let torch = arena.make_torch("torch");
+    let piston = arena.make_mechanism("piston", MechanismKind::Piston);
+
+    arena.link(torch, piston).unwrap();
+
+    let rg = RedstoneGraph::collect(&arena, piston);
+    rg.run(&arena);
+
+    let RedstoneNode::Mechanism(node) = arena[piston].node() else {
+        unreachable!();
+    };
+
+    assert!(node.is_directly_powered());
+    assert!(node.is_active());
+    assert!(!node.is_budded());
+    assert_eq!(node.fired(), 0);
+}
+
+#[test]
+fn quasi_powered_piston_waits_for_a_block_update() {
+    let mut arena = RedstoneArena::new();
+
+    let torch = arena.make_torch("torch");
+    let piston = arena.make_mechanism("piston", MechanismKind::Piston);
+
+    arena.quasi_link(torch, piston).unwrap();
+
+    let rg = RedstoneGraph::collect(&arena, piston);
+    rg.run(&arena);
+
+    let RedstoneNode::Mechanism(node) = arena[piston].node() else {
+        unreachable!();
+    };
+
+    assert!(arena[torch].redstate().is_on());
+    assert!(!node.is_directly_powered());
+    assert!(node.is_quasi_powered());
+    assert!(!node.is_active());
+    assert!(node.is_budded());
+
+    RedstoneGraph::block_update(&arena, piston).run(&arena);
+
+    assert!(node.is_active());
+    assert!(!node.is_budded());
+}
+
+#[test]
+fn dropper_fires_once_per_rising_edge() {
+    let mut arena = RedstoneArena::new();
+
+    let torch = arena.make_torch("torch");
+    let dropper = arena.make_mechanism("dropper", MechanismKind::Dropper);
+
+    arena.link(torch, dropper).unwrap();
+
+    let rg = RedstoneGraph::collect(&arena, dropper);
+    rg.run(&arena);
+
+    RedstoneGraph::block_update(&arena, dropper).run(&arena);
+
+    let RedstoneNode::Mechanism(node) = arena[dropper].node() else {
+        unreachable!();
+    };
+
+    assert!(node.is_active());
+    assert_eq!(node.fired(), 1);
+}
+
+#[test]
+fn settled_graph_is_stable() {
+    let mut arena = RedstoneArena::new();
+
+    let torch = arena.make_torch("torch");
+    let dust = arena.make_dust("dust");
+
+    arena.link(torch, dust).unwrap();
+    arena.add_weighted_edge(dust, torch, 1).unwrap();
+
+    let rg = RedstoneGraph::collect(&arena, torch);
+    assert_eq!(rg.run(&arena), RunOutcome::Stable);
+}
+
+#[test]
+fn torch_clock_oscillates() {
+    let mut arena = RedstoneArena::new();
+
+    // The torch turns off the block it powers, which turns the torch back on.
+    let torch = arena.make_torch("torch");
+    let block = arena.make_block("block");
 
-    throughput.link(output);
+    arena.link(torch, block).unwrap();
+    arena.link(block, torch).unwrap();
 
-    ruststone::add_weighted_edge(dust1, torch, 1);
-    ruststone::add_weighted_edge(dust2, torch, 2);
-    ruststone::add_weighted_edge(dust3, torch, 2);
-    ruststone::add_weighted_edge(dust4, torch, 3);
+    let rg = RedstoneGraph::collect(&arena, torch);
+    let outcome = rg.run(&arena);
 
-    ruststone::add_weighted_edge(output, throughput, 1);
+    assert_eq!(
+        outcome,
+        RunOutcome::Oscillating {
+            period: 4,
+            phase: 4
+        }
+    );
+}
 
-    ruststone::lock(throughput, locker);
+#[test]
+fn torch_clock_runs_out_of_dispatches() {
+    let mut arena = RedstoneArena::new();
+
+    let torch = arena.make_torch("torch");
+    let block = arena.make_block("block");
 
-    let rg = RedstoneGraph::collect(output);
-    rg.run();
+    arena.link(torch, block).unwrap();
+    arena.link(block, torch).unwrap();
+
+    let rg = RedstoneGraph::collect(&arena, torch);
+    let outcome = rg.run_with_budget(&arena, Budget::new().dispatches(3));
+
+    assert_eq!(outcome, RunOutcome::BudgetExhausted);
+}
+
+fn slow_repeater(arena: &mut RedstoneArena) -> (RedstoneId, RedstoneId) {
+    let torch = arena.make_torch("torch");
+    let dust = arena.make_dust("dust");
+    let block = arena.make_block("block");
+    let repeater = arena.make_repeater("repeater", 4).unwrap();
+
+    arena.link(torch, dust).unwrap();
+    arena.link(dust, block).unwrap();
+    arena.link(block, repeater).unwrap();
+
+    arena.add_weighted_edge(dust, torch, 1).unwrap();
+
+    (torch, repeater)
+}
+
+#[test]
+fn slow_repeater_runs_out_of_ticks() {
+    let mut arena = RedstoneArena::new();
+    let (torch, repeater) = slow_repeater(&mut arena);
+
+    // The torch lights up at tick 2, and the repeater only follows 8 ticks later.
+    let rg = RedstoneGraph::collect(&arena, torch);
+    assert_eq!(
+        rg.run_with_budget(&arena, Budget::new().ticks(8)),
+        RunOutcome::BudgetExhausted
+    );
+    assert!(arena[repeater].redstate().is_off());
+
+    let mut arena = RedstoneArena::new();
+    let (torch, repeater) = slow_repeater(&mut arena);
+
+    let rg = RedstoneGraph::collect(&arena, torch);
+    assert_eq!(
+        rg.run_with_budget(&arena, Budget::new().ticks(10)),
+        RunOutcome::Stable
+    );
+    assert!(arena[repeater].redstate().is_on());
+}
+
+#[test]
+fn trace_torch_and_dust() {
+    let mut arena = RedstoneArena::new();
+
+    let torch = arena.make_torch("torch");
+    let dust = arena.make_dust("dust");
+
+    arena.link(torch, dust).unwrap();
+    arena.add_weighted_edge(dust, torch, 1).unwrap();
+
+    let mut events = Vec::new();
+    let rg = RedstoneGraph::collect(&arena, torch);
+    rg.run_traced(&arena, Budget::new(), &mut events);
+
+    assert_eq!(
+        events,
+        vec![
+            TraceEvent::Deferred {
+                tick: 0,
+                redstone: torch,
+                vec3: None,
+                until: 2,
+            },
+            TraceEvent::FrameAdvanced { from: 0, to: 2 },
+            TraceEvent::Dispatched {
+                tick: 2,
+                redstone: torch,
+                vec3: None,
+                cause: None,
+            },
+            TraceEvent::PowerChanged {
+                tick: 2,
+                redstone: torch,
+                vec3: None,
+                cause: None,
+                old_power: 0,
+                new_power: 16,
+                was_on: false,
+                is_on: true,
+            },
+            TraceEvent::Dispatched {
+                tick: 2,
+                redstone: dust,
+                vec3: None,
+                cause: Some(torch),
+            },
+            TraceEvent::PowerChanged {
+                tick: 2,
+                redstone: dust,
+                vec3: None,
+                cause: Some(torch),
+                old_power: 0,
+                new_power: 15,
+                was_on: false,
+                is_on: true,
+            },
+        ]
+    );
+}
+
+#[test]
+fn links_that_do_not_fit_are_refused() {
+    let mut arena = RedstoneArena::new();
+
+    let torch1 = arena.make_torch("torch1");
+    let torch2 = arena.make_torch("torch2");
+    let target = arena.make_torch("target");
+    let note_block = arena
+        .make_note_block("note_block", Instrument::Harp, 0)
+        .unwrap();
+    let dust = arena.make_dust("dust");
+
+    arena.link(torch1, target).unwrap();
+    assert_eq!(
+        arena.link(torch2, target),
+        Err(RuststoneError::AlreadyPowered {
+            redstone: target,
+            vec3: None
+        })
+    );
+    assert_eq!(
+        arena.link(note_block, dust),
+        Err(RuststoneError::NoOutgoingEdges {
+            redstone: note_block,
+            vec3: None
+        })
+    );
+    assert_eq!(
+        arena.quasi_link(torch2, dust),
+        Err(RuststoneError::WrongKind {
+            redstone: dust,
+            vec3: None,
+            expected: "mechanism"
+        })
+    );
+    assert_eq!(
+        arena.add_weighted_edge(dust, dust, 0),
+        Err(RuststoneError::DustAsSource {
+            redstone: dust,
+            vec3: None
+        })
+    );
+    assert!(arena.lock(torch1, target).is_err());
+
+    // Nothing was linked by the links that were refused.
+    assert_eq!(arena.iter(torch1).collect::<Vec<_>>(), [torch1, target]);
+    assert_eq!(arena.iter(torch2).collect::<Vec<_>>(), [torch2]);
+}
+
+#[test]
+fn too_many_edges_are_refused() {
+    let mut arena = RedstoneArena::new();
+
+    let torch = arena.make_torch("torch");
+    let dusts: Vec<RedstoneId> = (0..7)
+        .map(|idx| arena.make_dust(&format!("dust{idx}")))
+        .collect();
+
+    for &dust in &dusts[..6] {
+        arena.link(torch, dust).unwrap();
+    }
+
+    assert_eq!(
+        arena.link(torch, dusts[6]),
+        Err(RuststoneError::TooManyEdges {
+            redstone: torch,
+            vec3: None
+        })
+    );
+}
+
+#[test]
+fn settings_out_of_range_are_refused() {
+    let mut arena = RedstoneArena::new();
+
+    assert_eq!(
+        arena.make_repeater("repeater", 0),
+        Err(RuststoneError::InvalidDelay { delay: 0 })
+    );
+    assert_eq!(
+        arena.make_note_block("note_block", Instrument::Harp, 25),
+        Err(RuststoneError::InvalidPitch { pitch: 25 })
+    );
+
+    let crafter = arena.make_crafter("crafter");
+    let RedstoneNode::Crafter(node) = arena[crafter].node() else {
+        unreachable!();
+    };
+    assert_eq!(
+        node.set_slot(9, CrafterSlot::Disabled),
+        Err(RuststoneError::NoSuchSlot { idx: 9 })
+    );
+    assert_eq!(node.slots(), [CrafterSlot::Empty; 9]);
+}
+
+#[test]
+fn repeaters_are_locked_by_at_most_two_sides() {
+    let mut arena = RedstoneArena::new();
+
+    let repeater = arena.make_repeater("repeater", 1).unwrap();
+    let lockers: Vec<RedstoneId> = (0..3)
+        .map(|idx| arena.make_repeater(&format!("locker{idx}"), 1).unwrap())
+        .collect();
+
+    arena.lock(repeater, lockers[0]).unwrap();
+    arena.lock(repeater, lockers[1]).unwrap();
+
+    assert_eq!(
+        arena.lock(repeater, lockers[2]),
+        Err(RuststoneError::TooManyEdges {
+            redstone: repeater,
+            vec3: None
+        })
+    );
+}
+
+#[test]
+fn repeaters_facing_nothing_are_reported() {
+    let mut arena = RedstoneArena::new();
+
+    let torch = arena.make_torch("torch");
+    let repeater = arena.make_repeater("repeater", 1).unwrap();
+    let dust = arena.make_dust("dust");
 
-    assert!(torch.redstate().is_on());
-    assert_eq!(dust1.redstate().get_power(), 15);
-    assert_eq!(dust2.redstate().get_power(), 14);
-    assert_eq!(dust3.redstate().get_power(), 14);
-    assert_eq!(dust4.redstate().get_power(), 13);
-    assert!(locker.redstate().is_on());
-    assert!(throughput.redstate().is_off());
-    assert_eq!(output.redstate().get_power(), 0);
+    arena.link(torch, repeater).unwrap();
+    assert_eq!(
+        arena.validate(),
+        [RuststoneError::RepeaterFacingNothing {
+            redstone: repeater,
+            vec3: None
+        }]
+    );
+
+    arena.link(repeater, dust).unwrap();
+    assert!(arena.validate().is_empty());
 }
@@ -0,0 +1,106 @@
+use ruststone::{
+    stimulus::{Action, Script, Stimulus},
+    text::parse_world,
+    trace::TraceEvent,
+    vec3::Vec3,
+    world::World,
+    RunOutcome,
+};
+
+fn torch_and_dust() -> World {
+    parse_world(
+        "0 0 0 stone
+        0 1 0 torch
+        1 0 0 stone
+        1 1 0 dust",
+    )
+    .unwrap()
+}
+
+fn is_on(world: &World, vec3: Vec3) -> bool {
+    world.get(vec3).unwrap().redstate().is_on()
+}
+
+#[test]
+fn toggling_a_torch_breaks_it_and_puts_it_back() {
+    let mut world = torch_and_dust();
+    let mut script = Script::parse("10 toggle 0 1 0").unwrap();
+
+    assert_eq!(
+        script.run(&mut world, Some(20), &mut ()),
+        RunOutcome::Stable
+    );
+    assert_eq!(world.tick(), 20);
+    assert!(world[Vec3(0, 1, 0)].is_air());
+    assert!(!is_on(&world, Vec3(1, 1, 0)));
+
+    script.apply(&mut world, &Action::Toggle(Vec3(0, 1, 0)));
+    world.run();
+
+    assert!(world[Vec3(0, 1, 0)].is_torch());
+    assert!(is_on(&world, Vec3(1, 1, 0)));
+}
+
+#[test]
+fn stimuli_happen_at_their_tick() {
+    let mut world = torch_and_dust();
+    let mut script = Script::parse(
+        "# out of order on purpose
+        30 place 0 1 0 torch
+        10 break 0 1 0",
+    )
+    .unwrap();
+
+    let mut events = Vec::new();
+    script.run(&mut world, None, &mut events);
+
+    let changes: Vec<(u64, bool)> = events
+        .iter()
+        .filter_map(|event| match *event {
+            TraceEvent::PowerChanged {
+                vec3: Some(Vec3(1, 1, 0)),
+                tick,
+                is_on,
+                ..
+            } => Some((tick, is_on)),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(changes, [(2, true), (10, false), (32, true)]);
+}
+
+#[test]
+fn stimuli_after_the_end_are_left_out() {
+    let mut world = torch_and_dust();
+    let mut script = Script::new(vec![Stimulus {
+        tick: 50,
+        action: Action::Break(Vec3(0, 1, 0)),
+    }]);
+
+    script.run(&mut world, Some(20), &mut ());
+
+    assert!(is_on(&world, Vec3(1, 1, 0)));
+}
+
+#[test]
+fn rejects_what_it_cannot_read() {
+    assert_eq!(
+        Script::parse("ten toggle 0 1 0").err().map(|e| e.line),
+        Some(1)
+    );
+    assert_eq!(
+        Script::parse("\n10 flip 0 1 0").err().map(|e| e.line),
+        Some(2)
+    );
+    assert_eq!(
+        Script::parse("10 place 0 1 0").err().map(|e| e.line),
+        Some(1)
+    );
+    assert_eq!(
+        Script::parse("10 toggle 0 1 0\n3 toggle 0 1 0")
+            .err()
+            .map(|e| e.line),
+        Some(2)
+    );
+}
@@ -0,0 +1,162 @@
+use ruststone::{
+    structure::Structure,
+    text::format_world,
+    vec3::{Axis, Direction, Region, Rotation, Vec3},
+    voxels::Voxel,
+    world::World,
+};
+
+/// A stone with a torch hanging on its east side and a piston to the south of it, all standing
+/// on a floor three wide.
+fn module() -> World {
+    let mut world = World::new();
+
+    for x in 0..3 {
+        world[Vec3(x, 0, 0)] = Voxel::stone().voxel();
+    }
+    world[Vec3(1, 1, 0)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 0)] = Voxel::torch().facing_west().voxel();
+    world[Vec3(1, 1, 1)] = Voxel::piston().facing(Direction::South).voxel();
+
+    world
+}
+
+fn pasted(structure: &Structure) -> String {
+    let mut world = World::new();
+    world.paste(structure, Vec3(0, 0, 0));
+
+    format_world(&world)
+}
+
+#[test]
+fn pasting_a_copy_translates_it() {
+    let mut world = module();
+    let structure = world.copy(Region::new(Vec3(2, 1, 1), Vec3(0, 0, 0)));
+
+    assert_eq!(structure.size(), Vec3(3, 2, 2));
+
+    world.paste(&structure, Vec3(10, 5, -3));
+
+    assert!(world[Vec3(10, 6, -3)].is_torch());
+    assert!(world[Vec3(11, 6, -2)].is_piston());
+    assert!(world.validate().is_empty());
+
+    world.run();
+
+    assert!(world.get(Vec3(0, 1, 0)).unwrap().redstate().is_on());
+    assert!(world.get(Vec3(10, 6, -3)).unwrap().redstate().is_on());
+}
+
+#[test]
+fn rotating_turns_positions_and_facings_alike() {
+    let structure = module()
+        .copy(Region::new(Vec3(0, 0, 0), Vec3(2, 1, 1)))
+        .rotate_y(Rotation::Clockwise);
+    assert_eq!(structure.size(), Vec3(2, 2, 3));
+
+    let mut world = World::new();
+    world.paste(&structure, Vec3(0, 0, 0));
+
+    // The torch was on the west side of the floor, which is now its north end.
+    assert!(world[Vec3(0, 1, 2)].is_torch());
+    assert_eq!(world[Vec3(0, 1, 2)].facing(), Some(Direction::North));
+    assert_eq!(world[Vec3(1, 1, 1)].facing(), Some(Direction::West));
+    assert!(world.validate().is_empty());
+}
+
+#[test]
+fn turning_all_the_way_around_changes_nothing() {
+    let structure = module().copy(Region::new(Vec3(0, 0, 0), Vec3(2, 1, 1)));
+
+    let mut turned = structure.clone();
+    for _ in 0..4 {
+        turned = turned.rotate_y(Rotation::Clockwise);
+    }
+    assert_eq!(pasted(&turned), pasted(&structure));
+
+    let half = structure.clone().rotate_y(Rotation::Clockwise);
+    let half = half.rotate_y(Rotation::Clockwise);
+    let other_half = structure
+        .clone()
+        .rotate_y(Rotation::Counterclockwise)
+        .rotate_y(Rotation::Counterclockwise);
+    assert_eq!(pasted(&half), pasted(&other_half));
+    assert_ne!(pasted(&half), pasted(&structure));
+}
+
+#[test]
+fn mirroring_flips_positions_and_facings_alike() {
+    let structure = module().copy(Region::new(Vec3(0, 0, 0), Vec3(2, 1, 1)));
+
+    let mut world = World::new();
+    world.paste(&structure.clone().mirror(Axis::X), Vec3(0, 0, 0));
+
+    assert_eq!(world[Vec3(2, 1, 0)].facing(), Some(Direction::East));
+    assert_eq!(world[Vec3(1, 1, 1)].facing(), Some(Direction::South));
+    assert!(world.validate().is_empty());
+
+    let mut world = World::new();
+    world.paste(&structure.clone().mirror(Axis::Z), Vec3(0, 0, 0));
+
+    assert_eq!(world[Vec3(0, 1, 1)].facing(), Some(Direction::West));
+    assert_eq!(world[Vec3(1, 1, 0)].facing(), Some(Direction::North));
+
+    let twice = structure.clone().mirror(Axis::Z).mirror(Axis::Z);
+    assert_eq!(pasted(&twice), pasted(&structure));
+}
+
+#[test]
+fn repeaters_turn_and_flip_with_the_structure() {
+    let mut world = World::new();
+    world[Vec3(0, 0, 0)] = Voxel::repeater()
+        .facing(Direction::West)
+        .unwrap()
+        .delay(3)
+        .unwrap()
+        .voxel();
+    let structure = world.copy(Region::new(Vec3(0, 0, 0), Vec3(0, 0, 0)));
+
+    let facing = |structure: &Structure| structure.voxels().next().unwrap().1.facing();
+
+    let turned = structure.clone().rotate_y(Rotation::Clockwise);
+    assert_eq!(
+        facing(&turned),
+        Some(Direction::West.rotate_y(Rotation::Clockwise))
+    );
+    assert_eq!(
+        facing(&structure.clone().mirror(Axis::X)),
+        Some(Direction::East)
+    );
+    assert_eq!(
+        facing(&structure.clone().mirror(Axis::Z)),
+        Some(Direction::West)
+    );
+    assert_eq!(
+        pasted(&turned),
+        format!(
+            "0 0 0 repeater:{}:3\n",
+            Direction::West.rotate_y(Rotation::Clockwise)
+        )
+    );
+}
+
+#[test]
+#[should_panic(expected = "structures can't be turned upside down")]
+fn mirroring_upside_down_is_rejected() {
+    module()
+        .copy(Region::new(Vec3(0, 0, 0), Vec3(2, 1, 1)))
+        .mirror(Axis::Y);
+}
+
+#[test]
+fn pasting_clears_what_was_in_the_way() {
+    let mut world = World::new();
+    world[Vec3(5, 5, 5)] = Voxel::stone().voxel();
+    world[Vec3(6, 5, 5)] = Voxel::dust().voxel();
+
+    let structure = module().copy(Region::new(Vec3(0, 0, 0), Vec3(2, 1, 1)));
+    world.paste(&structure, Vec3(5, 4, 4));
+
+    assert!(world[Vec3(5, 5, 5)].is_air());
+    assert!(world[Vec3(6, 5, 5)].is_piston());
+}
@@ -0,0 +1,83 @@
+use ruststone::{
+    text::{format_world, parse_voxel, parse_world, ParseError},
+    vec3::Vec3,
+    voxels::Voxel,
+    world::World,
+    CrafterSlot,
+};
+
+#[test]
+fn parses_a_world() {
+    let mut world = parse_world(
+        "# A torch powering some dust.
+        0 0 0 stone
+        0 1 0 torch
+
+        1 0 0 stone
+        1 1 0 dust # the dust
+        ",
+    )
+    .unwrap();
+
+    world.run();
+
+    assert!(world.get(Vec3(0, 1, 0)).unwrap().redstate().is_on());
+    assert!(world.get(Vec3(1, 1, 0)).unwrap().redstate().is_on());
+}
+
+#[test]
+fn formats_what_it_parses() {
+    let text = "\
+0 0 0 stone
+2 0 0 note_block:12
+3 0 0 crafter:1,-,x,-,-,-,-,-,64
+4 0 0 piston:down
+5 0 0 dispenser
+6 0 0 repeater:south:3
+7 0 0 repeater:east
+-1 1 0 dust
+0 1 0 torch
+1 1 0 torch:west
+";
+
+    assert_eq!(format_world(&parse_world(text).unwrap()), text);
+}
+
+#[test]
+fn formats_a_world_without_air() {
+    let mut world = World::new();
+    world[Vec3(0, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 0)] = Voxel::air().voxel();
+    world[Vec3(0, 2, 0)] = Voxel::crafter()
+        .with_slot(4, CrafterSlot::Disabled)
+        .unwrap()
+        .voxel();
+
+    assert_eq!(
+        format_world(&world),
+        "0 0 0 stone\n0 2 0 crafter:-,-,-,-,x,-,-,-,-\n"
+    );
+}
+
+#[test]
+fn rejects_what_it_cannot_read() {
+    assert!(parse_voxel("torch:up").is_err());
+    assert!(parse_voxel("piston:sideways").is_err());
+    assert!(parse_voxel("note_block:25").is_err());
+    assert!(parse_voxel("repeater:up").is_err());
+    assert!(parse_voxel("repeater:north:5").is_err());
+    assert!(parse_voxel("stone:1").is_err());
+    assert!(parse_voxel("lever").is_err());
+
+    assert_eq!(
+        parse_world("0 0 0 stone\n0 1 torch").err(),
+        Some(ParseError {
+            line: 2,
+            message: String::from("\"torch\" is not a coordinate"),
+        })
+    );
+    assert_eq!(
+        parse_world("0 0 0 stone extra").err().map(|e| e.line),
+        Some(1)
+    );
+}
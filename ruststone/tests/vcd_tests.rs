@@ -0,0 +1,102 @@
+use ruststone::{
+    vcd::{Probe, UnknownProbe, VcdRecorder},
+    vec3::Vec3,
+    voxels::Voxel,
+    world::World,
+    RedstoneArena, RedstoneGraph,
+};
+
+fn torch_and_dust() -> World {
+    let mut world = World::new();
+
+    world[Vec3(0, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 0)] = Voxel::torch().voxel();
+    world[Vec3(0, 0, 1)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 1)] = Voxel::dust().voxel();
+
+    world
+}
+
+fn dump(vcd: &VcdRecorder) -> String {
+    let mut out = Vec::new();
+    vcd.write(&mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn probes_are_found_by_position_and_name() {
+    let mut world = torch_and_dust();
+    world.build();
+
+    let probes = [Probe::at(Vec3(0, 1, 0)), Probe::named("dust (0, 1, 1)")];
+    let mut vcd = VcdRecorder::new(world.arena(), &probes).unwrap();
+    world.run_traced(&mut vcd);
+
+    let text = dump(&vcd);
+
+    assert!(text.contains("$timescale 50ms $end\n"));
+    assert!(text.contains("$var wire 5 ! torch_0_1_0 $end\n"));
+    assert!(text.contains("$var wire 1 \" torch_0_1_0_on $end\n"));
+    assert!(text.contains("$var wire 5 # dust_0_1_1 $end\n"));
+    assert!(text.contains("$var wire 1 $ dust_0_1_1_on $end\n"));
+    assert!(text.contains("#0\n$dumpvars\nb0 !\n0\"\nb0 #\n0$\n$end\n"));
+    assert!(text.contains("#2\nb10000 !\n1\"\nb1111 #\n1$\n"));
+}
+
+#[test]
+fn unknown_probes_are_rejected() {
+    let mut world = torch_and_dust();
+    world.build();
+
+    assert_eq!(
+        VcdRecorder::new(world.arena(), &[Probe::at(Vec3(5, 5, 5))]).err(),
+        Some(UnknownProbe(Probe::at(Vec3(5, 5, 5))))
+    );
+    assert_eq!(
+        VcdRecorder::new(world.arena(), &[Probe::named("lever")]).err(),
+        Some(UnknownProbe(Probe::named("lever")))
+    );
+}
+
+#[test]
+fn building_ahead_of_a_run_still_powers_everything_up() {
+    let mut world = torch_and_dust();
+    world.build();
+    world.run();
+
+    assert!(world.get(Vec3(0, 1, 1)).unwrap().redstate().is_on());
+}
+
+#[test]
+fn later_runs_pick_up_where_the_last_one_ended() {
+    let mut world = torch_and_dust();
+    world.build();
+
+    let mut vcd = VcdRecorder::new(world.arena(), &[Probe::at(Vec3(0, 1, 1))]).unwrap();
+    world.run_traced(&mut vcd);
+
+    vcd.start_run_at(vcd.end() + 10);
+    world[Vec3(0, 1, 0)] = Voxel::air().voxel();
+    world.run_traced(&mut vcd);
+
+    let text = dump(&vcd);
+    let off = format!("#{}\nb0 !\n0\"\n", vcd.end());
+
+    assert!(text.contains("#2\nb1111 !\n1\"\n"));
+    assert!(text.contains(&off), "{text}");
+}
+
+#[test]
+fn graphs_are_probed_by_name() {
+    let mut arena = RedstoneArena::new();
+    let torch = arena.make_torch("torch");
+    let dust = arena.make_dust("dust");
+    arena.link(torch, dust).unwrap();
+    arena.add_weighted_edge(dust, torch, 0).unwrap();
+
+    let mut vcd = VcdRecorder::new(&arena, &[Probe::named("dust")]).unwrap();
+    RedstoneGraph::collect(&arena, torch).run_traced(&arena, Default::default(), &mut vcd);
+
+    assert!(dump(&vcd).contains("$var wire 5 ! dust $end\n"));
+    assert!(dump(&vcd).contains("b10000 !\n1\"\n"));
+}
@@ -1,4 +1,4 @@
-use ruststone::vec3::Vec3;
+use ruststone::vec3::{Axis, Direction, Region, Rotation, Vec3};
 
 #[test]
 fn add() {
@@ -29,3 +29,117 @@ fn display() {
 
     assert_eq!(a.to_string(), "(5, 7, 1)");
 }
+
+#[test]
+fn directions_are_single_steps() {
+    for direction in Direction::ALL {
+        assert_eq!(Direction::from_vec3(direction.to_vec3()), Some(direction));
+        assert_eq!(
+            Vec3(3, 4, 5).offset(direction) - Vec3(3, 4, 5),
+            direction.to_vec3()
+        );
+        assert_eq!(
+            direction.opposite().to_vec3(),
+            Vec3(0, 0, 0) - direction.to_vec3()
+        );
+        assert_eq!(
+            Direction::from_name(&direction.to_string()),
+            Some(direction)
+        );
+    }
+
+    assert_eq!(Vec3(0, 0, 0).west(), Vec3(1, 0, 0));
+    assert_eq!(Vec3(0, 0, 0).north(), Vec3(0, 0, -1));
+    assert_eq!(Direction::from_vec3(Vec3(1, 1, 0)), None);
+    assert_eq!(Direction::from_vec3(Vec3(0, 0, 0)), None);
+}
+
+#[test]
+fn rotating_goes_around_the_compass() {
+    let mut direction = Direction::North;
+    let mut seen = Vec::new();
+
+    for _ in 0..4 {
+        seen.push(direction);
+        direction = direction.rotate_y(Rotation::Clockwise);
+    }
+
+    assert_eq!(seen, Direction::HORIZONTAL);
+    assert_eq!(direction, Direction::North);
+
+    for direction in Direction::ALL {
+        let there = direction.rotate_y(Rotation::Clockwise);
+        assert_eq!(there.rotate_y(Rotation::Counterclockwise), direction);
+    }
+
+    assert_eq!(Direction::Up.rotate_y(Rotation::Clockwise), Direction::Up);
+    assert_eq!(
+        Direction::North.rotate_y(Rotation::Counterclockwise),
+        Direction::West
+    );
+}
+
+#[test]
+fn mirroring_flips_only_along_the_axis() {
+    assert_eq!(Direction::West.mirror(Axis::X), Direction::East);
+    assert_eq!(Direction::West.mirror(Axis::Z), Direction::West);
+    assert_eq!(Direction::North.mirror(Axis::Z), Direction::South);
+    assert_eq!(Direction::Up.mirror(Axis::Y), Direction::Down);
+    assert_eq!(Direction::Up.mirror(Axis::X), Direction::Up);
+}
+
+#[test]
+fn regions_span_their_corners() {
+    let region = Region::new(Vec3(2, -1, 0), Vec3(0, 1, 3));
+
+    assert_eq!(region.min(), Vec3(0, -1, 0));
+    assert_eq!(region.max(), Vec3(2, 1, 3));
+    assert_eq!(region.size(), Vec3(3, 3, 4));
+    assert_eq!(region.volume(), 36);
+    assert_eq!(Region::from_size(Vec3(0, -1, 0), Vec3(3, 3, 4)), region);
+
+    assert!(region.contains(Vec3(2, 1, 3)));
+    assert!(region.contains(Vec3(0, -1, 0)));
+    assert!(!region.contains(Vec3(3, 0, 0)));
+    assert!(!region.contains(Vec3(0, -2, 0)));
+}
+
+#[test]
+fn regions_intersect_and_unite() {
+    let a = Region::new(Vec3(0, 0, 0), Vec3(3, 3, 3));
+    let b = Region::new(Vec3(2, 1, -5), Vec3(6, 2, 2));
+
+    assert_eq!(
+        a.intersection(b),
+        Some(Region::new(Vec3(2, 1, 0), Vec3(3, 2, 2)))
+    );
+    assert_eq!(a.union(b), Region::new(Vec3(0, 0, -5), Vec3(6, 3, 3)));
+
+    let far = a.translate(Vec3(10, 0, 0));
+    assert_eq!(a.intersection(far), None);
+    assert_eq!(
+        Region::around([Vec3(1, 2, 3), Vec3(-1, 0, 5)]),
+        Some(Region::new(Vec3(-1, 0, 3), Vec3(1, 2, 5)))
+    );
+    assert_eq!(Region::around([]), None);
+}
+
+#[test]
+fn regions_iterate_by_layer_then_row_then_column() {
+    let region = Region::new(Vec3(0, 0, 0), Vec3(1, 1, 1));
+
+    assert_eq!(
+        region.iter().collect::<Vec<Vec3>>(),
+        [
+            Vec3(0, 0, 0),
+            Vec3(1, 0, 0),
+            Vec3(0, 0, 1),
+            Vec3(1, 0, 1),
+            Vec3(0, 1, 0),
+            Vec3(1, 1, 0),
+            Vec3(0, 1, 1),
+            Vec3(1, 1, 1),
+        ]
+    );
+    assert!(region.iter().all(|vec3| region.contains(vec3)));
+}
@@ -0,0 +1,131 @@
+use ruststone::{
+    text::parse_world,
+    vec3::Vec3,
+    view::{Key, Viewer},
+};
+
+fn viewer() -> Viewer {
+    Viewer::new(
+        parse_world(
+            "0 0 0 stone
+            0 1 0 torch
+            1 0 0 stone
+            1 1 0 dust
+            2 0 0 stone
+            2 1 0 dust",
+        )
+        .unwrap(),
+    )
+}
+
+#[test]
+fn keys_come_from_terminal_input() {
+    assert_eq!(Key::from_bytes(b"\x1b[A"), Some(Key::North));
+    assert_eq!(Key::from_bytes(b"l"), Some(Key::Right));
+    assert_eq!(Key::from_bytes(b" "), Some(Key::Step));
+    assert_eq!(Key::from_bytes(b"q"), Some(Key::Quit));
+    assert_eq!(Key::from_bytes(b"z"), None);
+}
+
+#[test]
+fn shows_the_lowest_layer_first() {
+    let viewer = viewer();
+
+    assert_eq!(viewer.cursor(), Vec3(0, 0, 0));
+    assert!(viewer
+        .render()
+        .starts_with("tick 0  layer y=0\n[#] #  # \n"));
+}
+
+#[test]
+fn stepping_powers_up_the_dust_a_tick_at_a_time() {
+    let mut viewer = viewer();
+    viewer.press(Key::Up);
+
+    viewer.press(Key::Step);
+    assert!(viewer
+        .render()
+        .starts_with("tick 2  layer y=1\n[i] 0  0 \n"));
+
+    viewer.press(Key::Step);
+    assert!(viewer
+        .render()
+        .starts_with("tick 4  layer y=1\n[*] f  e \n"));
+}
+
+#[test]
+fn shows_the_events_of_the_selected_redstone() {
+    let mut viewer = viewer();
+    viewer.press(Key::Up);
+    viewer.press(Key::Right);
+    viewer.press(Key::Settle);
+
+    let frame = viewer.render();
+
+    assert!(frame.contains(&format!("dust {}: 15 (on)\n", Vec3(1, 1, 0))));
+    assert!(frame.contains("went from 0 (off) to 15 (on)"));
+    assert!(!frame.contains(&format!("at {} went", Vec3(0, 1, 0))));
+}
+
+#[test]
+fn settling_moves_the_clock_on_for_what_comes_after() {
+    let mut viewer = viewer();
+    viewer.press(Key::Up);
+    viewer.press(Key::Right);
+    viewer.press(Key::Settle);
+    assert!(viewer.render().starts_with("tick 4  layer y=1\n"));
+
+    viewer.press(Key::Left);
+    viewer.press(Key::Toggle);
+    viewer.press(Key::Right);
+    viewer.press(Key::Step);
+    viewer.press(Key::Step);
+
+    let frame = viewer.render();
+    let ticks: Vec<u64> = frame
+        .lines()
+        .filter_map(|line| line.trim().split_once(':'))
+        .filter_map(|(tick, _)| tick.parse().ok())
+        .collect();
+
+    assert!(frame.starts_with("tick 8  layer y=1\n"));
+    assert!(ticks.iter().any(|&tick| tick >= 4));
+    assert!(ticks.windows(2).all(|w| w[0] <= w[1]), "{frame}");
+}
+
+#[test]
+fn toggling_breaks_the_torch_under_the_cursor() {
+    let mut viewer = viewer();
+    viewer.press(Key::Up);
+    viewer.press(Key::Settle);
+    viewer.press(Key::Toggle);
+    viewer.press(Key::Settle);
+
+    assert!(viewer.render().contains("[.] 0  0 \n"));
+    assert!(!viewer.press(Key::Quit));
+}
+
+#[test]
+fn repeaters_point_the_way_they_power() {
+    let mut viewer = Viewer::new(
+        parse_world(
+            "0 0 0 stone
+            0 1 0 torch
+            1 0 0 stone
+            1 1 0 dust
+            2 0 0 stone
+            2 1 0 repeater:west
+            2 0 1 stone
+            2 1 1 repeater:south:4",
+        )
+        .unwrap(),
+    );
+    viewer.press(Key::Up);
+
+    assert!(viewer
+        .render()
+        .starts_with("tick 0  layer y=1\n[i] 0  > \n .  .  v \n"));
+
+    viewer.press(Key::Settle);
+    assert!(viewer.render().contains("\n[*] f  ▶ \n .  .  v \n"));
+}
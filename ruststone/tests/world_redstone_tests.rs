@@ -1,4 +1,15 @@
-use ruststone::{vec3::Vec3, voxels::Voxel, world::World};
+use std::{
+    sync::{Arc, RwLock},
+    thread,
+};
+
+use ruststone::{
+    trace::{TraceEvent, TraceWriter},
+    vec3::Vec3,
+    voxels::Voxel,
+    world::{NoteEvent, World},
+    Budget, CrafterSlot, Instrument, RedstoneGraph, RedstoneNode, RunOutcome, RuststoneError,
+};
 
 #[test]
 fn cyclic_dusts() {
@@ -126,3 +137,742 @@ fn double_torches() {
     assert!(world.get(Vec3(0, 1, 0)).unwrap().redstate().is_on());
     assert!(world.get(Vec3(1, 1, 0)).unwrap().redstate().is_on());
 }
+
+/// Stacks `height` torches on top of each other, each on a block of its own.
+fn torch_tower(world: &mut World, height: i32) {
+    for level in 0..height {
+        world[Vec3(0, 2 * level, 0)] = Voxel::stone().voxel();
+        world[Vec3(0, 2 * level + 1, 0)] = Voxel::torch().voxel();
+    }
+}
+
+#[test]
+fn torch_towers_alternate() {
+    for height in 1..=8 {
+        let mut world = World::new();
+        torch_tower(&mut world, height);
+
+        assert_eq!(world.run(), RunOutcome::Stable);
+
+        for level in 0..height {
+            let torch = world.get(Vec3(0, 2 * level + 1, 0)).unwrap().redstate();
+            let block = world.get(Vec3(0, 2 * level, 0)).unwrap().redstate();
+
+            assert_eq!(torch.is_on(), level % 2 == 0, "torch {level} of {height}");
+            assert_eq!(block.is_on(), level % 2 == 1, "block {level} of {height}");
+        }
+    }
+}
+
+#[test]
+fn breaking_the_bottom_torch_flips_the_whole_tower() {
+    let height = 7;
+    let mut world = World::new();
+    torch_tower(&mut world, height);
+    world.run();
+
+    world[Vec3(0, 1, 0)] = Voxel::air().voxel();
+    world.run();
+
+    for level in 1..height {
+        let torch = world.get(Vec3(0, 2 * level + 1, 0)).unwrap().redstate();
+        assert_eq!(torch.is_on(), level % 2 == 1, "torch {level}");
+    }
+
+    let top = world.get(Vec3(0, 2 * height - 2, 0)).unwrap().redstate();
+    assert_eq!(top.get_power(), 16);
+}
+
+#[test]
+fn wall_torches_power_the_block_above_but_not_the_one_they_hang_on() {
+    let mut world = World::new();
+
+    world[Vec3(1, 1, 0)] = Voxel::stone().voxel();
+    world[Vec3(1, 2, 0)] = Voxel::torch().voxel();
+    world[Vec3(0, 1, 0)] = Voxel::torch().facing_west().voxel();
+    world[Vec3(0, 2, 0)] = Voxel::stone().voxel();
+    world[Vec3(0, 3, 0)] = Voxel::torch().voxel();
+
+    world.run();
+
+    assert!(world.get(Vec3(0, 1, 0)).unwrap().redstate().is_on());
+    assert!(world.get(Vec3(1, 1, 0)).unwrap().redstate().is_off());
+    assert!(world.get(Vec3(1, 2, 0)).unwrap().redstate().is_on());
+    assert_eq!(world.get(Vec3(0, 2, 0)).unwrap().redstate().get_power(), 16);
+    assert!(world.get(Vec3(0, 3, 0)).unwrap().redstate().is_off());
+}
+
+#[test]
+fn torch_lights_up_the_copper_bulb_next_to_it() {
+    let mut world = World::new();
+
+    world[Vec3(0, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 0)] = Voxel::torch().voxel();
+    world[Vec3(1, 1, 0)] = Voxel::copper_bulb().voxel();
+
+    world.run();
+
+    assert!(world.get(Vec3(0, 1, 0)).unwrap().redstate().is_on());
+    assert!(world.get(Vec3(1, 1, 0)).unwrap().redstate().is_on());
+    assert_eq!(
+        world.get(Vec3(1, 1, 0)).unwrap().comparator_output(),
+        Some(15)
+    );
+}
+
+#[test]
+fn copper_bulb_stays_lit_after_a_pulse() {
+    let mut world = World::new();
+
+    world[Vec3(0, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(1, 1, 0)] = Voxel::copper_bulb().voxel();
+    world.run();
+    assert!(world.get(Vec3(1, 1, 0)).unwrap().redstate().is_off());
+
+    world[Vec3(0, 1, 0)] = Voxel::torch().voxel();
+    world.run();
+    assert!(world.get(Vec3(1, 1, 0)).unwrap().redstate().is_on());
+
+    world[Vec3(0, 1, 0)] = Voxel::air().voxel();
+    world.run();
+    assert!(world.get(Vec3(1, 1, 0)).unwrap().redstate().is_on());
+
+    // The next rising edge toggles it back off.
+    world[Vec3(0, 1, 0)] = Voxel::torch().voxel();
+    world.run();
+    assert!(world.get(Vec3(1, 1, 0)).unwrap().redstate().is_off());
+}
+
+#[test]
+fn dust_on_top_of_a_crafter_fires_it() {
+    let mut world = World::new();
+
+    world[Vec3(0, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 0)] = Voxel::torch().voxel();
+    world[Vec3(1, 0, 0)] = Voxel::crafter()
+        .with_slot(4, CrafterSlot::Filled(1))
+        .unwrap()
+        .with_slot(5, CrafterSlot::Disabled)
+        .unwrap()
+        .voxel();
+    world[Vec3(1, 1, 0)] = Voxel::dust().voxel();
+
+    world.run();
+
+    assert!(world.get(Vec3(1, 1, 0)).unwrap().redstate().is_on());
+    assert!(world.get(Vec3(1, 0, 0)).unwrap().redstate().is_on());
+    assert_eq!(
+        world.get(Vec3(1, 0, 0)).unwrap().comparator_output(),
+        Some(1)
+    );
+}
+
+#[test]
+fn note_blocks_take_their_instrument_from_below() {
+    let mut world = World::new();
+
+    world[Vec3(0, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 0)] = Voxel::torch().voxel();
+    world[Vec3(1, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(1, 1, 0)] = Voxel::note_block().pitch(6).unwrap().voxel();
+    world[Vec3(-1, 1, 0)] = Voxel::note_block().pitch(18).unwrap().voxel();
+
+    world.run();
+
+    assert_eq!(
+        world.notes(),
+        vec![
+            NoteEvent {
+                vec3: Vec3(-1, 1, 0),
+                instrument: Instrument::Harp,
+                pitch: 18,
+                tick: 2,
+            },
+            NoteEvent {
+                vec3: Vec3(1, 1, 0),
+                instrument: Instrument::Basedrum,
+                pitch: 6,
+                tick: 2,
+            },
+        ]
+    );
+}
+
+#[test]
+fn torch_next_to_the_piston_extends_it() {
+    let mut world = World::new();
+
+    world[Vec3(0, 1, 0)] = Voxel::piston().voxel();
+    world[Vec3(1, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(1, 1, 0)] = Voxel::torch().voxel();
+
+    world.run();
+
+    let RedstoneNode::Mechanism(piston) = world.get(Vec3(0, 1, 0)).unwrap().node() else {
+        unreachable!();
+    };
+
+    assert!(piston.is_active());
+    assert!(!piston.is_budded());
+}
+
+#[test]
+fn bud_switch_piston() {
+    let mut world = World::new();
+
+    // The torch powers the air above the piston, but it's only diagonal to the piston itself.
+    world[Vec3(0, 1, 0)] = Voxel::piston().voxel();
+    world[Vec3(1, 1, 0)] = Voxel::stone().voxel();
+    world[Vec3(1, 2, 0)] = Voxel::torch().voxel();
+
+    world.run();
+
+    let RedstoneNode::Mechanism(piston) = world.get(Vec3(0, 1, 0)).unwrap().node() else {
+        unreachable!();
+    };
+
+    assert!(world.get(Vec3(1, 2, 0)).unwrap().redstate().is_on());
+    assert!(piston.is_quasi_powered());
+    assert!(!piston.is_active());
+    assert!(piston.is_budded());
+
+    world.update(Vec3(0, 1, 0));
+
+    let RedstoneNode::Mechanism(piston) = world.get(Vec3(0, 1, 0)).unwrap().node() else {
+        unreachable!();
+    };
+
+    assert!(piston.is_active());
+    assert!(!piston.is_budded());
+}
+
+#[test]
+fn dust_on_top_of_the_block_above_powers_the_dispenser() {
+    let mut world = World::new();
+
+    world[Vec3(0, 1, 0)] = Voxel::stone().voxel();
+    world[Vec3(0, 2, 0)] = Voxel::torch().voxel();
+    world[Vec3(1, 0, 0)] = Voxel::dispenser().voxel();
+    world[Vec3(1, 1, 0)] = Voxel::stone().voxel();
+    world[Vec3(1, 2, 0)] = Voxel::dust().voxel();
+
+    world.run();
+
+    let RedstoneNode::Mechanism(dispenser) = world.get(Vec3(1, 0, 0)).unwrap().node() else {
+        unreachable!();
+    };
+
+    assert!(world.get(Vec3(1, 1, 0)).unwrap().redstate().is_on());
+    assert!(dispenser.is_directly_powered());
+    assert!(dispenser.is_quasi_powered());
+    assert!(dispenser.is_active());
+    assert_eq!(dispenser.fired(), 1);
+}
+
+#[test]
+fn breaking_the_torch_turns_off_the_dust() {
+    let mut world = World::new();
+
+    world[Vec3(0, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 0)] = Voxel::torch().voxel();
+    world[Vec3(0, 0, 1)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 1)] = Voxel::dust().voxel();
+
+    world.run();
+
+    assert!(world.get(Vec3(0, 1, 1)).unwrap().redstate().is_on());
+
+    world[Vec3(0, 1, 0)] = Voxel::air().voxel();
+    world.run();
+
+    assert!(world.get(Vec3(0, 1, 0)).is_none());
+    assert!(world.get(Vec3(0, 1, 1)).unwrap().redstate().is_off());
+}
+
+#[test]
+fn world_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    assert_send_sync::<World>();
+    assert_send_sync::<RedstoneGraph>();
+}
+
+#[test]
+fn world_runs_on_another_thread() {
+    let mut world = World::new();
+
+    world[Vec3(0, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 0)] = Voxel::torch().voxel();
+    world[Vec3(0, 0, 1)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 1)] = Voxel::dust().voxel();
+
+    let world = Arc::new(RwLock::new(world));
+
+    let worker = Arc::clone(&world);
+    thread::spawn(move || worker.write().unwrap().run())
+        .join()
+        .unwrap();
+
+    let world = world.read().unwrap();
+    assert!(world.get(Vec3(0, 1, 1)).unwrap().redstate().is_on());
+}
+
+#[test]
+fn cloned_world_keeps_its_state() {
+    let mut world = World::new();
+
+    world[Vec3(0, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 0)] = Voxel::torch().voxel();
+    world[Vec3(0, 0, 1)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 1)] = Voxel::dust().voxel();
+
+    world.run();
+
+    let snapshot = world.clone();
+
+    world[Vec3(0, 1, 0)] = Voxel::air().voxel();
+    world.run();
+
+    assert!(world.get(Vec3(0, 1, 1)).unwrap().redstate().is_off());
+    assert!(snapshot.get(Vec3(0, 1, 1)).unwrap().redstate().is_on());
+}
+
+#[test]
+fn restoring_a_snapshot_undoes_edits_and_runs() {
+    let mut world = World::new();
+
+    world[Vec3(0, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 0)] = Voxel::torch().voxel();
+    world[Vec3(0, 0, 1)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 1)] = Voxel::dust().voxel();
+    world[Vec3(1, 1, 0)] = Voxel::copper_bulb().voxel();
+
+    world.run();
+    let snapshot = world.snapshot();
+
+    world[Vec3(0, 1, 0)] = Voxel::air().voxel();
+    world.run();
+    world[Vec3(0, 1, 0)] = Voxel::torch().voxel();
+    world.run();
+
+    // Powering it again toggled the bulb off.
+    let bulb_is_lit = |world: &World| match world.get(Vec3(1, 1, 0)).unwrap().node() {
+        RedstoneNode::CopperBulb(bulb) => bulb.is_lit(),
+        _ => unreachable!(),
+    };
+    assert!(!bulb_is_lit(&world));
+
+    world[Vec3(0, 1, 0)] = Voxel::air().voxel();
+    world.run();
+    world.restore(&snapshot);
+
+    assert!(world[Vec3(0, 1, 0)].is_torch());
+    assert!(world.get(Vec3(0, 1, 1)).unwrap().redstate().is_on());
+    assert!(bulb_is_lit(&world));
+
+    // Nothing is left to do, so nothing changes.
+    let mut events = Vec::new();
+    assert_eq!(world.run_traced(&mut events), RunOutcome::Stable);
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, TraceEvent::PowerChanged { .. })));
+}
+
+#[test]
+fn snapshots_taken_mid_run_carry_on_in_another_world() {
+    let mut world = World::new();
+    torch_lines(&mut world, 2);
+
+    world.run_for(1);
+    let snapshot = world.snapshot();
+    assert_eq!(snapshot.tick(), 2);
+
+    let mut other = World::new();
+    other.restore(&snapshot);
+    assert_eq!(other.tick(), 2);
+
+    let changes = |world: &mut World| {
+        let mut events = Vec::new();
+        world.run_for_traced(20, &mut events);
+
+        events
+            .into_iter()
+            .filter_map(|event| match event {
+                TraceEvent::PowerChanged {
+                    tick,
+                    vec3,
+                    new_power,
+                    ..
+                } => Some((tick, vec3, new_power)),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let expected = changes(&mut world);
+    assert!(!expected.is_empty());
+    assert_eq!(changes(&mut other), expected);
+    assert_eq!(other.notes(), world.notes());
+}
+
+fn torch_lines(world: &mut World, lines: i32) {
+    for z in 0..lines {
+        let z = z * 2;
+
+        world[Vec3(0, 0, z)] = Voxel::stone().voxel();
+        world[Vec3(0, 1, z)] = Voxel::torch().voxel();
+
+        for x in 1..=z + 1 {
+            world[Vec3(x, 0, z)] = Voxel::stone().voxel();
+            world[Vec3(x, 1, z)] = Voxel::dust().voxel();
+        }
+
+        world[Vec3(z + 2, 0, z)] = Voxel::note_block().voxel();
+    }
+}
+
+#[test]
+fn disjoint_graphs_run_in_parallel_like_they_do_in_sequence() {
+    let mut sequential = World::new();
+    sequential.set_max_threads(1);
+    torch_lines(&mut sequential, 8);
+    sequential.run();
+
+    let mut parallel = World::new();
+    parallel.set_max_threads(4);
+    torch_lines(&mut parallel, 8);
+    parallel.run();
+
+    for z in 0..16 {
+        for x in 0..20 {
+            for y in 0..2 {
+                let vec3 = Vec3(x, y, z);
+                let redstate = |world: &World| {
+                    world
+                        .get(vec3)
+                        .map(|r| (r.redstate().get_power(), r.redstate().is_forced()))
+                };
+
+                assert_eq!(redstate(&sequential), redstate(&parallel), "{vec3}");
+            }
+        }
+    }
+
+    assert_eq!(sequential.notes(), parallel.notes());
+    assert_eq!(parallel.notes().len(), 8);
+}
+
+#[test]
+fn world_settles_once_every_graph_does() {
+    let mut world = World::new();
+    torch_lines(&mut world, 4);
+
+    assert_eq!(world.run(), RunOutcome::Stable);
+
+    world.set_budget(Budget::new().dispatches(1));
+    world[Vec3(0, 1, 0)] = Voxel::air().voxel();
+
+    assert_eq!(world.run(), RunOutcome::BudgetExhausted);
+}
+
+#[test]
+fn running_for_a_while_picks_up_where_it_stopped() {
+    for threads in [0, 1, 4] {
+        let mut world = World::new();
+        world.set_max_threads(threads);
+        torch_lines(&mut world, 4);
+
+        assert_eq!(world.run_for(1), RunOutcome::BudgetExhausted);
+        assert_eq!(world.tick(), 2);
+        assert!(world.get(Vec3(0, 1, 0)).unwrap().redstate().is_off());
+        assert!(world.notes().is_empty());
+
+        assert_eq!(world.run_for(2), RunOutcome::Stable);
+        assert_eq!(world.tick(), 4);
+        assert!(world.get(Vec3(0, 1, 0)).unwrap().redstate().is_on());
+        assert!(world.get(Vec3(7, 1, 6)).unwrap().redstate().is_on());
+        assert_eq!(world.notes().len(), 4);
+    }
+}
+
+#[test]
+fn edits_between_runs_keep_what_was_pending() {
+    let mut world = World::new();
+    torch_lines(&mut world, 1);
+
+    world.run_for(0);
+    world[Vec3(5, 0, 5)] = Voxel::stone().voxel();
+    world.run_for(4);
+
+    assert!(world.get(Vec3(1, 1, 0)).unwrap().redstate().is_on());
+}
+
+#[test]
+fn traced_events_carry_positions() {
+    let mut world = World::new();
+
+    world[Vec3(0, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 0)] = Voxel::torch().voxel();
+    world[Vec3(0, 0, 1)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 1)] = Voxel::dust().voxel();
+
+    let mut events = Vec::new();
+    world.run_traced(&mut events);
+
+    let dust = world.redstone_id(Vec3(0, 1, 1)).unwrap();
+    let torch = world.redstone_id(Vec3(0, 1, 0)).unwrap();
+
+    assert!(events.contains(&TraceEvent::PowerChanged {
+        tick: 2,
+        redstone: dust,
+        vec3: Some(Vec3(0, 1, 1)),
+        cause: Some(torch),
+        old_power: 0,
+        new_power: 15,
+        was_on: false,
+        is_on: true,
+    }));
+}
+
+#[test]
+fn parallel_runs_trace_like_sequential_ones() {
+    let mut sequential = World::new();
+    sequential.set_max_threads(1);
+    torch_lines(&mut sequential, 6);
+
+    let mut parallel = sequential.clone();
+    parallel.set_max_threads(3);
+
+    let mut sequential_events = Vec::new();
+    sequential.run_traced(&mut sequential_events);
+
+    let mut parallel_events = Vec::new();
+    parallel.run_traced(&mut parallel_events);
+
+    assert_eq!(sequential_events, parallel_events);
+    assert!(sequential_events
+        .windows(2)
+        .all(|w| w[0].tick() <= w[1].tick()));
+}
+
+/// A torch on stone with another stone above it, and dust next to that one, which only some of
+/// the edges between them lead to.
+fn torch_under_a_stone(world: &mut World, z: i32) {
+    world[Vec3(0, 0, z)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, z)] = Voxel::torch().voxel();
+    world[Vec3(0, 2, z)] = Voxel::stone().voxel();
+    world[Vec3(1, 1, z)] = Voxel::stone().voxel();
+    world[Vec3(1, 2, z)] = Voxel::dust().voxel();
+}
+
+#[test]
+fn redstone_graphs_partition_the_arena() {
+    let mut world = World::new();
+    torch_under_a_stone(&mut world, 0);
+    torch_under_a_stone(&mut world, 3);
+    world.build();
+
+    let mut redstones: Vec<_> = world
+        .redstone_graphs()
+        .iter()
+        .flat_map(|rg| rg.redstones().to_vec())
+        .collect();
+    redstones.sort();
+
+    assert_eq!(redstones, world.arena().ids().collect::<Vec<_>>());
+    assert_eq!(world.redstone_graphs().len(), 2);
+}
+
+#[test]
+fn graphs_linked_one_way_trace_the_same_in_parallel() {
+    let mut sequential = World::new();
+    sequential.set_max_threads(1);
+    for z in 0..4 {
+        torch_under_a_stone(&mut sequential, z * 3);
+    }
+
+    let mut parallel = sequential.clone();
+    parallel.set_max_threads(4);
+
+    let mut sequential_events = Vec::new();
+    sequential.run_traced(&mut sequential_events);
+
+    let mut parallel_events = Vec::new();
+    parallel.run_traced(&mut parallel_events);
+
+    assert!(!sequential_events.is_empty());
+    assert_eq!(sequential_events, parallel_events);
+}
+
+#[test]
+fn trace_writer_writes_a_line_per_event() {
+    let mut world = World::new();
+
+    world[Vec3(0, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 0)] = Voxel::torch().voxel();
+
+    let mut writer = TraceWriter::new(Vec::new());
+    world.run_traced(&mut writer);
+
+    let torch = world.redstone_id(Vec3(0, 1, 0)).unwrap();
+    let text = String::from_utf8(writer.finish().unwrap()).unwrap();
+
+    assert!(text.contains(&format!(
+        "2: {torch} at {} went from 0 (off) to 16 (on)\n",
+        Vec3(0, 1, 0)
+    )));
+}
+
+#[test]
+fn validate_reports_what_cannot_be_simulated() {
+    let mut world = World::new();
+
+    world[Vec3(0, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 0)] = Voxel::torch().voxel();
+    world[Vec3(1, 1, 0)] = Voxel::dust().voxel();
+    world[Vec3(3, 1, 0)] = Voxel::torch().facing_west().voxel();
+
+    assert_eq!(
+        world.validate(),
+        [
+            RuststoneError::UnsupportedDust {
+                vec3: Vec3(1, 1, 0)
+            },
+            RuststoneError::FloatingTorch {
+                vec3: Vec3(3, 1, 0)
+            },
+        ]
+    );
+
+    // Unsupported dust still carries power.
+    world.run();
+    assert!(world.get(Vec3(1, 1, 0)).unwrap().redstate().is_on());
+
+    world[Vec3(1, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(4, 1, 0)] = Voxel::stone().voxel();
+    assert!(world.validate().is_empty());
+}
+
+#[test]
+fn removing_the_support_drops_torches_and_dust() {
+    let mut world = World::new();
+
+    world[Vec3(0, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 0)] = Voxel::torch().voxel();
+    world[Vec3(1, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(1, 1, 0)] = Voxel::dust().voxel();
+    world[Vec3(3, 2, 0)] = Voxel::stone().voxel();
+    world[Vec3(2, 2, 0)] = Voxel::torch().facing_west().voxel();
+    world.run();
+
+    world[Vec3(0, 0, 0)] = Voxel::air().voxel();
+    world[Vec3(3, 2, 0)] = Voxel::air().voxel();
+
+    let mut events = Vec::new();
+    world.run_traced(&mut events);
+
+    assert!(world[Vec3(0, 1, 0)].is_air());
+    assert!(world[Vec3(2, 2, 0)].is_air());
+    assert!(world[Vec3(1, 1, 0)].is_dust());
+    assert!(world.get(Vec3(1, 1, 0)).unwrap().redstate().is_off());
+
+    let dropped: Vec<Vec3> = events
+        .iter()
+        .filter_map(|event| match *event {
+            TraceEvent::Dropped { vec3, .. } => Some(vec3),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(dropped, [Vec3(0, 1, 0), Vec3(2, 2, 0)]);
+
+    world[Vec3(1, 0, 0)] = Voxel::air().voxel();
+    world.run();
+    assert!(world[Vec3(1, 1, 0)].is_air());
+}
+
+#[test]
+fn drops_keep_the_tick_they_happened_at_until_traced() {
+    let mut world = World::new();
+
+    world[Vec3(0, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 0)] = Voxel::torch().voxel();
+    world[Vec3(1, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(1, 1, 0)] = Voxel::torch().voxel();
+
+    // Settling steps the world on just like running for a while does.
+    world.run();
+    assert_eq!(world.tick(), 4);
+
+    world[Vec3(0, 0, 0)] = Voxel::air().voxel();
+    world.run_for(4);
+    world[Vec3(1, 0, 0)] = Voxel::air().voxel();
+
+    let mut events = Vec::new();
+    world.run_traced(&mut events);
+
+    assert_eq!(
+        events,
+        [
+            TraceEvent::Dropped {
+                tick: 4,
+                vec3: Vec3(0, 1, 0)
+            },
+            TraceEvent::Dropped {
+                tick: 8,
+                vec3: Vec3(1, 1, 0)
+            }
+        ]
+    );
+    assert!(world.validate().is_empty());
+}
+
+#[test]
+fn untraced_drops_are_validated_once() {
+    let mut world = World::new();
+
+    world[Vec3(0, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(0, 1, 0)] = Voxel::torch().voxel();
+    world.run();
+
+    world[Vec3(0, 0, 0)] = Voxel::air().voxel();
+    world.run();
+
+    assert_eq!(
+        world.validate(),
+        [RuststoneError::Dropped {
+            vec3: Vec3(0, 1, 0)
+        }]
+    );
+    assert!(world.validate().is_empty());
+
+    let mut events = Vec::new();
+    world.run_traced(&mut events);
+    assert!(events.is_empty());
+}
+
+#[test]
+fn placing_needs_a_solid_block_to_sit_on() {
+    let mut world = World::new();
+
+    assert_eq!(
+        world.place(Vec3(0, 1, 0), Voxel::torch().voxel()),
+        Err(RuststoneError::FloatingTorch {
+            vec3: Vec3(0, 1, 0)
+        })
+    );
+    assert_eq!(
+        world.place(Vec3(1, 1, 0), Voxel::dust().voxel()),
+        Err(RuststoneError::UnsupportedDust {
+            vec3: Vec3(1, 1, 0)
+        })
+    );
+    assert!(world[Vec3(0, 1, 0)].is_air());
+
+    world.place(Vec3(0, 0, 0), Voxel::stone().voxel()).unwrap();
+    world.place(Vec3(0, 1, 0), Voxel::torch().voxel()).unwrap();
+    world
+        .place(Vec3(1, 0, 0), Voxel::torch().facing_east().voxel())
+        .unwrap();
+
+    assert!(world.validate().is_empty());
+}
@@ -1,4 +1,9 @@
-use ruststone::{vec3::Vec3, voxels::Voxel, world::World};
+use ruststone::{
+    vec3::{Direction, Region, Vec3},
+    voxels::Voxel,
+    world::World,
+    CrafterSlot, RuststoneError,
+};
 
 #[test]
 fn subscripting() {
@@ -36,3 +41,130 @@ fn identical_vec3s() {
     assert!(world[Vec3(0, 1, 2)].is_torch());
     assert!(world[Vec3(2, 1, 0)].is_stone());
 }
+
+#[test]
+fn bounds_leave_out_air() {
+    let mut world = World::new();
+    assert_eq!(world.bounds(), None);
+
+    world[Vec3(-2, 0, 1)] = Voxel::stone().voxel();
+    world[Vec3(3, 4, -1)] = Voxel::dust().voxel();
+    world[Vec3(9, 9, 9)] = Voxel::air().voxel();
+
+    assert_eq!(
+        world.bounds(),
+        Some(Region::new(Vec3(-2, 0, -1), Vec3(3, 4, 1)))
+    );
+}
+
+#[test]
+fn iterating_a_region_includes_air() {
+    let mut world = World::new();
+    world[Vec3(1, 0, 0)] = Voxel::stone().voxel();
+
+    let voxels: Vec<(Vec3, bool)> = world
+        .iter_region(Region::new(Vec3(0, 0, 0), Vec3(2, 0, 0)))
+        .map(|(vec3, voxel)| (vec3, voxel.is_stone()))
+        .collect();
+
+    assert_eq!(
+        voxels,
+        [
+            (Vec3(0, 0, 0), false),
+            (Vec3(1, 0, 0), true),
+            (Vec3(2, 0, 0), false)
+        ]
+    );
+}
+
+#[test]
+fn voxels_keep_across_sections() {
+    let mut world = World::new();
+    let region = Region::new(Vec3(-20, -3, -17), Vec3(19, 2, 18));
+
+    // More kinds of voxel than fit in four bits, so that the indices have to be packed wider.
+    let voxel = |Vec3(x, y, z): Vec3| match (x + y + z).rem_euclid(27) {
+        25 => Voxel::air().voxel(),
+        26 => Voxel::stone().voxel(),
+        pitch => Voxel::note_block().pitch(pitch as u8).unwrap().voxel(),
+    };
+
+    for vec3 in region.iter() {
+        world[vec3] = voxel(vec3);
+    }
+
+    for vec3 in region.iter() {
+        assert!(world[vec3] == voxel(vec3), "{vec3}");
+    }
+    assert!(world[Vec3(20, 0, 0)].is_air());
+    assert_eq!(world.sections(), 4 * 2 * 4);
+    assert_eq!(
+        world.voxels().count(),
+        region.iter().filter(|&v| !voxel(v).is_air()).count()
+    );
+}
+
+#[test]
+fn sections_of_nothing_but_air_are_not_kept() {
+    let mut world = World::new();
+
+    world[Vec3(0, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(15, 15, 15)] = Voxel::stone().voxel();
+    world[Vec3(16, 0, 0)] = Voxel::stone().voxel();
+    world[Vec3(-1, 0, 0)] = Voxel::stone().voxel();
+    assert_eq!(world.sections(), 3);
+
+    world[Vec3(16, 0, 0)] = Voxel::air().voxel();
+    world[Vec3(-1, 0, 0)] = Voxel::air().voxel();
+    world.build();
+
+    assert_eq!(world.sections(), 1);
+    assert_eq!(world.voxels().count(), 2);
+}
+
+#[test]
+fn changing_a_voxel_in_place_leaves_the_same_kind_elsewhere_alone() {
+    let mut world = World::new();
+
+    world[Vec3(0, 0, 0)] = Voxel::note_block().voxel();
+    world[Vec3(1, 0, 0)] = Voxel::note_block().voxel();
+
+    if let Voxel::NoteBlock(note_block) = &mut world[Vec3(0, 0, 0)] {
+        *note_block = Voxel::note_block().pitch(3).unwrap();
+    }
+    world[Vec3(2, 0, 0)] = Voxel::note_block().voxel();
+
+    assert!(world[Vec3(0, 0, 0)] == Voxel::note_block().pitch(3).unwrap().voxel());
+    assert!(world[Vec3(1, 0, 0)] == Voxel::note_block().voxel());
+    assert!(world[Vec3(2, 0, 0)] == Voxel::note_block().voxel());
+}
+
+#[test]
+fn voxels_refuse_what_they_cant_be() {
+    assert_eq!(
+        Voxel::torch().facing(Direction::Up).err(),
+        Some(RuststoneError::NotHorizontal {
+            kind: "torch",
+            facing: Direction::Up
+        })
+    );
+    assert_eq!(
+        Voxel::repeater().facing(Direction::Down).err(),
+        Some(RuststoneError::NotHorizontal {
+            kind: "repeater",
+            facing: Direction::Down
+        })
+    );
+    assert_eq!(
+        Voxel::repeater().delay(5).err(),
+        Some(RuststoneError::InvalidDelay { delay: 5 })
+    );
+    assert_eq!(
+        Voxel::note_block().pitch(25).err(),
+        Some(RuststoneError::InvalidPitch { pitch: 25 })
+    );
+    assert_eq!(
+        Voxel::crafter().with_slot(9, CrafterSlot::Disabled).err(),
+        Some(RuststoneError::NoSuchSlot { idx: 9 })
+    );
+}